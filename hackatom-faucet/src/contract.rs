@@ -1,17 +1,28 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
-use cw2::set_contract_version;
+use cw2::{get_contract_version, set_contract_version};
 use execute::{
-    execute_claim, execute_set_admin, execute_update_rate_limit, execute_update_token_config,
+    execute_airdrop, execute_claim, execute_deposit, execute_grant_role, execute_receive,
+    execute_revoke_role, execute_set_admin, execute_set_claim_window,
+    execute_set_depositor_withdrawals, execute_set_paused, execute_set_tiers,
+    execute_update_rate_limit, execute_update_token_config, execute_withdraw,
+    execute_withdraw_vested,
 };
 use query::{
-    query_admin, query_balance, query_can_user_claim, query_rate_limit, query_token_configs,
+    query_admin, query_all_claimers, query_balance, query_can_user_claim, query_claim_history,
+    query_claim_window, query_claimers_in_cooldown, query_claims_in_range, query_depositor,
+    query_depositors, query_rate_limit, query_roles, query_stats, query_token_configs,
     query_user_info,
 };
 
-use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
-use crate::state::{State, DEFAULT_RATE_LIMIT, STATE};
+use crate::error::ContractError;
+use crate::helpers::{validate_address, validate_denom, validate_token};
+use crate::migrations;
+use crate::msg::{
+    ExecuteMsg, InstantiateMsg, MigrateMsg, PowChallengeResponse, QueryMsg, SudoMsg, TiersResponse,
+};
+use crate::state::{State, DEFAULT_RATE_LIMIT, STATE, TIERS};
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:hackatom-faucet";
@@ -23,15 +34,27 @@ pub fn instantiate(
     _env: Env,
     info: MessageInfo,
     msg: InstantiateMsg,
-) -> StdResult<Response> {
-    let admin = msg.admin.unwrap_or(info.sender.clone());
+) -> Result<Response, ContractError> {
+    let admin = match msg.admin {
+        Some(admin) => validate_address(deps.api, admin.as_str())?,
+        None => info.sender.clone(),
+    };
+    for token in msg.tokens.iter() {
+        validate_token(deps.api, token)?;
+    }
 
     let rate_limit = msg.rate_limit_seconds.unwrap_or(DEFAULT_RATE_LIMIT);
 
+    execute::validate_claim_window(msg.opens_at, msg.closes_at)?;
+
     let state = State {
         admin,
         tokens: msg.tokens,
         rate_limit_seconds: rate_limit,
+        depositor_withdrawals: false,
+        paused: false,
+        opens_at: msg.opens_at,
+        closes_at: msg.closes_at,
     };
 
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
@@ -45,27 +68,145 @@ pub fn instantiate(
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> StdResult<Response> {
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
     match msg {
-        ExecuteMsg::Claim {} => execute_claim(deps, env, info),
+        ExecuteMsg::Claim { nonce } => execute_claim(deps, env, info, nonce),
         ExecuteMsg::UpdateTokenConfig {
             token_index,
             new_config,
         } => execute_update_token_config(deps, info, token_index, new_config),
         ExecuteMsg::UpdateRateLimit { seconds } => execute_update_rate_limit(deps, info, seconds),
         ExecuteMsg::SetAdmin { admin } => execute_set_admin(deps, info, admin),
+        ExecuteMsg::SetTiers { tiers } => execute_set_tiers(deps, info, tiers),
+        ExecuteMsg::SetPowConfig { config } => {
+            crate::pow::execute_set_pow_config(deps, env, info, config)
+        }
+        ExecuteMsg::RotatePowSeed {} => crate::pow::execute_rotate_pow_seed(deps, env),
+        ExecuteMsg::GrantRole { address, role } => execute_grant_role(deps, info, address, role),
+        ExecuteMsg::RevokeRole { address, role } => execute_revoke_role(deps, info, address, role),
+        ExecuteMsg::Receive(msg) => execute_receive(deps, info, msg),
+        ExecuteMsg::Deposit {} => execute_deposit(deps, info),
+        ExecuteMsg::Withdraw { denom, amount } => execute_withdraw(deps, env, info, denom, amount),
+        ExecuteMsg::WithdrawVested {} => execute_withdraw_vested(deps, env, info),
+        ExecuteMsg::Airdrop { recipients } => execute_airdrop(deps, env, info, recipients),
+        ExecuteMsg::SetDepositorWithdrawals { enabled } => {
+            execute_set_depositor_withdrawals(deps, info, enabled)
+        }
+        ExecuteMsg::Pause {} => execute_set_paused(deps, info, true),
+        ExecuteMsg::Unpause {} => execute_set_paused(deps, info, false),
+        ExecuteMsg::SetClaimWindow {
+            opens_at,
+            closes_at,
+        } => execute_set_claim_window(deps, info, opens_at, closes_at),
+        #[cfg(feature = "neutron")]
+        ExecuteMsg::SetSybilGate { gate } => {
+            crate::neutron::execute_set_sybil_gate(deps, info, gate)
+        }
+        #[cfg(feature = "neutron")]
+        ExecuteMsg::LinkHubAccount { query_id } => {
+            crate::neutron::execute_link_hub_account(deps, info, query_id)
+        }
     }
 }
 
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    let stored = get_contract_version(deps.storage)?;
+    if stored.contract != CONTRACT_NAME {
+        return Err(ContractError::InvalidMigrationContract {
+            contract: stored.contract,
+        });
+    }
+
+    let from = parse_version(&stored.version)?;
+    let to = parse_version(CONTRACT_VERSION)?;
+    if from > to {
+        return Err(ContractError::MigrationDowngrade {
+            from: from.to_string(),
+            to: to.to_string(),
+        });
+    }
+
+    // Run every storage migration newer than the stored version, oldest first
+    if from < semver::Version::new(0, 2, 0) {
+        migrations::v0_2_0::migrate(deps.storage)?;
+    }
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "migrate")
+        .add_attribute("from_version", from.to_string())
+        .add_attribute("to_version", to.to_string()))
+}
+
+/// Lets chain governance recover a faucet, e.g. when the admin key is lost. Runs the
+/// same logic as the admin messages without checking a sender.
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn sudo(deps: DepsMut, env: Env, msg: SudoMsg) -> Result<Response, ContractError> {
+    match msg {
+        SudoMsg::ForceSetAdmin { admin } => {
+            let admin = validate_address(deps.api, &admin)?;
+            execute::set_admin(deps, admin)
+        }
+        SudoMsg::ForceUpdateConfig {
+            tokens,
+            rate_limit_seconds,
+            paused,
+        } => execute::update_config(deps, tokens, rate_limit_seconds, paused),
+        SudoMsg::ForceWithdraw {
+            denom,
+            amount,
+            recipient,
+        } => {
+            let recipient = validate_address(deps.api, &recipient)?;
+            validate_denom(deps.api, &denom)?;
+            execute::withdraw_to(deps, env, recipient, denom, amount, "sudo")
+        }
+    }
+}
+
+fn parse_version(version: &str) -> Result<semver::Version, ContractError> {
+    version
+        .parse()
+        .map_err(|err: semver::Error| ContractError::InvalidVersion {
+            version: version.to_string(),
+            reason: err.to_string(),
+        })
+}
+
 pub mod execute {
-    use cosmwasm_std::{coin, Addr, BankMsg, CosmosMsg, WasmMsg};
-    use cw20::{Cw20ExecuteMsg, Denom};
+    use cosmwasm_std::{
+        from_json, Addr, BankMsg, Coin, CosmosMsg, Decimal, Order, Storage, Timestamp, Uint128,
+    };
+    use cw20::{Cw20ReceiveMsg, Denom};
 
-    use crate::state::{TokenConfig, UserInfo, NATIVE_DENOM, USER_CLAIMS};
+    use crate::events::{claim_event, config_update_event, SkipReason};
+    use crate::helpers::{
+        assert_role, claim_blocked_reason, claim_multiplier, query_available_balance, transfer_msg,
+    };
+    use crate::msg::ReceiveMsg;
+    use crate::oracle::token_amount;
+    use crate::state::{
+        claims, denom_key, user_claims, ClaimRecord, Role, Tier, TierRequirement, TokenAmount,
+        TokenConfig, UserInfo, VestingSchedule, CLAIM_STATS, DEPOSITS, DEPOSIT_TOTALS, DISTRIBUTED,
+        MAX_AIRDROP_RECIPIENTS, MAX_TIERS, NATIVE_DENOM, ROLES, TIERS, VESTING, VESTING_SEQ,
+        VESTING_TOTALS,
+    };
 
     use super::*;
 
-    pub fn execute_claim(deps: DepsMut, env: Env, info: MessageInfo) -> StdResult<Response> {
+    pub fn execute_claim(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        nonce: Option<u64>,
+    ) -> Result<Response, ContractError> {
         // Load state using Item
         let state = STATE.load(deps.storage)?;
 
@@ -73,98 +214,112 @@ pub mod execute {
         let current_time = env.block.time.seconds();
 
         // Check user's last claim time using Map
-        let user_info = USER_CLAIMS.may_load(deps.storage, &user_addr)?;
-
-        // Check if user is rate limited
-        if let Some(user_data) = &user_info {
-            let time_since_last_claim = current_time.saturating_sub(user_data.last_claim_time);
-            if time_since_last_claim < state.rate_limit_seconds {
-                let seconds_remaining = state.rate_limit_seconds - time_since_last_claim;
-                return Err(cosmwasm_std::StdError::generic_err(format!(
-                    "Rate limit exceeded. You can claim again in {} seconds",
-                    seconds_remaining
-                )));
-            }
+        let user_info = user_claims().may_load(deps.storage, &user_addr)?;
+
+        // Check if the faucet is open and the user is not rate limited
+        if let Some(reason) = claim_blocked_reason(&state, user_info.as_ref(), env.block.time) {
+            return Err(reason.into());
         }
 
+        // Check the proof of work when the challenge is enabled
+        crate::pow::check_pow(deps.storage, &env, &user_addr, nonce)?;
+
+        // Check the claimer's Cosmos Hub account when the sybil gate is enabled
+        #[cfg(feature = "neutron")]
+        crate::neutron::check_sybil_gate(deps.as_ref(), &env, &user_addr)?;
+
         // Update or create user claim record
         let updated_user_info = UserInfo {
             last_claim_time: current_time,
         };
-        USER_CLAIMS.save(deps.storage, &user_addr, &updated_user_info)?;
+        user_claims().save(deps.storage, &user_addr, &updated_user_info)?;
+
+        // Scale amounts by the best tier the claimer qualifies for
+        let tiers = TIERS.may_load(deps.storage)?.unwrap_or_default();
+        let multiplier = claim_multiplier(deps.as_ref(), &tiers, &user_addr)?;
 
         // Prepare transfer messages for each token
         let mut messages: Vec<CosmosMsg> = vec![];
-        let mut distributed_tokens: Vec<String> = vec![];
+        let mut sent: Vec<TokenAmount> = vec![];
+        let mut streamed: Vec<TokenAmount> = vec![];
+        let mut skipped: Vec<(Denom, SkipReason)> = vec![];
 
         for token_config in state.tokens.iter() {
-            let token_msg = match &token_config.denom {
-                Denom::Native(denom) if denom == NATIVE_DENOM => {
-                    // Create bank send message for native token (untrn)
-                    let msg = CosmosMsg::Bank(BankMsg::Send {
-                        to_address: user_addr.to_string(),
-                        amount: vec![coin(token_config.amount.u128(), denom)],
-                    });
-
-                    // Check if contract has enough balance for this native token
-                    let balance = deps
-                        .querier
-                        .query_balance(env.contract.address.clone(), denom)?;
-                    if balance.amount >= token_config.amount {
-                        distributed_tokens.push(format!("{} {}", token_config.amount, denom));
-                        Some(msg)
-                    } else {
-                        None // Skip if insufficient balance
-                    }
-                }
-                Denom::Cw20(contract_addr) => {
-                    // Create CW20 transfer message
-                    let cw20_addr = contract_addr;
-
-                    // Query CW20 balance of the contract
-                    let balance: cw20::BalanceResponse = deps.querier.query_wasm_smart(
-                        cw20_addr,
-                        &cw20::Cw20QueryMsg::Balance {
-                            address: env.contract.address.to_string(),
-                        },
-                    )?;
+            // Skip native tokens other than untrn
+            if matches!(&token_config.denom, Denom::Native(denom) if denom != NATIVE_DENOM) {
+                continue;
+            }
 
-                    if balance.balance >= token_config.amount {
-                        let msg = CosmosMsg::Wasm(WasmMsg::Execute {
-                            contract_addr: cw20_addr.to_string(),
-                            msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
-                                recipient: user_addr.to_string(),
-                                amount: token_config.amount,
-                            })?,
-                            funds: vec![],
-                        });
-                        distributed_tokens
-                            .push(format!("{} CW20:{}", token_config.amount, contract_addr));
-                        Some(msg)
-                    } else {
-                        None // Skip if insufficient balance
-                    }
+            // A bad price only holds back this token, not the whole claim
+            let amount = match token_amount(deps.as_ref(), token_config) {
+                Ok(amount) => amount.mul_floor(multiplier),
+                Err(err) => {
+                    let reason = match err {
+                        ContractError::PriceOutOfBounds { .. } => SkipReason::PriceOutOfBounds,
+                        _ => SkipReason::PriceUnavailable,
+                    };
+                    skipped.push((token_config.denom.clone(), reason));
+                    continue;
                 }
-                _ => None, // Skip other token types
             };
 
-            if let Some(msg) = token_msg {
-                messages.push(msg);
+            // Skip if the faucet can't cover this token after vesting reservations
+            let balance =
+                query_available_balance(deps.as_ref(), &env.contract.address, &token_config.denom)?;
+            if balance < amount {
+                skipped.push((token_config.denom.clone(), SkipReason::InsufficientFunds));
+                continue;
+            }
+
+            let token = TokenAmount {
+                denom: token_config.denom.clone(),
+                amount,
+            };
+            match &token_config.stream {
+                // Streamed tokens stay in the faucet until they vest
+                Some(stream) => {
+                    create_vesting_schedule(
+                        deps.storage,
+                        &user_addr,
+                        token.clone(),
+                        current_time,
+                        stream.duration_seconds,
+                    )?;
+                    streamed.push(token);
+                }
+                None => {
+                    messages.push(transfer_msg(&token.denom, &user_addr, amount)?);
+                    sent.push(token);
+                }
             }
         }
 
         // Return error if no tokens were distributed
-        if messages.is_empty() {
-            return Err(cosmwasm_std::StdError::generic_err(
-                "Insufficient funds in faucet for all token types",
-            ));
+        if sent.is_empty() && streamed.is_empty() {
+            return Err(ContractError::NothingToClaim {});
         }
 
-        Ok(Response::new()
+        // Append to the claim log
+        let claim_id = record_claim(
+            deps.storage,
+            &user_addr,
+            current_time,
+            user_info.is_none(),
+            sent.iter().chain(streamed.iter()).cloned().collect(),
+        )?;
+
+        let mut response = Response::new()
             .add_messages(messages)
+            .add_event(claim_event(
+                claim_id, &user_addr, multiplier, &sent, &streamed, &skipped,
+            ))
             .add_attribute("action", "claim")
-            .add_attribute("recipient", user_addr.to_string())
-            .add_attribute("distributed_tokens", distributed_tokens.join(", ")))
+            .add_attribute("recipient", user_addr.to_string());
+        if multiplier != Decimal::one() {
+            response = response.add_attribute("multiplier", multiplier.to_string());
+        }
+
+        Ok(response)
     }
 
     pub fn execute_update_token_config(
@@ -172,30 +327,39 @@ pub mod execute {
         info: MessageInfo,
         token_index: u32,
         new_config: TokenConfig,
-    ) -> StdResult<Response> {
+    ) -> Result<Response, ContractError> {
         // Load state using Item
-        let mut state = STATE.load(deps.storage)?;
+        let state = STATE.load(deps.storage)?;
 
-        // Check if sender is admin
-        if info.sender != state.admin {
-            return Err(cosmwasm_std::StdError::generic_err("Unauthorized"));
-        }
+        // Check if sender is an operator
+        assert_role(deps.storage, &state, &info.sender, Role::Operator)?;
 
         // Check if token index is valid
         if token_index as usize >= state.tokens.len() {
-            return Err(cosmwasm_std::StdError::generic_err("Invalid token index"));
+            return Err(ContractError::InvalidTokenIndex {});
         }
 
         // Update token config
-        state.tokens[token_index as usize] = new_config.clone();
+        let mut tokens = state.tokens;
+        tokens[token_index as usize] = new_config.clone();
+        save_tokens(deps, tokens)?;
 
-        // Save updated state
-        STATE.save(deps.storage, &state)?;
+        let mut event = config_update_event("token_config")
+            .add_attribute("token_index", token_index.to_string())
+            .add_attribute("denom", denom_key(&new_config.denom))
+            .add_attribute("amount", new_config.amount.to_string());
+        if let Some(stream) = &new_config.stream {
+            event = event.add_attribute(
+                "stream_duration_seconds",
+                stream.duration_seconds.to_string(),
+            );
+        }
 
         Ok(Response::new()
+            .add_event(event)
             .add_attribute("action", "update_token_config")
             .add_attribute("token_index", token_index.to_string())
-            .add_attribute("new_denom", format!("{:?}", new_config.denom))
+            .add_attribute("new_denom", denom_key(&new_config.denom))
             .add_attribute("new_amount", new_config.amount.to_string()))
     }
 
@@ -203,50 +367,760 @@ pub mod execute {
         deps: DepsMut,
         info: MessageInfo,
         seconds: u64,
-    ) -> StdResult<Response> {
+    ) -> Result<Response, ContractError> {
         // Load state using Item
-        let mut state = STATE.load(deps.storage)?;
-
-        // Check if sender is admin
-        if info.sender != state.admin {
-            return Err(cosmwasm_std::StdError::generic_err("Unauthorized"));
-        }
+        let state = STATE.load(deps.storage)?;
 
-        // Update rate limit
-        state.rate_limit_seconds = seconds;
+        // Check if sender is an operator
+        assert_role(deps.storage, &state, &info.sender, Role::Operator)?;
 
-        // Save updated state
-        STATE.save(deps.storage, &state)?;
+        save_rate_limit(deps.storage, seconds)?;
 
         Ok(Response::new()
+            .add_event(
+                config_update_event("rate_limit")
+                    .add_attribute("rate_limit_seconds", seconds.to_string()),
+            )
             .add_attribute("action", "update_rate_limit")
             .add_attribute("seconds", seconds.to_string()))
     }
 
+    /// Replace any of the token configs, rate limit and pause flag without checking
+    /// the sender
+    pub(crate) fn update_config(
+        mut deps: DepsMut,
+        tokens: Option<Vec<TokenConfig>>,
+        rate_limit_seconds: Option<u64>,
+        paused: Option<bool>,
+    ) -> Result<Response, ContractError> {
+        if let Some(tokens) = tokens {
+            save_tokens(deps.branch(), tokens)?;
+        }
+        if let Some(seconds) = rate_limit_seconds {
+            save_rate_limit(deps.storage, seconds)?;
+        }
+        if let Some(paused) = paused {
+            save_paused(deps.storage, paused)?;
+        }
+        let state = STATE.load(deps.storage)?;
+
+        Ok(Response::new()
+            .add_event(
+                config_update_event("config")
+                    .add_attribute("tokens_count", state.tokens.len().to_string())
+                    .add_attribute("rate_limit_seconds", state.rate_limit_seconds.to_string())
+                    .add_attribute("paused", state.paused.to_string()),
+            )
+            .add_attribute("action", "update_config")
+            .add_attribute("tokens_count", state.tokens.len().to_string())
+            .add_attribute("rate_limit", state.rate_limit_seconds.to_string())
+            .add_attribute("paused", state.paused.to_string()))
+    }
+
+    /// Validate and store `tokens` in place of the current token configs
+    fn save_tokens(deps: DepsMut, tokens: Vec<TokenConfig>) -> Result<(), ContractError> {
+        for token in tokens.iter() {
+            validate_token(deps.api, token)?;
+        }
+        let mut state = STATE.load(deps.storage)?;
+        state.tokens = tokens;
+        STATE.save(deps.storage, &state)?;
+        Ok(())
+    }
+
+    fn save_rate_limit(storage: &mut dyn Storage, seconds: u64) -> StdResult<()> {
+        let mut state = STATE.load(storage)?;
+        state.rate_limit_seconds = seconds;
+        STATE.save(storage, &state)
+    }
+
+    fn save_paused(storage: &mut dyn Storage, paused: bool) -> StdResult<()> {
+        let mut state = STATE.load(storage)?;
+        state.paused = paused;
+        STATE.save(storage, &state)
+    }
+
     pub fn execute_set_admin(
         deps: DepsMut,
         info: MessageInfo,
         admin: String,
-    ) -> StdResult<Response> {
+    ) -> Result<Response, ContractError> {
         // Load state using Item
-        let mut state = STATE.load(deps.storage)?;
+        let state = STATE.load(deps.storage)?;
 
-        // Check if sender is current admin
-        if info.sender != state.admin {
-            return Err(cosmwasm_std::StdError::generic_err("Unauthorized"));
-        }
+        // Check if sender is admin
+        assert_role(deps.storage, &state, &info.sender, Role::Owner)?;
+
+        let admin = validate_address(deps.api, &admin)?;
+        set_admin(deps, admin)
+    }
+
+    /// Transfer the admin role without checking the sender
+    pub(crate) fn set_admin(deps: DepsMut, new_admin: Addr) -> Result<Response, ContractError> {
+        // Load state using Item
+        let mut state = STATE.load(deps.storage)?;
 
         // Update admin
-        let new_admin = Addr::unchecked(admin);
         state.admin = new_admin.clone();
 
         // Save updated state
         STATE.save(deps.storage, &state)?;
 
         Ok(Response::new()
+            .add_event(config_update_event("admin").add_attribute("admin", new_admin.to_string()))
             .add_attribute("action", "set_admin")
             .add_attribute("admin", new_admin.to_string()))
     }
+
+    pub fn execute_set_tiers(
+        deps: DepsMut,
+        info: MessageInfo,
+        mut tiers: Vec<Tier>,
+    ) -> Result<Response, ContractError> {
+        let state = STATE.load(deps.storage)?;
+
+        // Check if sender is an operator
+        assert_role(deps.storage, &state, &info.sender, Role::Operator)?;
+
+        if tiers.len() > MAX_TIERS {
+            return Err(ContractError::TooManyTiers { max: MAX_TIERS });
+        }
+        for tier in tiers.iter() {
+            if tier.multiplier < Decimal::one() {
+                return Err(ContractError::InvalidTierMultiplier {});
+            }
+            let contract = match &tier.requirement {
+                TierRequirement::Cw721 { collection } => collection,
+                TierRequirement::Cw20 { token, .. } => token,
+            };
+            validate_address(deps.api, contract.as_str())?;
+        }
+
+        // Claims stop at the first matching tier, so check the best ones first
+        tiers.sort_by_key(|tier| std::cmp::Reverse(tier.multiplier));
+        TIERS.save(deps.storage, &tiers)?;
+
+        let mut event =
+            config_update_event("tiers").add_attribute("tiers_count", tiers.len().to_string());
+        for (index, tier) in tiers.iter().enumerate() {
+            let contract = match &tier.requirement {
+                TierRequirement::Cw721 { collection } => collection,
+                TierRequirement::Cw20 { token, .. } => token,
+            };
+            event = event
+                .add_attribute(format!("tier.{}.contract", index), contract.to_string())
+                .add_attribute(
+                    format!("tier.{}.multiplier", index),
+                    tier.multiplier.to_string(),
+                );
+        }
+
+        Ok(Response::new()
+            .add_event(event)
+            .add_attribute("action", "set_tiers")
+            .add_attribute("tiers_count", tiers.len().to_string()))
+    }
+
+    pub fn execute_grant_role(
+        deps: DepsMut,
+        info: MessageInfo,
+        address: String,
+        role: Role,
+    ) -> Result<Response, ContractError> {
+        let state = STATE.load(deps.storage)?;
+
+        // Check if sender is admin
+        assert_role(deps.storage, &state, &info.sender, Role::Owner)?;
+
+        if role == Role::Owner {
+            return Err(ContractError::OwnerRoleNotGrantable {});
+        }
+
+        let address = validate_address(deps.api, &address)?;
+        let mut roles = ROLES.may_load(deps.storage, &address)?.unwrap_or_default();
+        if !roles.contains(&role) {
+            roles.push(role);
+            ROLES.save(deps.storage, &address, &roles)?;
+        }
+
+        Ok(Response::new()
+            .add_event(
+                config_update_event("role")
+                    .add_attribute("address", address.to_string())
+                    .add_attribute("role", role.as_str())
+                    .add_attribute("granted", "true"),
+            )
+            .add_attribute("action", "grant_role")
+            .add_attribute("address", address.to_string())
+            .add_attribute("role", role.as_str()))
+    }
+
+    pub fn execute_revoke_role(
+        deps: DepsMut,
+        info: MessageInfo,
+        address: String,
+        role: Role,
+    ) -> Result<Response, ContractError> {
+        let state = STATE.load(deps.storage)?;
+
+        // Check if sender is admin
+        assert_role(deps.storage, &state, &info.sender, Role::Owner)?;
+
+        let address = validate_address(deps.api, &address)?;
+        let mut roles = ROLES.may_load(deps.storage, &address)?.unwrap_or_default();
+        if !roles.contains(&role) {
+            return Err(ContractError::RoleNotGranted {
+                address: address.to_string(),
+                role,
+            });
+        }
+
+        roles.retain(|granted| *granted != role);
+        if roles.is_empty() {
+            ROLES.remove(deps.storage, &address);
+        } else {
+            ROLES.save(deps.storage, &address, &roles)?;
+        }
+
+        Ok(Response::new()
+            .add_event(
+                config_update_event("role")
+                    .add_attribute("address", address.to_string())
+                    .add_attribute("role", role.as_str())
+                    .add_attribute("granted", "false"),
+            )
+            .add_attribute("action", "revoke_role")
+            .add_attribute("address", address.to_string())
+            .add_attribute("role", role.as_str()))
+    }
+
+    pub fn execute_receive(
+        deps: DepsMut,
+        info: MessageInfo,
+        wrapper: Cw20ReceiveMsg,
+    ) -> Result<Response, ContractError> {
+        // The CW20 contract calling us is the token being deposited
+        let denom = Denom::Cw20(info.sender);
+        let depositor = validate_address(deps.api, &wrapper.sender)?;
+
+        match from_json(&wrapper.msg)? {
+            ReceiveMsg::Deposit {} => {
+                record_deposit(deps.storage, &depositor, &denom, wrapper.amount)?;
+
+                Ok(Response::new()
+                    .add_attribute("action", "deposit")
+                    .add_attribute("depositor", depositor.to_string())
+                    .add_attribute("deposited_tokens", format_token(&denom, wrapper.amount)))
+            }
+        }
+    }
+
+    pub fn execute_deposit(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+        if info.funds.is_empty() {
+            return Err(ContractError::NoFunds {});
+        }
+
+        let mut deposited_tokens: Vec<String> = vec![];
+
+        for fund in info.funds.iter() {
+            let denom = Denom::Native(fund.denom.clone());
+            record_deposit(deps.storage, &info.sender, &denom, fund.amount)?;
+            deposited_tokens.push(format_token(&denom, fund.amount));
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "deposit")
+            .add_attribute("depositor", info.sender.to_string())
+            .add_attribute("deposited_tokens", deposited_tokens.join(", ")))
+    }
+
+    pub fn execute_withdraw(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        denom: Denom,
+        amount: Option<Uint128>,
+    ) -> Result<Response, ContractError> {
+        validate_denom(deps.api, &denom)?;
+
+        // Load state using Item
+        let state = STATE.load(deps.storage)?;
+
+        // Owner may withdraw any amount up to the current balance
+        if assert_role(deps.storage, &state, &info.sender, Role::Owner).is_ok() {
+            return withdraw_to(deps, env, info.sender, denom, amount, "admin");
+        }
+
+        if !state.depositor_withdrawals {
+            return Err(ContractError::Unauthorized {});
+        }
+        if amount.is_some() {
+            return Err(ContractError::PartialDepositorWithdrawal {});
+        }
+
+        let key = denom_key(&denom);
+        let deposit = DEPOSITS
+            .may_load(deps.storage, (&key, &info.sender))?
+            .unwrap_or_default();
+        if deposit.is_zero() {
+            return Err(ContractError::NoDeposit {});
+        }
+
+        // Tokens owed to vesting schedules can't be withdrawn
+        let balance = query_available_balance(deps.as_ref(), &env.contract.address, &denom)?;
+
+        // Depositor receives the same fraction of what is left of the deposits as they
+        // contributed. Funds sent outside of `Deposit` aren't shared out, so the pool is
+        // capped at the tracked total and no depositor gets back more than they put in.
+        let mut total = DEPOSIT_TOTALS.load(deps.storage, &key)?;
+        let share = balance
+            .min(total.amount)
+            .multiply_ratio(deposit, total.amount);
+
+        DEPOSITS.remove(deps.storage, (&key, &info.sender));
+        total.amount -= deposit;
+        if total.amount.is_zero() {
+            DEPOSIT_TOTALS.remove(deps.storage, &key);
+        } else {
+            DEPOSIT_TOTALS.save(deps.storage, &key, &total)?;
+        }
+
+        withdraw_response(&denom, &info.sender, share, "pro_rata")
+    }
+
+    /// Send up to the available balance of `denom` to `recipient` without checking the
+    /// sender. Defaults to the full available balance.
+    pub(crate) fn withdraw_to(
+        deps: DepsMut,
+        env: Env,
+        recipient: Addr,
+        denom: Denom,
+        amount: Option<Uint128>,
+        mode: &str,
+    ) -> Result<Response, ContractError> {
+        // Tokens owed to vesting schedules can't be withdrawn
+        let balance = query_available_balance(deps.as_ref(), &env.contract.address, &denom)?;
+
+        let amount = amount.unwrap_or(balance);
+        if amount > balance {
+            return Err(ContractError::InsufficientFunds {});
+        }
+
+        withdraw_response(&denom, &recipient, amount, mode)
+    }
+
+    fn withdraw_response(
+        denom: &Denom,
+        recipient: &Addr,
+        amount: Uint128,
+        mode: &str,
+    ) -> Result<Response, ContractError> {
+        if amount.is_zero() {
+            return Err(ContractError::NothingToWithdraw {});
+        }
+
+        Ok(Response::new()
+            .add_message(transfer_msg(denom, recipient, amount)?)
+            .add_attribute("action", "withdraw")
+            .add_attribute("mode", mode)
+            .add_attribute("recipient", recipient.to_string())
+            .add_attribute("withdrawn_tokens", format_token(denom, amount)))
+    }
+
+    pub fn execute_withdraw_vested(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+    ) -> Result<Response, ContractError> {
+        let now = env.block.time.seconds();
+        let schedules = VESTING
+            .prefix(&info.sender)
+            .range(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+
+        // Release everything vested so far, merged per token
+        let mut released: Vec<TokenAmount> = vec![];
+        for (id, mut schedule) in schedules {
+            let amount = schedule.withdrawable(now);
+            if amount.is_zero() {
+                continue;
+            }
+
+            schedule.released += amount;
+            if schedule.released == schedule.amount {
+                VESTING.remove(deps.storage, (&info.sender, id));
+            } else {
+                VESTING.save(deps.storage, (&info.sender, id), &schedule)?;
+            }
+            VESTING_TOTALS.update(
+                deps.storage,
+                &denom_key(&schedule.denom),
+                |total| -> StdResult<_> { Ok(total.unwrap_or_default().checked_sub(amount)?) },
+            )?;
+
+            match released
+                .iter_mut()
+                .find(|token| token.denom == schedule.denom)
+            {
+                Some(token) => token.amount += amount,
+                None => released.push(TokenAmount {
+                    denom: schedule.denom,
+                    amount,
+                }),
+            }
+        }
+
+        if released.is_empty() {
+            return Err(ContractError::NothingVested {});
+        }
+
+        let messages = released
+            .iter()
+            .map(|token| transfer_msg(&token.denom, &info.sender, token.amount))
+            .collect::<StdResult<Vec<_>>>()?;
+        let withdrawn_tokens: Vec<String> = released
+            .iter()
+            .map(|token| format_token(&token.denom, token.amount))
+            .collect();
+
+        Ok(Response::new()
+            .add_messages(messages)
+            .add_attribute("action", "withdraw_vested")
+            .add_attribute("recipient", info.sender.to_string())
+            .add_attribute("withdrawn_tokens", withdrawn_tokens.join(", ")))
+    }
+
+    pub fn execute_airdrop(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        recipients: Vec<(String, Vec<TokenConfig>)>,
+    ) -> Result<Response, ContractError> {
+        let state = STATE.load(deps.storage)?;
+
+        // Check if sender is admin
+        assert_role(deps.storage, &state, &info.sender, Role::Owner)?;
+
+        if recipients.is_empty() {
+            return Err(ContractError::EmptyAirdrop {});
+        }
+        if recipients.len() > MAX_AIRDROP_RECIPIENTS {
+            return Err(ContractError::TooManyRecipients {
+                max: MAX_AIRDROP_RECIPIENTS,
+            });
+        }
+
+        // Validate the whole batch and add up what it needs before sending anything
+        let mut batch: Vec<(Addr, Vec<TokenConfig>)> = vec![];
+        let mut totals: Vec<TokenAmount> = vec![];
+        for (address, mut tokens) in recipients {
+            let recipient = validate_address(deps.api, &address)?;
+            if batch.iter().any(|(other, _)| *other == recipient) {
+                return Err(ContractError::DuplicateRecipient { address });
+            }
+            if tokens.is_empty() {
+                return Err(ContractError::EmptyAirdrop {});
+            }
+            for token in tokens.iter_mut() {
+                validate_token(deps.api, token)?;
+                // Resolve priced tokens once, the rest of the batch uses the unit amount
+                token.amount = token_amount(deps.as_ref(), token)?;
+                if token.amount.is_zero() {
+                    return Err(ContractError::InvalidZeroAmount {});
+                }
+                merge_token(&mut totals, &token.denom, token.amount)?;
+            }
+            batch.push((recipient, tokens));
+        }
+
+        // Tokens owed to vesting schedules can't be airdropped
+        for total in totals.iter() {
+            let balance =
+                query_available_balance(deps.as_ref(), &env.contract.address, &total.denom)?;
+            if balance < total.amount {
+                return Err(ContractError::InsufficientFunds {});
+            }
+        }
+
+        let now = env.block.time.seconds();
+        let mut messages: Vec<CosmosMsg> = vec![];
+        let mut events = vec![];
+        for (recipient, tokens) in batch {
+            let mut sent: Vec<TokenAmount> = vec![];
+            let mut streamed: Vec<TokenAmount> = vec![];
+            for token in tokens {
+                match &token.stream {
+                    Some(stream) => {
+                        create_vesting_schedule(
+                            deps.storage,
+                            &recipient,
+                            TokenAmount {
+                                denom: token.denom.clone(),
+                                amount: token.amount,
+                            },
+                            now,
+                            stream.duration_seconds,
+                        )?;
+                        merge_token(&mut streamed, &token.denom, token.amount)?;
+                    }
+                    None => merge_token(&mut sent, &token.denom, token.amount)?,
+                }
+            }
+
+            // All native coins go out in a single bank send, sorted by denom
+            let mut coins: Vec<Coin> = vec![];
+            for token in sent.iter() {
+                match &token.denom {
+                    Denom::Native(denom) => coins.push(Coin::new(token.amount, denom)),
+                    Denom::Cw20(_) => {
+                        messages.push(transfer_msg(&token.denom, &recipient, token.amount)?)
+                    }
+                }
+            }
+            if !coins.is_empty() {
+                coins.sort_by(|a, b| a.denom.cmp(&b.denom));
+                messages.push(CosmosMsg::Bank(BankMsg::Send {
+                    to_address: recipient.to_string(),
+                    amount: coins,
+                }));
+            }
+
+            // Airdrops don't start the recipient's rate limit, so they only count as
+            // unique claimers once they claim themselves
+            let claim_id = record_claim(
+                deps.storage,
+                &recipient,
+                now,
+                false,
+                sent.iter().chain(streamed.iter()).cloned().collect(),
+            )?;
+            events.push(
+                claim_event(claim_id, &recipient, Decimal::one(), &sent, &streamed, &[])
+                    .add_attribute("airdrop", "true"),
+            );
+        }
+
+        let recipients_count = events.len();
+        Ok(Response::new()
+            .add_messages(messages)
+            .add_events(events)
+            .add_attribute("action", "airdrop")
+            .add_attribute("recipients_count", recipients_count.to_string()))
+    }
+
+    pub fn execute_set_depositor_withdrawals(
+        deps: DepsMut,
+        info: MessageInfo,
+        enabled: bool,
+    ) -> Result<Response, ContractError> {
+        // Load state using Item
+        let mut state = STATE.load(deps.storage)?;
+
+        // Check if sender is admin
+        assert_role(deps.storage, &state, &info.sender, Role::Owner)?;
+
+        state.depositor_withdrawals = enabled;
+
+        // Save updated state
+        STATE.save(deps.storage, &state)?;
+
+        Ok(Response::new()
+            .add_event(
+                config_update_event("depositor_withdrawals")
+                    .add_attribute("enabled", enabled.to_string()),
+            )
+            .add_attribute("action", "set_depositor_withdrawals")
+            .add_attribute("enabled", enabled.to_string()))
+    }
+
+    pub fn execute_set_paused(
+        deps: DepsMut,
+        info: MessageInfo,
+        paused: bool,
+    ) -> Result<Response, ContractError> {
+        // Load state using Item
+        let state = STATE.load(deps.storage)?;
+
+        // Check if sender is a pauser
+        assert_role(deps.storage, &state, &info.sender, Role::Pauser)?;
+
+        save_paused(deps.storage, paused)?;
+
+        let action = if paused { "pause" } else { "unpause" };
+        Ok(Response::new()
+            .add_event(config_update_event("paused").add_attribute("paused", paused.to_string()))
+            .add_attribute("action", action))
+    }
+
+    pub fn execute_set_claim_window(
+        deps: DepsMut,
+        info: MessageInfo,
+        opens_at: Option<Timestamp>,
+        closes_at: Option<Timestamp>,
+    ) -> Result<Response, ContractError> {
+        // Load state using Item
+        let mut state = STATE.load(deps.storage)?;
+
+        // Check if sender is an operator
+        assert_role(deps.storage, &state, &info.sender, Role::Operator)?;
+
+        validate_claim_window(opens_at, closes_at)?;
+        state.opens_at = opens_at;
+        state.closes_at = closes_at;
+
+        // Save updated state
+        STATE.save(deps.storage, &state)?;
+
+        let mut event = config_update_event("claim_window");
+        if let Some(opens_at) = opens_at {
+            event = event.add_attribute("opens_at", opens_at.seconds().to_string());
+        }
+        if let Some(closes_at) = closes_at {
+            event = event.add_attribute("closes_at", closes_at.seconds().to_string());
+        }
+
+        Ok(Response::new()
+            .add_event(event)
+            .add_attribute("action", "set_claim_window")
+            .add_attribute("opens_at", format_time(opens_at))
+            .add_attribute("closes_at", format_time(closes_at)))
+    }
+
+    pub(crate) fn validate_claim_window(
+        opens_at: Option<Timestamp>,
+        closes_at: Option<Timestamp>,
+    ) -> Result<(), ContractError> {
+        if let (Some(opens_at), Some(closes_at)) = (opens_at, closes_at) {
+            if opens_at >= closes_at {
+                return Err(ContractError::InvalidClaimWindow {});
+            }
+        }
+        Ok(())
+    }
+
+    fn format_time(time: Option<Timestamp>) -> String {
+        time.map(|time| time.seconds().to_string())
+            .unwrap_or_else(|| "none".to_string())
+    }
+
+    fn record_deposit(
+        storage: &mut dyn Storage,
+        depositor: &Addr,
+        denom: &Denom,
+        amount: Uint128,
+    ) -> Result<(), ContractError> {
+        let state = STATE.load(storage)?;
+
+        // Only tokens handed out by the faucet can be deposited
+        if !state.tokens.iter().any(|token| &token.denom == denom) {
+            return Err(ContractError::TokenNotDistributed {});
+        }
+        if amount.is_zero() {
+            return Err(ContractError::InvalidZeroAmount {});
+        }
+
+        let key = denom_key(denom);
+        DEPOSITS.update(storage, (&key, depositor), |deposit| -> StdResult<_> {
+            Ok(deposit.unwrap_or_default().checked_add(amount)?)
+        })?;
+        DEPOSIT_TOTALS.update(storage, &key, |total| -> StdResult<_> {
+            let mut total = total.unwrap_or(TokenAmount {
+                denom: denom.clone(),
+                amount: Uint128::zero(),
+            });
+            total.amount = total.amount.checked_add(amount)?;
+            Ok(total)
+        })?;
+
+        Ok(())
+    }
+
+    fn create_vesting_schedule(
+        storage: &mut dyn Storage,
+        claimer: &Addr,
+        token: TokenAmount,
+        start_time: u64,
+        duration_seconds: u64,
+    ) -> StdResult<()> {
+        let id = VESTING_SEQ.may_load(storage)?.unwrap_or_default() + 1;
+        VESTING_SEQ.save(storage, &id)?;
+
+        VESTING_TOTALS.update(storage, &denom_key(&token.denom), |total| -> StdResult<_> {
+            Ok(total.unwrap_or_default().checked_add(token.amount)?)
+        })?;
+        VESTING.save(
+            storage,
+            (claimer, id),
+            &VestingSchedule {
+                id,
+                denom: token.denom,
+                amount: token.amount,
+                released: Uint128::zero(),
+                start_time,
+                end_time: start_time + duration_seconds,
+            },
+        )
+    }
+
+    fn record_claim(
+        storage: &mut dyn Storage,
+        claimer: &Addr,
+        time: u64,
+        first_claim: bool,
+        tokens: Vec<TokenAmount>,
+    ) -> StdResult<u64> {
+        let mut stats = CLAIM_STATS.may_load(storage)?.unwrap_or_default();
+        stats.total_claims += 1;
+        if first_claim {
+            stats.unique_claimers += 1;
+        }
+        CLAIM_STATS.save(storage, &stats)?;
+
+        for token in tokens.iter() {
+            DISTRIBUTED.update(storage, &denom_key(&token.denom), |total| -> StdResult<_> {
+                let mut total = total.unwrap_or(TokenAmount {
+                    denom: token.denom.clone(),
+                    amount: Uint128::zero(),
+                });
+                total.amount = total.amount.checked_add(token.amount)?;
+                Ok(total)
+            })?;
+        }
+
+        // Claim ids are sequential, starting at 1
+        let id = stats.total_claims;
+        claims().save(
+            storage,
+            id,
+            &ClaimRecord {
+                id,
+                claimer: claimer.clone(),
+                time,
+                tokens,
+            },
+        )?;
+
+        Ok(id)
+    }
+
+    /// Add `amount` to the entry for `denom`, creating it when missing
+    fn merge_token(tokens: &mut Vec<TokenAmount>, denom: &Denom, amount: Uint128) -> StdResult<()> {
+        match tokens.iter_mut().find(|token| &token.denom == denom) {
+            Some(token) => token.amount = token.amount.checked_add(amount)?,
+            None => tokens.push(TokenAmount {
+                denom: denom.clone(),
+                amount,
+            }),
+        }
+        Ok(())
+    }
+
+    fn format_token(denom: &Denom, amount: Uint128) -> String {
+        match denom {
+            Denom::Native(denom) => format!("{} {}", amount, denom),
+            Denom::Cw20(contract_addr) => format!("{} CW20:{}", amount, contract_addr),
+        }
+    }
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -254,26 +1128,86 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::GetTokenConfigs {} => to_json_binary(&query_token_configs(deps)?),
         QueryMsg::GetRateLimit {} => to_json_binary(&query_rate_limit(deps)?),
-        QueryMsg::GetUserInfo { address } => to_json_binary(&query_user_info(deps, address)?),
+        QueryMsg::GetUserInfo { address } => to_json_binary(&query_user_info(deps, env, address)?),
         QueryMsg::GetAdmin {} => to_json_binary(&query_admin(deps)?),
         QueryMsg::GetBalance {} => to_json_binary(&query_balance(deps, env)?),
         QueryMsg::CanUserClaim { address } => {
             to_json_binary(&query_can_user_claim(deps, env, address)?)
         }
+        QueryMsg::Depositors {
+            denom,
+            start_after,
+            limit,
+        } => to_json_binary(&query_depositors(deps, denom, start_after, limit)?),
+        QueryMsg::Depositor { address } => to_json_binary(&query_depositor(deps, address)?),
+        QueryMsg::ClaimHistory {
+            address,
+            start_after,
+            limit,
+        } => to_json_binary(&query_claim_history(deps, address, start_after, limit)?),
+        QueryMsg::ClaimsInRange {
+            from,
+            to,
+            start_after,
+            limit,
+        } => to_json_binary(&query_claims_in_range(deps, from, to, start_after, limit)?),
+        QueryMsg::Stats {} => to_json_binary(&query_stats(deps)?),
+        QueryMsg::ClaimWindow {} => to_json_binary(&query_claim_window(deps)?),
+        QueryMsg::PowChallenge {} => to_json_binary(&PowChallengeResponse {
+            challenge: crate::pow::query_pow_challenge(deps)?,
+        }),
+        QueryMsg::Tiers {} => to_json_binary(&TiersResponse {
+            tiers: TIERS.may_load(deps.storage)?.unwrap_or_default(),
+        }),
+        QueryMsg::Roles { start_after, limit } => {
+            to_json_binary(&query_roles(deps, start_after, limit)?)
+        }
+        QueryMsg::AllClaimers { start_after, limit } => {
+            to_json_binary(&query_all_claimers(deps, env, start_after, limit)?)
+        }
+        QueryMsg::ClaimersInCooldown {
+            at_time,
+            start_after,
+            limit,
+        } => to_json_binary(&query_claimers_in_cooldown(
+            deps,
+            at_time.unwrap_or(env.block.time),
+            start_after,
+            limit,
+        )?),
+        #[cfg(feature = "neutron")]
+        QueryMsg::SybilGate {} => to_json_binary(&crate::msg::SybilGateResponse {
+            gate: crate::neutron::SYBIL_GATE.may_load(deps.storage)?,
+        }),
+        #[cfg(feature = "neutron")]
+        QueryMsg::HubAccount { address } => {
+            let address = validate_address(deps.api, &address)?;
+            to_json_binary(&crate::msg::HubAccountResponse {
+                query_id: crate::neutron::HUB_QUERIES.may_load(deps.storage, &address)?,
+            })
+        }
     }
 }
 
 pub mod query {
-    use cosmwasm_std::Addr;
+    use cosmwasm_std::{Addr, Order, Timestamp};
     use cw20::{Balance, Denom};
+    use cw_storage_plus::Bound;
     use cw_utils::NativeBalance;
 
     use crate::{
+        helpers::claim_blocked_reason,
         msg::{
-            AdminResponse, BalanceResponse, ClaimStatusResponse, RateLimitResponse,
-            TokenConfigsResponse, UserInfoResponse,
+            AdminResponse, BalanceResponse, ClaimBlockedReason, ClaimHistoryResponse,
+            ClaimStatusResponse, ClaimWindowResponse, ClaimerInfo, ClaimersResponse,
+            DepositorAmount, DepositorResponse, DepositorsResponse, RateLimitResponse, RoleMember,
+            RolesResponse, StatsResponse, TokenConfigsResponse, UserInfoResponse,
+        },
+        state::{
+            claims, denom_key, user_claims, TokenAmount, UserInfo, CLAIM_BUCKET_SECONDS,
+            CLAIM_STATS, DEFAULT_QUERY_LIMIT, DEPOSITS, DEPOSIT_TOTALS, DISTRIBUTED,
+            MAX_QUERY_LIMIT, ROLES, VESTING,
         },
-        state::USER_CLAIMS,
     };
 
     use super::*;
@@ -294,11 +1228,37 @@ pub mod query {
         })
     }
 
-    pub fn query_user_info(deps: Deps, address: String) -> StdResult<UserInfoResponse> {
-        let user_addr = Addr::unchecked(address);
-        let user_info = USER_CLAIMS.may_load(deps.storage, &user_addr)?;
+    pub fn query_user_info(deps: Deps, env: Env, address: String) -> StdResult<UserInfoResponse> {
+        let user_addr = validate_address(deps.api, &address)?;
+        let user_info = user_claims().may_load(deps.storage, &user_addr)?;
+
+        let vesting = VESTING
+            .prefix(&user_addr)
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| item.map(|(_, schedule)| schedule))
+            .collect::<StdResult<Vec<_>>>()?;
+
+        let now = env.block.time.seconds();
+        let mut withdrawable: Vec<TokenAmount> = vec![];
+        for schedule in vesting.iter() {
+            let amount = schedule.withdrawable(now);
+            match withdrawable
+                .iter_mut()
+                .find(|token| token.denom == schedule.denom)
+            {
+                Some(token) => token.amount += amount,
+                None => withdrawable.push(TokenAmount {
+                    denom: schedule.denom.clone(),
+                    amount,
+                }),
+            }
+        }
 
-        Ok(UserInfoResponse { user_info })
+        Ok(UserInfoResponse {
+            user_info,
+            vesting,
+            withdrawable,
+        })
     }
 
     pub fn query_admin(deps: Deps) -> StdResult<AdminResponse> {
@@ -350,35 +1310,298 @@ pub mod query {
         address: String,
     ) -> StdResult<ClaimStatusResponse> {
         let state = STATE.load(deps.storage)?;
-        let user_addr = Addr::unchecked(address);
+        let user_addr = validate_address(deps.api, &address)?;
 
-        let user_info = USER_CLAIMS.may_load(deps.storage, &user_addr)?;
-        let current_time = env.block.time.seconds();
+        let user_info = user_claims().may_load(deps.storage, &user_addr)?;
 
-        if let Some(info) = user_info {
-            let time_since_last_claim = current_time.saturating_sub(info.last_claim_time);
-            if time_since_last_claim >= state.rate_limit_seconds {
-                // User can claim
-                Ok(ClaimStatusResponse {
-                    can_claim: true,
-                    seconds_until_next_claim: 0,
-                })
-            } else {
-                // User must wait
-                let seconds_remaining = state.rate_limit_seconds - time_since_last_claim;
-                Ok(ClaimStatusResponse {
-                    can_claim: false,
-                    seconds_until_next_claim: seconds_remaining,
+        let reason = claim_blocked_reason(&state, user_info.as_ref(), env.block.time);
+        let seconds_until_next_claim = match &reason {
+            Some(ClaimBlockedReason::RateLimited { seconds_remaining }) => *seconds_remaining,
+            Some(ClaimBlockedReason::NotOpenYet { opens_at }) => {
+                opens_at.seconds() - env.block.time.seconds()
+            }
+            _ => 0,
+        };
+
+        Ok(ClaimStatusResponse {
+            can_claim: reason.is_none(),
+            seconds_until_next_claim,
+            reason,
+        })
+    }
+
+    pub fn query_depositors(
+        deps: Deps,
+        denom: Denom,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<DepositorsResponse> {
+        validate_denom(deps.api, &denom)?;
+        let key = denom_key(&denom);
+        let limit = limit.unwrap_or(DEFAULT_QUERY_LIMIT).min(MAX_QUERY_LIMIT) as usize;
+        let start_after = start_after
+            .map(|address| validate_address(deps.api, &address))
+            .transpose()?;
+        let start = start_after.as_ref().map(Bound::exclusive);
+
+        let total = DEPOSIT_TOTALS
+            .may_load(deps.storage, &key)?
+            .map(|total| total.amount)
+            .unwrap_or_default();
+
+        let depositors = DEPOSITS
+            .prefix(&key)
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|item| {
+                let (address, amount) = item?;
+                Ok(DepositorAmount {
+                    address: address.to_string(),
+                    amount,
                 })
+            })
+            .collect::<StdResult<Vec<_>>>()?;
+
+        Ok(DepositorsResponse { total, depositors })
+    }
+
+    pub fn query_depositor(deps: Deps, address: String) -> StdResult<DepositorResponse> {
+        let depositor = validate_address(deps.api, &address)?;
+
+        // Look up the depositor's share of every token that has been deposited
+        let mut deposits: Vec<TokenAmount> = vec![];
+        for item in DEPOSIT_TOTALS.range(deps.storage, None, None, Order::Ascending) {
+            let (key, total) = item?;
+            if let Some(amount) = DEPOSITS.may_load(deps.storage, (&key, &depositor))? {
+                deposits.push(TokenAmount {
+                    denom: total.denom,
+                    amount,
+                });
             }
-        } else {
-            // First-time user can claim
-            Ok(ClaimStatusResponse {
-                can_claim: true,
-                seconds_until_next_claim: 0,
+        }
+
+        Ok(DepositorResponse {
+            address: depositor.to_string(),
+            deposits,
+        })
+    }
+
+    pub fn query_claim_history(
+        deps: Deps,
+        address: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<ClaimHistoryResponse> {
+        let claimer = validate_address(deps.api, &address)?;
+        let limit = limit.unwrap_or(DEFAULT_QUERY_LIMIT).min(MAX_QUERY_LIMIT) as usize;
+
+        // Most recent claims first
+        let claims = claims()
+            .idx
+            .claimer
+            .prefix(claimer)
+            .range(
+                deps.storage,
+                None,
+                start_after.map(Bound::exclusive),
+                Order::Descending,
+            )
+            .take(limit)
+            .map(|item| item.map(|(_, record)| record))
+            .collect::<StdResult<Vec<_>>>()?;
+
+        Ok(ClaimHistoryResponse { claims })
+    }
+
+    pub fn query_claims_in_range(
+        deps: Deps,
+        from: u64,
+        to: u64,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<ClaimHistoryResponse> {
+        let limit = limit.unwrap_or(DEFAULT_QUERY_LIMIT).min(MAX_QUERY_LIMIT) as usize;
+
+        // Resume right after the given claim, otherwise start at the first bucket
+        let min = match start_after {
+            Some(id) => {
+                let record = claims().load(deps.storage, id)?;
+                Bound::exclusive((record.time / CLAIM_BUCKET_SECONDS, id))
+            }
+            None => Bound::inclusive((from / CLAIM_BUCKET_SECONDS, 0)),
+        };
+
+        let claims = claims()
+            .idx
+            .bucket
+            .range(deps.storage, Some(min), None, Order::Ascending)
+            // Claims are stored in time order, so stop at the first one past `to`
+            .take_while(|item| match item {
+                Ok((_, record)) => record.time <= to,
+                Err(_) => true,
+            })
+            .filter(|item| match item {
+                Ok((_, record)) => record.time >= from,
+                Err(_) => true,
+            })
+            .take(limit)
+            .map(|item| item.map(|(_, record)| record))
+            .collect::<StdResult<Vec<_>>>()?;
+
+        Ok(ClaimHistoryResponse { claims })
+    }
+
+    pub fn query_stats(deps: Deps) -> StdResult<StatsResponse> {
+        let stats = CLAIM_STATS.may_load(deps.storage)?.unwrap_or_default();
+
+        let distributed = DISTRIBUTED
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| item.map(|(_, total)| total))
+            .collect::<StdResult<Vec<TokenAmount>>>()?;
+
+        Ok(StatsResponse {
+            total_claims: stats.total_claims,
+            unique_claimers: stats.unique_claimers,
+            distributed,
+        })
+    }
+
+    pub fn query_roles(
+        deps: Deps,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<RolesResponse> {
+        let state = STATE.load(deps.storage)?;
+        let limit = limit.unwrap_or(DEFAULT_QUERY_LIMIT).min(MAX_QUERY_LIMIT) as usize;
+        let start_after = start_after
+            .map(|address| validate_address(deps.api, &address))
+            .transpose()?;
+        let start = start_after.as_ref().map(Bound::exclusive);
+
+        let members = ROLES
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|item| {
+                let (address, roles) = item?;
+                Ok(RoleMember {
+                    address: address.to_string(),
+                    roles,
+                })
             })
+            .collect::<StdResult<Vec<_>>>()?;
+
+        Ok(RolesResponse {
+            owner: state.admin.to_string(),
+            members,
+        })
+    }
+
+    pub fn query_all_claimers(
+        deps: Deps,
+        env: Env,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<ClaimersResponse> {
+        let limit = limit.unwrap_or(DEFAULT_QUERY_LIMIT).min(MAX_QUERY_LIMIT) as usize;
+        let claimers = claimers(deps, env.block.time, start_after)?
+            .take(limit)
+            .collect::<StdResult<Vec<_>>>()?;
+
+        Ok(ClaimersResponse { claimers })
+    }
+
+    pub fn query_claimers_in_cooldown(
+        deps: Deps,
+        at_time: Timestamp,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<ClaimersResponse> {
+        let limit = limit.unwrap_or(DEFAULT_QUERY_LIMIT).min(MAX_QUERY_LIMIT) as usize;
+        let rate_limit_seconds = STATE.load(deps.storage)?.rate_limit_seconds;
+        let start_after = start_after
+            .map(|address| validate_address(deps.api, &address))
+            .transpose()?;
+
+        // Only claims after `at_time - rate_limit_seconds` and up to `at_time` are still
+        // cooling down. Resume right after the given claimer if they are among them.
+        let first_time = at_time
+            .seconds()
+            .checked_sub(rate_limit_seconds)
+            .map_or(0, |time| time + 1);
+        let first = Addr::unchecked("");
+        let max = Bound::exclusive((at_time.seconds() + 1, &first));
+        let min = match &start_after {
+            Some(address) => match user_claims().may_load(deps.storage, address)? {
+                Some(user_info) if user_info.last_claim_time >= first_time => {
+                    Bound::exclusive((user_info.last_claim_time, address))
+                }
+                _ => Bound::inclusive((first_time, &first)),
+            },
+            None => Bound::inclusive((first_time, &first)),
+        };
+
+        let claimers = user_claims()
+            .idx
+            .last_claim
+            .range(deps.storage, Some(min), Some(max), Order::Ascending)
+            .take(limit)
+            .map(|item| {
+                let (address, user_info) = item?;
+                Ok(claimer_info(
+                    address,
+                    user_info,
+                    at_time,
+                    rate_limit_seconds,
+                ))
+            })
+            .collect::<StdResult<Vec<_>>>()?;
+
+        Ok(ClaimersResponse { claimers })
+    }
+
+    /// Claimers ordered by address with their rate limit status at `time`
+    fn claimers<'a>(
+        deps: Deps<'a>,
+        time: Timestamp,
+        start_after: Option<String>,
+    ) -> StdResult<impl Iterator<Item = StdResult<ClaimerInfo>> + 'a> {
+        let rate_limit_seconds = STATE.load(deps.storage)?.rate_limit_seconds;
+        let start_after = start_after
+            .map(|address| validate_address(deps.api, &address))
+            .transpose()?;
+        let start = start_after.as_ref().map(Bound::exclusive);
+
+        Ok(user_claims()
+            .range(deps.storage, start, None, Order::Ascending)
+            .map(move |item| {
+                let (address, user_info) = item?;
+                Ok(claimer_info(address, user_info, time, rate_limit_seconds))
+            }))
+    }
+
+    fn claimer_info(
+        address: Addr,
+        user_info: UserInfo,
+        time: Timestamp,
+        rate_limit_seconds: u64,
+    ) -> ClaimerInfo {
+        let seconds_remaining =
+            (user_info.last_claim_time + rate_limit_seconds).saturating_sub(time.seconds());
+        ClaimerInfo {
+            address: address.to_string(),
+            last_claim_time: user_info.last_claim_time,
+            seconds_remaining,
         }
     }
+
+    pub fn query_claim_window(deps: Deps) -> StdResult<ClaimWindowResponse> {
+        let state = STATE.load(deps.storage)?;
+        Ok(ClaimWindowResponse {
+            paused: state.paused,
+            opens_at: state.opens_at,
+            closes_at: state.closes_at,
+        })
+    }
 }
 
 #[cfg(test)]
@@ -386,35 +1609,47 @@ mod tests {
     use super::*;
 
     use crate::contract::instantiate;
-    use crate::msg::InstantiateMsg;
+    use crate::msg::{ClaimBlockedReason, InstantiateMsg, ReceiveMsg, RoleMember};
     use crate::state::{
-        State, TokenConfig, UserInfo, DEFAULT_CW20_AMOUNT, DEFAULT_NATIVE_AMOUNT, NATIVE_DENOM,
-        STATE, USER_CLAIMS,
+        denom_key, user_claims, ClaimRecord, Role, State, TokenAmount, TokenConfig, UserInfo,
+        DEFAULT_CW20_AMOUNT, DEFAULT_NATIVE_AMOUNT, DEFAULT_QUERY_LIMIT, MAX_AIRDROP_RECIPIENTS,
+        NATIVE_DENOM, STATE,
     };
     use cosmwasm_std::testing::{
-        message_info, mock_dependencies, mock_dependencies_with_balance, mock_env,
+        message_info, mock_dependencies, mock_dependencies_with_balance, mock_env, MockApi,
     };
     use cosmwasm_std::{
         coins, from_json, Addr, BankMsg, Coin, ContractResult, CosmosMsg, Response, SystemError,
         SystemResult, Uint128, WasmMsg, WasmQuery,
     };
-    use cw20::{Balance, BalanceResponse, Cw20ExecuteMsg, Denom};
+    use cw20::{Balance, BalanceResponse, Cw20ExecuteMsg, Cw20ReceiveMsg, Denom};
+
+    // Bech32 address as produced by the default MockApi
+    fn addr(name: &str) -> Addr {
+        MockApi::default().addr_make(name)
+    }
 
     // Define a helper function to create a default instantiation message
     fn default_instantiate_msg() -> InstantiateMsg {
         InstantiateMsg {
-            admin: Some(Addr::unchecked("admin")),
+            admin: Some(addr("admin")),
             tokens: vec![
                 TokenConfig {
                     denom: Denom::Native("untrn".to_string()),
                     amount: Uint128::from(DEFAULT_NATIVE_AMOUNT),
+                    stream: None,
+                    price: None,
                 },
                 TokenConfig {
-                    denom: Denom::Cw20(Addr::unchecked("cw20_token")),
+                    denom: Denom::Cw20(addr("cw20_token")),
                     amount: Uint128::from(DEFAULT_CW20_AMOUNT),
+                    stream: None,
+                    price: None,
                 },
             ],
             rate_limit_seconds: Some(DEFAULT_RATE_LIMIT),
+            opens_at: None,
+            closes_at: None,
         }
     }
 
@@ -423,7 +1658,7 @@ mod tests {
     fn test_instantiate() {
         let mut deps = mock_dependencies();
         let env = mock_env();
-        let info = message_info(&Addr::unchecked("creator"), &[]);
+        let info = message_info(&addr("creator"), &[]);
 
         let msg = default_instantiate_msg();
 
@@ -434,7 +1669,7 @@ mod tests {
         assert_eq!(response.attributes[0].key, "method");
         assert_eq!(response.attributes[0].value, "instantiate");
         assert_eq!(response.attributes[1].key, "admin");
-        assert_eq!(response.attributes[1].value, "admin");
+        assert_eq!(response.attributes[1].value, addr("admin").as_str());
         assert_eq!(response.attributes[2].key, "rate_limit");
         assert_eq!(response.attributes[2].value, DEFAULT_RATE_LIMIT.to_string());
         assert_eq!(response.attributes[3].key, "tokens_count");
@@ -442,15 +1677,12 @@ mod tests {
 
         // Verify state stored in contract
         let state: State = STATE.load(&deps.storage).unwrap();
-        assert_eq!(state.admin, Addr::unchecked("admin"));
+        assert_eq!(state.admin, addr("admin"));
         assert_eq!(state.rate_limit_seconds, DEFAULT_RATE_LIMIT);
         assert_eq!(state.tokens.len(), 2);
         assert_eq!(state.tokens[0].denom, Denom::Native("untrn".to_string()));
         assert_eq!(state.tokens[0].amount, Uint128::from(DEFAULT_NATIVE_AMOUNT));
-        assert_eq!(
-            state.tokens[1].denom,
-            Denom::Cw20(Addr::unchecked("cw20_token"))
-        );
+        assert_eq!(state.tokens[1].denom, Denom::Cw20(addr("cw20_token")));
         assert_eq!(state.tokens[1].amount, Uint128::from(DEFAULT_CW20_AMOUNT));
     }
 
@@ -467,29 +1699,34 @@ mod tests {
             tokens: vec![TokenConfig {
                 denom: Denom::Native(NATIVE_DENOM.to_string()),
                 amount: Uint128::new(DEFAULT_NATIVE_AMOUNT),
+                stream: None,
+                price: None,
             }],
             rate_limit_seconds: None,
+            opens_at: None,
+            closes_at: None,
         };
 
-        let info = message_info(&Addr::unchecked("creator"), &coins(1000, "token"));
+        let info = message_info(&addr("creator"), &coins(1000, "token"));
         let env = mock_env();
         instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
 
         // First claim should succeed
-        let claim_info = message_info(&Addr::unchecked("user1"), &[]);
-        let claim_res = execute_claim(deps.as_mut(), env.clone(), claim_info.clone()).unwrap();
+        let claim_info = message_info(&addr("user1"), &[]);
+        let claim_res =
+            execute_claim(deps.as_mut(), env.clone(), claim_info.clone(), None).unwrap();
 
-        assert_eq!(claim_res.attributes.len(), 3);
+        assert_eq!(claim_res.attributes.len(), 2);
         assert_eq!(claim_res.attributes[0].key, "action");
         assert_eq!(claim_res.attributes[0].value, "claim");
         assert_eq!(claim_res.attributes[1].key, "recipient");
-        assert_eq!(claim_res.attributes[1].value, "user1");
-        assert!(claim_res.messages.len() > 0);
+        assert_eq!(claim_res.attributes[1].value, addr("user1").as_str());
+        assert!(!claim_res.messages.is_empty());
 
         // Verify the BankMsg::Send was created
         match &claim_res.messages[0].msg {
             CosmosMsg::Bank(BankMsg::Send { to_address, amount }) => {
-                assert_eq!(to_address, "user1");
+                assert_eq!(to_address, addr("user1").as_str());
                 assert_eq!(amount[0].amount, Uint128::new(DEFAULT_NATIVE_AMOUNT));
                 assert_eq!(amount[0].denom, NATIVE_DENOM);
             }
@@ -497,7 +1734,7 @@ mod tests {
         }
 
         // Second claim should fail due to rate limiting
-        let err = execute_claim(deps.as_mut(), env.clone(), claim_info.clone()).unwrap_err();
+        let err = execute_claim(deps.as_mut(), env.clone(), claim_info.clone(), None).unwrap_err();
         assert!(err.to_string().contains("Rate limit exceeded"));
     }
 
@@ -509,18 +1746,22 @@ mod tests {
         let msg = InstantiateMsg {
             admin: None,
             tokens: vec![TokenConfig {
-                denom: Denom::Cw20(Addr::unchecked("token_contract")),
+                denom: Denom::Cw20(addr("token_contract")),
                 amount: Uint128::new(DEFAULT_CW20_AMOUNT),
+                stream: None,
+                price: None,
             }],
             rate_limit_seconds: None,
+            opens_at: None,
+            closes_at: None,
         };
 
-        let info = message_info(&Addr::unchecked("creator"), &coins(1000, "token"));
+        let info = message_info(&addr("creator"), &coins(1000, "token"));
         let env = mock_env();
         instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
 
         // Mock balance for CW20 tokens
-        let cw20_token_address = String::from("token_contract");
+        let cw20_token_address = addr("token_contract").to_string();
 
         // Clone necessary variables to avoid moving them into the closure
         let cw20_token_address_clone = cw20_token_address.clone();
@@ -533,7 +1774,7 @@ mod tests {
             match query {
                 WasmQuery::Smart { contract_addr, msg } => {
                     if contract_addr == &cw20_token_address.clone() {
-                        if let Ok(cw20::Cw20QueryMsg::Balance { address }) = from_json(&msg) {
+                        if let Ok(cw20::Cw20QueryMsg::Balance { address }) = from_json(msg) {
                             if address == env.contract.address.to_string() {
                                 return SystemResult::Ok(ContractResult::Ok(
                                     to_json_binary(&BalanceResponse {
@@ -555,15 +1796,16 @@ mod tests {
         });
 
         // First claim should succeed
-        let claim_info = message_info(&Addr::unchecked("user1"), &[]);
-        let claim_res = execute_claim(deps.as_mut(), env.clone(), claim_info.clone()).unwrap();
+        let claim_info = message_info(&addr("user1"), &[]);
+        let claim_res =
+            execute_claim(deps.as_mut(), env.clone(), claim_info.clone(), None).unwrap();
 
-        assert_eq!(claim_res.attributes.len(), 3);
+        assert_eq!(claim_res.attributes.len(), 2);
         assert_eq!(claim_res.attributes[0].key, "action");
         assert_eq!(claim_res.attributes[0].value, "claim");
         assert_eq!(claim_res.attributes[1].key, "recipient");
-        assert_eq!(claim_res.attributes[1].value, "user1");
-        assert!(claim_res.messages.len() > 0);
+        assert_eq!(claim_res.attributes[1].value, addr("user1").as_str());
+        assert!(!claim_res.messages.is_empty());
 
         // Verify the WasmMsg::Execute was created for CW20 transfer
         match &claim_res.messages[0].msg {
@@ -572,12 +1814,12 @@ mod tests {
                 msg,
                 funds,
             }) => {
-                assert_eq!(contract_addr, "token_contract");
+                assert_eq!(contract_addr, addr("token_contract").as_str());
                 let expected_msg = Cw20ExecuteMsg::Transfer {
-                    recipient: "user1".to_string(),
+                    recipient: addr("user1").to_string(),
                     amount: Uint128::new(DEFAULT_CW20_AMOUNT),
                 };
-                let actual_msg: Cw20ExecuteMsg = from_json(&msg).unwrap();
+                let actual_msg: Cw20ExecuteMsg = from_json(msg).unwrap();
                 assert_eq!(actual_msg, expected_msg);
                 assert!(funds.is_empty());
             }
@@ -585,7 +1827,7 @@ mod tests {
         }
 
         // Second claim should fail due to rate limiting
-        let err = execute_claim(deps.as_mut(), env.clone(), claim_info.clone()).unwrap_err();
+        let err = execute_claim(deps.as_mut(), env.clone(), claim_info.clone(), None).unwrap_err();
         assert!(err.to_string().contains("Rate limit exceeded"));
     }
 
@@ -595,31 +1837,39 @@ mod tests {
 
         // Initialize the contract
         let init_msg = InstantiateMsg {
-            admin: Some(Addr::unchecked("admin")),
+            admin: Some(addr("admin")),
             tokens: vec![
                 TokenConfig {
                     denom: Denom::Native(NATIVE_DENOM.to_string()),
                     amount: Uint128::new(DEFAULT_NATIVE_AMOUNT),
+                    stream: None,
+                    price: None,
                 },
                 TokenConfig {
-                    denom: Denom::Cw20(Addr::unchecked("old_token_contract")),
+                    denom: Denom::Cw20(addr("old_token_contract")),
                     amount: Uint128::new(DEFAULT_CW20_AMOUNT),
+                    stream: None,
+                    price: None,
                 },
             ],
             rate_limit_seconds: Some(DEFAULT_RATE_LIMIT),
+            opens_at: None,
+            closes_at: None,
         };
-        let info = message_info(&Addr::unchecked("creator"), &coins(1000, "token"));
+        let info = message_info(&addr("creator"), &coins(1000, "token"));
         let env = mock_env();
         instantiate(deps.as_mut(), env.clone(), info.clone(), init_msg).unwrap();
 
         // New token config to update
         let new_token_config = TokenConfig {
-            denom: Denom::Cw20(Addr::unchecked("new_token_contract")),
+            denom: Denom::Cw20(addr("new_token_contract")),
             amount: Uint128::new(200_000_000),
+            stream: None,
+            price: None,
         };
 
         // Execute the update token config function
-        let update_info = message_info(&Addr::unchecked("admin"), &[]);
+        let update_info = message_info(&addr("admin"), &[]);
         let res =
             execute_update_token_config(deps.as_mut(), update_info, 1, new_token_config.clone())
                 .unwrap();
@@ -631,10 +1881,7 @@ mod tests {
         assert_eq!(res.attributes[1].key, "token_index");
         assert_eq!(res.attributes[1].value, "1");
         assert_eq!(res.attributes[2].key, "new_denom");
-        assert_eq!(
-            res.attributes[2].value,
-            format!("{:?}", new_token_config.denom)
-        );
+        assert_eq!(res.attributes[2].value, denom_key(&new_token_config.denom));
         assert_eq!(res.attributes[3].key, "new_amount");
         assert_eq!(res.attributes[3].value, new_token_config.amount.to_string());
 
@@ -649,36 +1896,44 @@ mod tests {
 
         // Initialize the contract
         let init_msg = InstantiateMsg {
-            admin: Some(Addr::unchecked("admin")),
+            admin: Some(addr("admin")),
             tokens: vec![
                 TokenConfig {
                     denom: Denom::Native(NATIVE_DENOM.to_string()),
                     amount: Uint128::new(DEFAULT_NATIVE_AMOUNT),
+                    stream: None,
+                    price: None,
                 },
                 TokenConfig {
-                    denom: Denom::Cw20(Addr::unchecked("old_token_contract")),
+                    denom: Denom::Cw20(addr("old_token_contract")),
                     amount: Uint128::new(DEFAULT_CW20_AMOUNT),
+                    stream: None,
+                    price: None,
                 },
             ],
             rate_limit_seconds: Some(DEFAULT_RATE_LIMIT),
+            opens_at: None,
+            closes_at: None,
         };
-        let info = message_info(&Addr::unchecked("creator"), &coins(1000, "token"));
+        let info = message_info(&addr("creator"), &coins(1000, "token"));
         let env = mock_env();
         instantiate(deps.as_mut(), env.clone(), info.clone(), init_msg).unwrap();
 
         // New token config to update
         let new_token_config = TokenConfig {
-            denom: Denom::Cw20(Addr::unchecked("new_token_contract")),
+            denom: Denom::Cw20(addr("new_token_contract")),
             amount: Uint128::new(200_000_000),
+            stream: None,
+            price: None,
         };
 
         // Attempt to update token config by a non-admin
-        let update_info = message_info(&Addr::unchecked("user"), &[]);
+        let update_info = message_info(&addr("user"), &[]);
         let err = execute_update_token_config(deps.as_mut(), update_info, 1, new_token_config)
             .unwrap_err();
 
         // Verify error message
-        assert_eq!(err.to_string(), "Generic error: Unauthorized");
+        assert_eq!(err, ContractError::Unauthorized {});
     }
 
     #[test]
@@ -687,36 +1942,44 @@ mod tests {
 
         // Initialize the contract
         let init_msg = InstantiateMsg {
-            admin: Some(Addr::unchecked("admin")),
+            admin: Some(addr("admin")),
             tokens: vec![
                 TokenConfig {
                     denom: Denom::Native(NATIVE_DENOM.to_string()),
                     amount: Uint128::new(DEFAULT_NATIVE_AMOUNT),
+                    stream: None,
+                    price: None,
                 },
                 TokenConfig {
-                    denom: Denom::Cw20(Addr::unchecked("old_token_contract")),
+                    denom: Denom::Cw20(addr("old_token_contract")),
                     amount: Uint128::new(DEFAULT_CW20_AMOUNT),
+                    stream: None,
+                    price: None,
                 },
             ],
             rate_limit_seconds: Some(DEFAULT_RATE_LIMIT),
+            opens_at: None,
+            closes_at: None,
         };
-        let info = message_info(&Addr::unchecked("creator"), &coins(1000, "token"));
+        let info = message_info(&addr("creator"), &coins(1000, "token"));
         let env = mock_env();
         instantiate(deps.as_mut(), env.clone(), info.clone(), init_msg).unwrap();
 
         // New token config to update
         let new_token_config = TokenConfig {
-            denom: Denom::Cw20(Addr::unchecked("new_token_contract")),
+            denom: Denom::Cw20(addr("new_token_contract")),
             amount: Uint128::new(200_000_000),
+            stream: None,
+            price: None,
         };
 
         // Attempt to update token config with an invalid index
-        let update_info = message_info(&Addr::unchecked("admin"), &[]);
+        let update_info = message_info(&addr("admin"), &[]);
         let err = execute_update_token_config(deps.as_mut(), update_info, 5, new_token_config)
             .unwrap_err();
 
         // Verify error message
-        assert_eq!(err.to_string(), "Generic error: Invalid token index");
+        assert_eq!(err, ContractError::InvalidTokenIndex {});
     }
 
     #[test]
@@ -725,25 +1988,31 @@ mod tests {
 
         // Initialize the contract
         let init_msg = InstantiateMsg {
-            admin: Some(Addr::unchecked("admin")),
+            admin: Some(addr("admin")),
             tokens: vec![
                 TokenConfig {
                     denom: Denom::Native(NATIVE_DENOM.to_string()),
                     amount: Uint128::new(DEFAULT_NATIVE_AMOUNT),
+                    stream: None,
+                    price: None,
                 },
                 TokenConfig {
-                    denom: Denom::Cw20(Addr::unchecked("old_token_contract")),
+                    denom: Denom::Cw20(addr("old_token_contract")),
                     amount: Uint128::new(DEFAULT_CW20_AMOUNT),
+                    stream: None,
+                    price: None,
                 },
             ],
             rate_limit_seconds: Some(DEFAULT_RATE_LIMIT),
+            opens_at: None,
+            closes_at: None,
         };
-        let info = message_info(&Addr::unchecked("creator"), &coins(1000, "token"));
+        let info = message_info(&addr("creator"), &coins(1000, "token"));
         let env = mock_env();
         instantiate(deps.as_mut(), env.clone(), info.clone(), init_msg).unwrap();
 
         // Execute the update rate limit function
-        let update_info = message_info(&Addr::unchecked("admin"), &[]);
+        let update_info = message_info(&addr("admin"), &[]);
         let new_rate_limit = 3600; // 1 hour
         let res = execute_update_rate_limit(deps.as_mut(), update_info, new_rate_limit).unwrap();
 
@@ -765,31 +2034,37 @@ mod tests {
 
         // Initialize the contract
         let init_msg = InstantiateMsg {
-            admin: Some(Addr::unchecked("admin")),
+            admin: Some(addr("admin")),
             tokens: vec![
                 TokenConfig {
                     denom: Denom::Native(NATIVE_DENOM.to_string()),
                     amount: Uint128::new(DEFAULT_NATIVE_AMOUNT),
+                    stream: None,
+                    price: None,
                 },
                 TokenConfig {
-                    denom: Denom::Cw20(Addr::unchecked("old_token_contract")),
+                    denom: Denom::Cw20(addr("old_token_contract")),
                     amount: Uint128::new(DEFAULT_CW20_AMOUNT),
+                    stream: None,
+                    price: None,
                 },
             ],
             rate_limit_seconds: Some(DEFAULT_RATE_LIMIT),
+            opens_at: None,
+            closes_at: None,
         };
-        let info = message_info(&Addr::unchecked("creator"), &coins(1000, "token"));
+        let info = message_info(&addr("creator"), &coins(1000, "token"));
         let env = mock_env();
         instantiate(deps.as_mut(), env.clone(), info.clone(), init_msg).unwrap();
 
         // Attempt to update rate limit by a non-admin
-        let update_info = message_info(&Addr::unchecked("user"), &[]);
+        let update_info = message_info(&addr("user"), &[]);
         let new_rate_limit = 3600; // 1 hour
         let err =
             execute_update_rate_limit(deps.as_mut(), update_info, new_rate_limit).unwrap_err();
 
         // Verify error message
-        assert_eq!(err.to_string(), "Generic error: Unauthorized");
+        assert_eq!(err, ContractError::Unauthorized {});
     }
 
     #[test]
@@ -798,26 +2073,32 @@ mod tests {
 
         // Initialize the contract
         let init_msg = InstantiateMsg {
-            admin: Some(Addr::unchecked("current_admin")),
+            admin: Some(addr("current_admin")),
             tokens: vec![
                 TokenConfig {
                     denom: Denom::Native(NATIVE_DENOM.to_string()),
                     amount: Uint128::new(DEFAULT_NATIVE_AMOUNT),
+                    stream: None,
+                    price: None,
                 },
                 TokenConfig {
-                    denom: Denom::Cw20(Addr::unchecked("old_token_contract")),
+                    denom: Denom::Cw20(addr("old_token_contract")),
                     amount: Uint128::new(DEFAULT_CW20_AMOUNT),
+                    stream: None,
+                    price: None,
                 },
             ],
             rate_limit_seconds: Some(DEFAULT_RATE_LIMIT),
+            opens_at: None,
+            closes_at: None,
         };
-        let info = message_info(&Addr::unchecked("creator"), &coins(1000, "token"));
+        let info = message_info(&addr("creator"), &coins(1000, "token"));
         let env = mock_env();
         instantiate(deps.as_mut(), env.clone(), info.clone(), init_msg).unwrap();
 
         // Execute the set admin function
-        let update_info = message_info(&Addr::unchecked("current_admin"), &[]);
-        let new_admin = "new_admin".to_string();
+        let update_info = message_info(&addr("current_admin"), &[]);
+        let new_admin = addr("new_admin").to_string();
         let res = execute_set_admin(deps.as_mut(), update_info, new_admin.clone()).unwrap();
 
         // Verify the response
@@ -829,7 +2110,7 @@ mod tests {
 
         // Verify state
         let state = STATE.load(&deps.storage).unwrap();
-        assert_eq!(state.admin, Addr::unchecked("new_admin"));
+        assert_eq!(state.admin, addr("new_admin"));
     }
 
     #[test]
@@ -838,30 +2119,36 @@ mod tests {
 
         // Initialize the contract
         let init_msg = InstantiateMsg {
-            admin: Some(Addr::unchecked("current_admin")),
+            admin: Some(addr("current_admin")),
             tokens: vec![
                 TokenConfig {
                     denom: Denom::Native(NATIVE_DENOM.to_string()),
                     amount: Uint128::new(DEFAULT_NATIVE_AMOUNT),
+                    stream: None,
+                    price: None,
                 },
                 TokenConfig {
-                    denom: Denom::Cw20(Addr::unchecked("old_token_contract")),
+                    denom: Denom::Cw20(addr("old_token_contract")),
                     amount: Uint128::new(DEFAULT_CW20_AMOUNT),
+                    stream: None,
+                    price: None,
                 },
             ],
             rate_limit_seconds: Some(DEFAULT_RATE_LIMIT),
+            opens_at: None,
+            closes_at: None,
         };
-        let info = message_info(&Addr::unchecked("creator"), &coins(1000, "token"));
+        let info = message_info(&addr("creator"), &coins(1000, "token"));
         let env = mock_env();
         instantiate(deps.as_mut(), env.clone(), info.clone(), init_msg).unwrap();
 
         // Attempt to set admin by a non-admin
-        let update_info = message_info(&Addr::unchecked("user"), &[]);
-        let new_admin = "new_admin".to_string();
+        let update_info = message_info(&addr("user"), &[]);
+        let new_admin = addr("new_admin").to_string();
         let err = execute_set_admin(deps.as_mut(), update_info, new_admin).unwrap_err();
 
         // Verify error message
-        assert_eq!(err.to_string(), "Generic error: Unauthorized");
+        assert_eq!(err, ContractError::Unauthorized {});
     }
 
     #[test]
@@ -870,20 +2157,26 @@ mod tests {
 
         // Initialize the contract
         let init_msg = InstantiateMsg {
-            admin: Some(Addr::unchecked("admin")),
+            admin: Some(addr("admin")),
             tokens: vec![
                 TokenConfig {
                     denom: Denom::Native(NATIVE_DENOM.to_string()),
                     amount: Uint128::new(DEFAULT_NATIVE_AMOUNT),
+                    stream: None,
+                    price: None,
                 },
                 TokenConfig {
-                    denom: Denom::Cw20(Addr::unchecked("token_contract")),
+                    denom: Denom::Cw20(addr("token_contract")),
                     amount: Uint128::new(DEFAULT_CW20_AMOUNT),
+                    stream: None,
+                    price: None,
                 },
             ],
             rate_limit_seconds: Some(DEFAULT_RATE_LIMIT),
+            opens_at: None,
+            closes_at: None,
         };
-        let info = message_info(&Addr::unchecked("creator"), &coins(1000, "token"));
+        let info = message_info(&addr("creator"), &coins(1000, "token"));
         let env = mock_env();
         instantiate(deps.as_mut(), env.clone(), info.clone(), init_msg).unwrap();
 
@@ -894,10 +2187,7 @@ mod tests {
         assert_eq!(res.tokens.len(), 2);
         assert_eq!(res.tokens[0].denom, Denom::Native(NATIVE_DENOM.to_string()));
         assert_eq!(res.tokens[0].amount, Uint128::new(DEFAULT_NATIVE_AMOUNT));
-        assert_eq!(
-            res.tokens[1].denom,
-            Denom::Cw20(Addr::unchecked("token_contract"))
-        );
+        assert_eq!(res.tokens[1].denom, Denom::Cw20(addr("token_contract")));
         assert_eq!(res.tokens[1].amount, Uint128::new(DEFAULT_CW20_AMOUNT));
     }
 
@@ -907,20 +2197,26 @@ mod tests {
 
         // Initialize the contract
         let init_msg = InstantiateMsg {
-            admin: Some(Addr::unchecked("admin")),
+            admin: Some(addr("admin")),
             tokens: vec![
                 TokenConfig {
                     denom: Denom::Native(NATIVE_DENOM.to_string()),
                     amount: Uint128::new(DEFAULT_NATIVE_AMOUNT),
+                    stream: None,
+                    price: None,
                 },
                 TokenConfig {
-                    denom: Denom::Cw20(Addr::unchecked("token_contract")),
+                    denom: Denom::Cw20(addr("token_contract")),
                     amount: Uint128::new(DEFAULT_CW20_AMOUNT),
+                    stream: None,
+                    price: None,
                 },
             ],
             rate_limit_seconds: Some(DEFAULT_RATE_LIMIT),
+            opens_at: None,
+            closes_at: None,
         };
-        let info = message_info(&Addr::unchecked("creator"), &coins(1000, "token"));
+        let info = message_info(&addr("creator"), &coins(1000, "token"));
         let env = mock_env();
         instantiate(deps.as_mut(), env.clone(), info.clone(), init_msg).unwrap();
 
@@ -937,34 +2233,40 @@ mod tests {
 
         // Initialize the contract
         let init_msg = InstantiateMsg {
-            admin: Some(Addr::unchecked("admin")),
+            admin: Some(addr("admin")),
             tokens: vec![
                 TokenConfig {
                     denom: Denom::Native(NATIVE_DENOM.to_string()),
                     amount: Uint128::new(DEFAULT_NATIVE_AMOUNT),
+                    stream: None,
+                    price: None,
                 },
                 TokenConfig {
-                    denom: Denom::Cw20(Addr::unchecked("token_contract")),
+                    denom: Denom::Cw20(addr("token_contract")),
                     amount: Uint128::new(DEFAULT_CW20_AMOUNT),
+                    stream: None,
+                    price: None,
                 },
             ],
             rate_limit_seconds: Some(DEFAULT_RATE_LIMIT),
+            opens_at: None,
+            closes_at: None,
         };
-        let info = message_info(&Addr::unchecked("creator"), &coins(1000, "token"));
+        let info = message_info(&addr("creator"), &coins(1000, "token"));
         let env = mock_env();
         instantiate(deps.as_mut(), env.clone(), info.clone(), init_msg).unwrap();
 
         // Set user info
-        let user_addr = Addr::unchecked("user1");
+        let user_addr = addr("user1");
         let user_info = UserInfo {
             last_claim_time: env.block.time.seconds(),
         };
-        USER_CLAIMS
+        user_claims()
             .save(&mut deps.storage, &user_addr, &user_info)
             .unwrap();
 
         // Query user info
-        let res = query_user_info(deps.as_ref(), "user1".to_string()).unwrap();
+        let res = query_user_info(deps.as_ref(), mock_env(), addr("user1").to_string()).unwrap();
 
         // Verify the response
         assert_eq!(res.user_info, Some(user_info));
@@ -976,25 +2278,36 @@ mod tests {
 
         // Initialize the contract
         let init_msg = InstantiateMsg {
-            admin: Some(Addr::unchecked("admin")),
+            admin: Some(addr("admin")),
             tokens: vec![
                 TokenConfig {
                     denom: Denom::Native(NATIVE_DENOM.to_string()),
                     amount: Uint128::new(DEFAULT_NATIVE_AMOUNT),
+                    stream: None,
+                    price: None,
                 },
                 TokenConfig {
-                    denom: Denom::Cw20(Addr::unchecked("token_contract")),
+                    denom: Denom::Cw20(addr("token_contract")),
                     amount: Uint128::new(DEFAULT_CW20_AMOUNT),
+                    stream: None,
+                    price: None,
                 },
             ],
             rate_limit_seconds: Some(DEFAULT_RATE_LIMIT),
+            opens_at: None,
+            closes_at: None,
         };
-        let info = message_info(&Addr::unchecked("creator"), &coins(1000, "token"));
+        let info = message_info(&addr("creator"), &coins(1000, "token"));
         let env = mock_env();
         instantiate(deps.as_mut(), env.clone(), info.clone(), init_msg).unwrap();
 
         // Query user info for a non-existent user
-        let res = query_user_info(deps.as_ref(), "nonexistent_user".to_string()).unwrap();
+        let res = query_user_info(
+            deps.as_ref(),
+            mock_env(),
+            addr("nonexistent_user").to_string(),
+        )
+        .unwrap();
 
         // Verify the response
         assert_eq!(res.user_info, None);
@@ -1006,20 +2319,26 @@ mod tests {
 
         // Initialize the contract
         let init_msg = InstantiateMsg {
-            admin: Some(Addr::unchecked("admin")),
+            admin: Some(addr("admin")),
             tokens: vec![
                 TokenConfig {
                     denom: Denom::Native(NATIVE_DENOM.to_string()),
                     amount: Uint128::new(DEFAULT_NATIVE_AMOUNT),
+                    stream: None,
+                    price: None,
                 },
                 TokenConfig {
-                    denom: Denom::Cw20(Addr::unchecked("token_contract")),
+                    denom: Denom::Cw20(addr("token_contract")),
                     amount: Uint128::new(DEFAULT_CW20_AMOUNT),
+                    stream: None,
+                    price: None,
                 },
             ],
             rate_limit_seconds: Some(DEFAULT_RATE_LIMIT),
+            opens_at: None,
+            closes_at: None,
         };
-        let info = message_info(&Addr::unchecked("creator"), &coins(1000, "token"));
+        let info = message_info(&addr("creator"), &coins(1000, "token"));
         let env = mock_env();
         instantiate(deps.as_mut(), env.clone(), info.clone(), init_msg).unwrap();
 
@@ -1027,7 +2346,7 @@ mod tests {
         let res = query_admin(deps.as_ref()).unwrap();
 
         // Verify the response
-        assert_eq!(res.admin, "admin".to_string());
+        assert_eq!(res.admin, addr("admin").to_string());
     }
 
     #[test]
@@ -1039,7 +2358,7 @@ mod tests {
         }]);
 
         // Mock balance for CW20 tokens
-        let cw20_token_address = String::from("token_contract");
+        let cw20_token_address = addr("token_contract").to_string();
 
         // Clone necessary variables to avoid moving them into the closure
         let cw20_token_address_clone = cw20_token_address.clone();
@@ -1052,7 +2371,7 @@ mod tests {
             match query {
                 WasmQuery::Smart { contract_addr, msg } => {
                     if contract_addr == &cw20_token_address.clone() {
-                        if let Ok(cw20::Cw20QueryMsg::Balance { address }) = from_json(&msg) {
+                        if let Ok(cw20::Cw20QueryMsg::Balance { address }) = from_json(msg) {
                             if address == env.contract.address.to_string() {
                                 return SystemResult::Ok(ContractResult::Ok(
                                     to_json_binary(&BalanceResponse {
@@ -1075,20 +2394,26 @@ mod tests {
 
         // Initialize the contract
         let init_msg = InstantiateMsg {
-            admin: Some(Addr::unchecked("admin")),
+            admin: Some(addr("admin")),
             tokens: vec![
                 TokenConfig {
                     denom: Denom::Native(NATIVE_DENOM.to_string()),
                     amount: Uint128::new(DEFAULT_NATIVE_AMOUNT),
+                    stream: None,
+                    price: None,
                 },
                 TokenConfig {
-                    denom: Denom::Cw20(Addr::unchecked("token_contract")),
+                    denom: Denom::Cw20(addr("token_contract")),
                     amount: Uint128::new(DEFAULT_CW20_AMOUNT),
+                    stream: None,
+                    price: None,
                 },
             ],
             rate_limit_seconds: Some(DEFAULT_RATE_LIMIT),
+            opens_at: None,
+            closes_at: None,
         };
-        let info = message_info(&Addr::unchecked("creator"), &coins(1000, "token"));
+        let info = message_info(&addr("creator"), &coins(1000, "token"));
         let env = mock_env();
         instantiate(deps.as_mut(), env.clone(), info.clone(), init_msg).unwrap();
 
@@ -1109,7 +2434,7 @@ mod tests {
 
         // Verify CW20 balance
         if let Balance::Cw20(cw20_balance) = &res.balances[1] {
-            assert_eq!(cw20_balance.address, Addr::unchecked("token_contract"));
+            assert_eq!(cw20_balance.address, addr("token_contract"));
             assert_eq!(cw20_balance.amount, Uint128::new(100_000_000));
         } else {
             panic!("Expected CW20 balance");
@@ -1122,25 +2447,32 @@ mod tests {
 
         // Initialize the contract
         let init_msg = InstantiateMsg {
-            admin: Some(Addr::unchecked("admin")),
+            admin: Some(addr("admin")),
             tokens: vec![
                 TokenConfig {
                     denom: Denom::Native(NATIVE_DENOM.to_string()),
                     amount: Uint128::new(DEFAULT_NATIVE_AMOUNT),
+                    stream: None,
+                    price: None,
                 },
                 TokenConfig {
-                    denom: Denom::Cw20(Addr::unchecked("token_contract")),
+                    denom: Denom::Cw20(addr("token_contract")),
                     amount: Uint128::new(DEFAULT_CW20_AMOUNT),
+                    stream: None,
+                    price: None,
                 },
             ],
             rate_limit_seconds: Some(DEFAULT_RATE_LIMIT),
+            opens_at: None,
+            closes_at: None,
         };
-        let info = message_info(&Addr::unchecked("creator"), &coins(1000, "token"));
+        let info = message_info(&addr("creator"), &coins(1000, "token"));
         let env = mock_env();
         instantiate(deps.as_mut(), env.clone(), info.clone(), init_msg).unwrap();
 
         // Query claim status for a first-time user
-        let res = query_can_user_claim(deps.as_ref(), env.clone(), "user1".to_string()).unwrap();
+        let res =
+            query_can_user_claim(deps.as_ref(), env.clone(), addr("user1").to_string()).unwrap();
 
         // Verify the response
         assert!(res.can_claim);
@@ -1153,34 +2485,41 @@ mod tests {
 
         // Initialize the contract
         let init_msg = InstantiateMsg {
-            admin: Some(Addr::unchecked("admin")),
+            admin: Some(addr("admin")),
             tokens: vec![
                 TokenConfig {
                     denom: Denom::Native(NATIVE_DENOM.to_string()),
                     amount: Uint128::new(DEFAULT_NATIVE_AMOUNT),
+                    stream: None,
+                    price: None,
                 },
                 TokenConfig {
-                    denom: Denom::Cw20(Addr::unchecked("token_contract")),
+                    denom: Denom::Cw20(addr("token_contract")),
                     amount: Uint128::new(DEFAULT_CW20_AMOUNT),
+                    stream: None,
+                    price: None,
                 },
             ],
             rate_limit_seconds: Some(DEFAULT_RATE_LIMIT),
+            opens_at: None,
+            closes_at: None,
         };
-        let info = message_info(&Addr::unchecked("creator"), &coins(1000, "token"));
+        let info = message_info(&addr("creator"), &coins(1000, "token"));
         let env = mock_env();
         instantiate(deps.as_mut(), env.clone(), info.clone(), init_msg).unwrap();
 
         // Set user info with a recent claim
-        let user_addr = Addr::unchecked("user1");
+        let user_addr = addr("user1");
         let user_info = UserInfo {
             last_claim_time: env.block.time.seconds(),
         };
-        USER_CLAIMS
+        user_claims()
             .save(&mut deps.storage, &user_addr, &user_info)
             .unwrap();
 
         // Query claim status within the rate limit period
-        let res = query_can_user_claim(deps.as_ref(), env.clone(), "user1".to_string()).unwrap();
+        let res =
+            query_can_user_claim(deps.as_ref(), env.clone(), addr("user1").to_string()).unwrap();
 
         // Verify the response
         assert!(!res.can_claim);
@@ -1193,39 +2532,1253 @@ mod tests {
 
         // Initialize the contract
         let init_msg = InstantiateMsg {
-            admin: Some(Addr::unchecked("admin")),
+            admin: Some(addr("admin")),
             tokens: vec![
                 TokenConfig {
                     denom: Denom::Native(NATIVE_DENOM.to_string()),
                     amount: Uint128::new(DEFAULT_NATIVE_AMOUNT),
+                    stream: None,
+                    price: None,
                 },
                 TokenConfig {
-                    denom: Denom::Cw20(Addr::unchecked("token_contract")),
+                    denom: Denom::Cw20(addr("token_contract")),
                     amount: Uint128::new(DEFAULT_CW20_AMOUNT),
+                    stream: None,
+                    price: None,
                 },
             ],
             rate_limit_seconds: Some(DEFAULT_RATE_LIMIT),
+            opens_at: None,
+            closes_at: None,
         };
-        let info = message_info(&Addr::unchecked("creator"), &coins(1000, "token"));
+        let info = message_info(&addr("creator"), &coins(1000, "token"));
         let mut env = mock_env();
         instantiate(deps.as_mut(), env.clone(), info.clone(), init_msg).unwrap();
 
         // Set user info with an old claim
-        let user_addr = Addr::unchecked("user1");
+        let user_addr = addr("user1");
         let old_claim_time = env.block.time.seconds() - DEFAULT_RATE_LIMIT - 1;
         let user_info = UserInfo {
             last_claim_time: old_claim_time,
         };
-        USER_CLAIMS
+        user_claims()
             .save(&mut deps.storage, &user_addr, &user_info)
             .unwrap();
 
         // Query claim status after the rate limit period has passed
         env.block.time = env.block.time.plus_seconds(DEFAULT_RATE_LIMIT + 1);
-        let res = query_can_user_claim(deps.as_ref(), env.clone(), "user1".to_string()).unwrap();
+        let res =
+            query_can_user_claim(deps.as_ref(), env.clone(), addr("user1").to_string()).unwrap();
 
         // Verify the response
         assert!(res.can_claim);
         assert_eq!(res.seconds_until_next_claim, 0);
     }
+
+    #[test]
+    fn test_address_validation() {
+        let mut deps = mock_dependencies();
+        deps.api = MockApi::default().with_prefix("neutron");
+
+        let admin = deps.api.addr_make("admin");
+        let user = deps.api.addr_make("user1");
+        let uppercase = user.as_str().to_uppercase();
+        let wrong_prefix = MockApi::default().addr_make("user1").to_string();
+
+        let token = |contract: &str| TokenConfig {
+            denom: Denom::Cw20(Addr::unchecked(contract)),
+            amount: Uint128::new(DEFAULT_CW20_AMOUNT),
+            stream: None,
+            price: None,
+        };
+        let init_msg = |admin: &str, tokens: Vec<TokenConfig>| InstantiateMsg {
+            admin: Some(Addr::unchecked(admin)),
+            tokens,
+            rate_limit_seconds: None,
+            opens_at: None,
+            closes_at: None,
+        };
+        let info = message_info(&admin, &[]);
+
+        // Admin and CW20 contract addresses are checked on instantiation
+        for invalid in [&uppercase, &wrong_prefix] {
+            let err = instantiate(
+                deps.as_mut(),
+                mock_env(),
+                info.clone(),
+                init_msg(invalid, vec![]),
+            )
+            .unwrap_err();
+            assert!(
+                matches!(err, ContractError::InvalidAddress { address, .. } if &address == invalid)
+            );
+
+            let err = instantiate(
+                deps.as_mut(),
+                mock_env(),
+                info.clone(),
+                init_msg(admin.as_str(), vec![token(invalid)]),
+            )
+            .unwrap_err();
+            assert!(
+                matches!(err, ContractError::InvalidAddress { address, .. } if &address == invalid)
+            );
+        }
+
+        let contract = deps.api.addr_make("cw20_token");
+        instantiate(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            init_msg(admin.as_str(), vec![token(contract.as_str())]),
+        )
+        .unwrap();
+
+        for invalid in [&uppercase, &wrong_prefix] {
+            // Queries no longer treat malformed addresses as users who never claimed
+            let err = query_user_info(deps.as_ref(), mock_env(), invalid.clone()).unwrap_err();
+            assert!(err.to_string().contains("Invalid address"));
+            let err = query_can_user_claim(deps.as_ref(), mock_env(), invalid.clone()).unwrap_err();
+            assert!(err.to_string().contains("Invalid address"));
+
+            let err = execute_set_admin(deps.as_mut(), info.clone(), invalid.clone()).unwrap_err();
+            assert!(matches!(err, ContractError::InvalidAddress { .. }));
+
+            let err = execute_update_token_config(deps.as_mut(), info.clone(), 0, token(invalid))
+                .unwrap_err();
+            assert!(matches!(err, ContractError::InvalidAddress { .. }));
+
+            let err = execute_withdraw(
+                deps.as_mut(),
+                mock_env(),
+                info.clone(),
+                Denom::Cw20(Addr::unchecked(invalid)),
+                None,
+            )
+            .unwrap_err();
+            assert!(matches!(err, ContractError::InvalidAddress { .. }));
+        }
+
+        // Well formed addresses still work
+        let res = query_can_user_claim(deps.as_ref(), mock_env(), user.to_string()).unwrap();
+        assert!(res.can_claim);
+        execute_set_admin(deps.as_mut(), info, user.to_string()).unwrap();
+        assert_eq!(STATE.load(&deps.storage).unwrap().admin, user);
+    }
+
+    #[test]
+    fn test_pause_and_unpause() {
+        let mut deps = mock_dependencies_with_balance(&coins(1_000_000, NATIVE_DENOM));
+        let env = instantiate_native_faucet(deps.as_mut());
+        let admin = message_info(&addr("admin"), &[]);
+        let user = deps.api.addr_make("user1");
+
+        // Only the admin can pause
+        let err = execute_set_paused(deps.as_mut(), message_info(&user, &[]), true).unwrap_err();
+        assert!(err.to_string().contains("Unauthorized"));
+
+        execute_set_paused(deps.as_mut(), admin.clone(), true).unwrap();
+        let err =
+            execute_claim(deps.as_mut(), env.clone(), message_info(&user, &[]), None).unwrap_err();
+        assert!(err.to_string().contains("Faucet is paused"));
+
+        let res = query_can_user_claim(deps.as_ref(), env.clone(), user.to_string()).unwrap();
+        assert!(!res.can_claim);
+        assert_eq!(res.reason, Some(ClaimBlockedReason::Paused));
+
+        execute_set_paused(deps.as_mut(), admin, false).unwrap();
+        execute_claim(deps.as_mut(), env.clone(), message_info(&user, &[]), None).unwrap();
+
+        // Rate limiting still applies once unpaused
+        let res = query_can_user_claim(deps.as_ref(), env, user.to_string()).unwrap();
+        assert_eq!(
+            res.reason,
+            Some(ClaimBlockedReason::RateLimited {
+                seconds_remaining: DEFAULT_RATE_LIMIT
+            })
+        );
+    }
+
+    #[test]
+    fn test_claim_window() {
+        let mut deps = mock_dependencies_with_balance(&coins(1_000_000, NATIVE_DENOM));
+        let mut env = instantiate_native_faucet(deps.as_mut());
+        let admin = message_info(&addr("admin"), &[]);
+        let user = deps.api.addr_make("user1");
+
+        let opens_at = env.block.time.plus_seconds(100);
+        let closes_at = env.block.time.plus_seconds(200);
+
+        // The window must open before it closes
+        let err = execute_set_claim_window(
+            deps.as_mut(),
+            admin.clone(),
+            Some(closes_at),
+            Some(opens_at),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("must open before it closes"));
+
+        execute_set_claim_window(deps.as_mut(), admin, Some(opens_at), Some(closes_at)).unwrap();
+        let res = query_claim_window(deps.as_ref()).unwrap();
+        assert_eq!(res.opens_at, Some(opens_at));
+        assert_eq!(res.closes_at, Some(closes_at));
+
+        // Before the window opens
+        let err =
+            execute_claim(deps.as_mut(), env.clone(), message_info(&user, &[]), None).unwrap_err();
+        assert!(err.to_string().contains("Faucet opens at"));
+        let res = query_can_user_claim(deps.as_ref(), env.clone(), user.to_string()).unwrap();
+        assert_eq!(
+            res.reason,
+            Some(ClaimBlockedReason::NotOpenYet { opens_at })
+        );
+        assert_eq!(res.seconds_until_next_claim, 100);
+
+        // Inside the window
+        env.block.time = opens_at;
+        execute_claim(deps.as_mut(), env.clone(), message_info(&user, &[]), None).unwrap();
+
+        // After the window closes
+        env.block.time = closes_at;
+        let res = query_can_user_claim(deps.as_ref(), env.clone(), user.to_string()).unwrap();
+        assert!(!res.can_claim);
+        assert_eq!(
+            res.reason,
+            Some(ClaimBlockedReason::Closed {
+                closed_at: closes_at
+            })
+        );
+        let other = deps.api.addr_make("user2");
+        let err = execute_claim(deps.as_mut(), env, message_info(&other, &[]), None).unwrap_err();
+        assert!(err.to_string().contains("Faucet closed at"));
+    }
+
+    #[test]
+    fn test_roles() {
+        let mut deps = mock_dependencies();
+        instantiate_native_faucet(deps.as_mut());
+        let admin = message_info(&addr("admin"), &[]);
+        let operator = deps.api.addr_make("operator");
+        let pauser = deps.api.addr_make("pauser");
+
+        // Only the admin can grant roles, and ownership moves with SetAdmin
+        let err = execute_grant_role(
+            deps.as_mut(),
+            message_info(&operator, &[]),
+            operator.to_string(),
+            Role::Operator,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("Unauthorized"));
+        let err = execute_grant_role(
+            deps.as_mut(),
+            admin.clone(),
+            operator.to_string(),
+            Role::Owner,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("transferred with SetAdmin"));
+
+        execute_grant_role(
+            deps.as_mut(),
+            admin.clone(),
+            operator.to_string(),
+            Role::Operator,
+        )
+        .unwrap();
+        execute_grant_role(
+            deps.as_mut(),
+            admin.clone(),
+            pauser.to_string(),
+            Role::Pauser,
+        )
+        .unwrap();
+
+        // Operators tune the config but can't pause or move the admin
+        execute_update_rate_limit(deps.as_mut(), message_info(&operator, &[]), 60).unwrap();
+        let err =
+            execute_set_paused(deps.as_mut(), message_info(&operator, &[]), true).unwrap_err();
+        assert!(err.to_string().contains("Unauthorized"));
+        let err = execute_set_admin(
+            deps.as_mut(),
+            message_info(&operator, &[]),
+            operator.to_string(),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("Unauthorized"));
+
+        // Pausers only pause
+        execute_set_paused(deps.as_mut(), message_info(&pauser, &[]), true).unwrap();
+        let err =
+            execute_update_rate_limit(deps.as_mut(), message_info(&pauser, &[]), 1).unwrap_err();
+        assert!(err.to_string().contains("Unauthorized"));
+
+        let res = query_roles(deps.as_ref(), None, None).unwrap();
+        assert_eq!(res.owner, addr("admin").as_str());
+        assert_eq!(res.members.len(), 2);
+        let res = query_roles(deps.as_ref(), Some(res.members[0].address.clone()), None).unwrap();
+        assert_eq!(res.members.len(), 1);
+
+        // Revoked roles no longer pass the guard
+        execute_revoke_role(
+            deps.as_mut(),
+            admin.clone(),
+            operator.to_string(),
+            Role::Operator,
+        )
+        .unwrap();
+        let err =
+            execute_update_rate_limit(deps.as_mut(), message_info(&operator, &[]), 1).unwrap_err();
+        assert!(err.to_string().contains("Unauthorized"));
+        let err = execute_revoke_role(deps.as_mut(), admin, operator.to_string(), Role::Operator)
+            .unwrap_err();
+        assert!(err.to_string().contains("does not have the Operator role"));
+
+        let res = query_roles(deps.as_ref(), None, None).unwrap();
+        assert_eq!(
+            res.members,
+            vec![RoleMember {
+                address: pauser.to_string(),
+                roles: vec![Role::Pauser],
+            }]
+        );
+        assert!(query_claim_window(deps.as_ref()).unwrap().paused);
+    }
+
+    #[test]
+    fn test_pow_challenge() {
+        use crate::pow::{
+            execute_rotate_pow_seed, execute_set_pow_config, leading_zero_bits, pow_hash,
+            query_pow_challenge, solve, PowConfig,
+        };
+
+        let mut deps = mock_dependencies_with_balance(&coins(1_000_000, NATIVE_DENOM));
+        let mut env = instantiate_native_faucet(deps.as_mut());
+        env.block.height = 1_050;
+        let admin = message_info(&addr("admin"), &[]);
+        let config = PowConfig {
+            difficulty: 8,
+            epoch_blocks: 100,
+        };
+
+        let err = execute_set_pow_config(
+            deps.as_mut(),
+            env.clone(),
+            admin.clone(),
+            Some(PowConfig {
+                difficulty: 33,
+                epoch_blocks: 100,
+            }),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("cannot exceed 32 bits"));
+        execute_set_pow_config(deps.as_mut(), env.clone(), admin, Some(config)).unwrap();
+
+        let challenge = query_pow_challenge(deps.as_ref()).unwrap().unwrap();
+        assert_eq!(challenge.epoch, 10);
+        assert_eq!(challenge.next_epoch_height, 1_100);
+
+        // A nonce is required and must solve the current challenge
+        let user1 = deps.api.addr_make("user1");
+        let err =
+            execute_claim(deps.as_mut(), env.clone(), message_info(&user1, &[]), None).unwrap_err();
+        assert!(err.to_string().contains("nonce required"));
+        let bad_nonce = (0..)
+            .find(|nonce| leading_zero_bits(&pow_hash(user1.as_str(), *nonce, &challenge.seed)) < 8)
+            .unwrap();
+        let err = execute_claim(
+            deps.as_mut(),
+            env.clone(),
+            message_info(&user1, &[]),
+            Some(bad_nonce),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("Invalid proof of work"));
+
+        let nonce = solve(user1.as_str(), &challenge.seed, 8).unwrap();
+        execute_claim(
+            deps.as_mut(),
+            env.clone(),
+            message_info(&user1, &[]),
+            Some(nonce),
+        )
+        .unwrap();
+
+        // Solutions are bound to the sender
+        let user2 = deps.api.addr_make("user2");
+        let user2_nonce = solve(user2.as_str(), &challenge.seed, 8).unwrap();
+        let user3 = deps.api.addr_make("user3");
+        assert!(execute_claim(
+            deps.as_mut(),
+            env.clone(),
+            message_info(&user3, &[]),
+            Some(user2_nonce)
+        )
+        .is_err());
+
+        // The first claim of the next epoch rotates the seed, solutions for the previous
+        // seed are still accepted, older ones are not
+        env.block.height = 1_100;
+        execute_claim(
+            deps.as_mut(),
+            env.clone(),
+            message_info(&user2, &[]),
+            Some(user2_nonce),
+        )
+        .unwrap();
+        let rotated = query_pow_challenge(deps.as_ref()).unwrap().unwrap();
+        assert_eq!(rotated.epoch, 11);
+        assert_eq!(rotated.next_epoch_height, 1_200);
+        assert_ne!(rotated.seed, challenge.seed);
+        assert_eq!(rotated.expires_at_height, 1_300);
+        env.block.height = 1_200;
+        let user3_nonce = solve(user3.as_str(), &challenge.seed, 8).unwrap();
+        let err = execute_claim(
+            deps.as_mut(),
+            env.clone(),
+            message_info(&user3, &[]),
+            Some(user3_nonce),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("Invalid proof of work"));
+
+        // Once expired, the seed is no longer accepted and has to be rotated first
+        env.block.height = 1_400;
+        let user3_nonce = solve(user3.as_str(), &rotated.seed, 8).unwrap();
+        let err = execute_claim(
+            deps.as_mut(),
+            env.clone(),
+            message_info(&user3, &[]),
+            Some(user3_nonce),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::PowSeedExpired {});
+        execute_rotate_pow_seed(deps.as_mut(), env.clone()).unwrap();
+        let fresh = query_pow_challenge(deps.as_ref()).unwrap().unwrap();
+        assert_eq!(fresh.epoch, 14);
+        let err = execute_claim(
+            deps.as_mut(),
+            env.clone(),
+            message_info(&user3, &[]),
+            Some(user3_nonce),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidProofOfWork {});
+        let user3_nonce = solve(user3.as_str(), &fresh.seed, 8).unwrap();
+        execute_claim(
+            deps.as_mut(),
+            env,
+            message_info(&user3, &[]),
+            Some(user3_nonce),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_stream_mode() {
+        use crate::state::{StreamConfig, VESTING_TOTALS};
+
+        // The faucet holds just enough for one streamed claim
+        let mut deps = mock_dependencies_with_balance(&coins(DEFAULT_NATIVE_AMOUNT, NATIVE_DENOM));
+        let mut env = mock_env();
+        let msg = InstantiateMsg {
+            admin: Some(addr("admin")),
+            tokens: vec![TokenConfig {
+                denom: Denom::Native(NATIVE_DENOM.to_string()),
+                amount: Uint128::new(DEFAULT_NATIVE_AMOUNT),
+                stream: Some(StreamConfig {
+                    duration_seconds: 1_000,
+                }),
+                price: None,
+            }],
+            rate_limit_seconds: Some(0),
+            opens_at: None,
+            closes_at: None,
+        };
+        let admin = message_info(&addr("admin"), &[]);
+        instantiate(deps.as_mut(), env.clone(), admin.clone(), msg).unwrap();
+
+        // Claiming starts a stream instead of sending tokens
+        let user = deps.api.addr_make("user1");
+        let res =
+            execute_claim(deps.as_mut(), env.clone(), message_info(&user, &[]), None).unwrap();
+        assert!(res.messages.is_empty());
+        let start = env.block.time.seconds();
+
+        let res = query_user_info(deps.as_ref(), env.clone(), user.to_string()).unwrap();
+        assert_eq!(res.vesting.len(), 1);
+        assert_eq!(res.vesting[0].end_time, start + 1_000);
+        assert_eq!(res.withdrawable[0].amount, Uint128::zero());
+
+        // Streamed tokens are reserved from later claims and admin withdrawals
+        let other = deps.api.addr_make("user2");
+        let err =
+            execute_claim(deps.as_mut(), env.clone(), message_info(&other, &[]), None).unwrap_err();
+        assert!(err.to_string().contains("Insufficient funds"));
+        let err = execute_withdraw(
+            deps.as_mut(),
+            env.clone(),
+            admin,
+            Denom::Native(NATIVE_DENOM.to_string()),
+            Some(Uint128::one()),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("Insufficient funds"));
+
+        let err = execute_withdraw_vested(deps.as_mut(), env.clone(), message_info(&user, &[]))
+            .unwrap_err();
+        assert!(err.to_string().contains("Nothing has vested yet"));
+
+        // A quarter vests after a quarter of the duration
+        env.block.time = env.block.time.plus_seconds(250);
+        let res =
+            execute_withdraw_vested(deps.as_mut(), env.clone(), message_info(&user, &[])).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: user.to_string(),
+                amount: coins(DEFAULT_NATIVE_AMOUNT / 4, NATIVE_DENOM),
+            })
+        );
+
+        // The rest is released once the stream ends and the schedule is closed
+        env.block.time = env.block.time.plus_seconds(10_000);
+        let res =
+            execute_withdraw_vested(deps.as_mut(), env.clone(), message_info(&user, &[])).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: user.to_string(),
+                amount: coins(DEFAULT_NATIVE_AMOUNT * 3 / 4, NATIVE_DENOM),
+            })
+        );
+        let res = query_user_info(deps.as_ref(), env, user.to_string()).unwrap();
+        assert!(res.vesting.is_empty());
+        assert_eq!(
+            VESTING_TOTALS
+                .load(&deps.storage, &format!("native:{}", NATIVE_DENOM))
+                .unwrap(),
+            Uint128::zero()
+        );
+    }
+
+    #[test]
+    fn test_structured_events() {
+        use crate::events::{CLAIM_EVENT, CONFIG_UPDATE_EVENT, EVENT_SCHEMA_VERSION};
+        use crate::state::StreamConfig;
+        use std::collections::HashMap;
+
+        // Index an event's attributes by key, as an indexer would
+        fn attributes<'a>(res: &'a Response, ty: &str) -> HashMap<&'a str, &'a str> {
+            let event = res.events.iter().find(|event| event.ty == ty).unwrap();
+            event
+                .attributes
+                .iter()
+                .map(|attr| (attr.key.as_str(), attr.value.as_str()))
+                .collect()
+        }
+
+        let mut deps = mock_dependencies_with_balance(&coins(1_000_000, NATIVE_DENOM));
+        let env = instantiate_native_faucet(deps.as_mut());
+        let admin = message_info(&addr("admin"), &[]);
+        let stream_token = addr("stream_token");
+        deps.querier.update_wasm(|query| match query {
+            WasmQuery::Smart { .. } => SystemResult::Ok(ContractResult::Ok(
+                to_json_binary(&BalanceResponse {
+                    balance: Uint128::new(1_000_000),
+                })
+                .unwrap(),
+            )),
+            _ => SystemResult::Err(SystemError::Unknown {}),
+        });
+
+        let res = execute::update_config(
+            deps.as_mut(),
+            Some(vec![
+                TokenConfig {
+                    denom: Denom::Native(NATIVE_DENOM.to_string()),
+                    amount: Uint128::new(100),
+                    stream: None,
+                    price: None,
+                },
+                TokenConfig {
+                    denom: Denom::Cw20(stream_token.clone()),
+                    amount: Uint128::new(500),
+                    stream: None,
+                    price: None,
+                },
+            ]),
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(attributes(&res, CONFIG_UPDATE_EVENT)["setting"], "config");
+
+        let res = execute_update_token_config(
+            deps.as_mut(),
+            admin,
+            1,
+            TokenConfig {
+                denom: Denom::Cw20(stream_token.clone()),
+                amount: Uint128::new(500),
+                stream: Some(StreamConfig {
+                    duration_seconds: 60,
+                }),
+                price: None,
+            },
+        )
+        .unwrap();
+        let config = attributes(&res, CONFIG_UPDATE_EVENT);
+        assert_eq!(config["schema_version"], EVENT_SCHEMA_VERSION);
+        assert_eq!(config["setting"], "token_config");
+        assert_eq!(config["denom"], format!("cw20:{}", stream_token));
+        assert_eq!(config["amount"], "500");
+        assert_eq!(config["stream_duration_seconds"], "60");
+
+        let user = deps.api.addr_make("user1");
+        let res = execute_claim(deps.as_mut(), env, message_info(&user, &[]), None).unwrap();
+        let claim = attributes(&res, CLAIM_EVENT);
+        assert_eq!(claim["schema_version"], EVENT_SCHEMA_VERSION);
+        assert_eq!(claim["claim_id"], "1");
+        assert_eq!(claim["claimer"], user.as_str());
+        assert_eq!(claim["multiplier"], "1");
+        assert_eq!(claim["sent.native:untrn"], "100");
+        assert_eq!(
+            claim[format!("streamed.cw20:{}", stream_token).as_str()],
+            "500"
+        );
+    }
+
+    #[test]
+    fn test_deposit_native_records_contributions() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = message_info(&addr("creator"), &[]);
+        instantiate(deps.as_mut(), env.clone(), info, default_instantiate_msg()).unwrap();
+
+        let alice = deps.api.addr_make("alice");
+        let bob = deps.api.addr_make("bob");
+
+        execute_deposit(
+            deps.as_mut(),
+            message_info(&alice, &coins(300, NATIVE_DENOM)),
+        )
+        .unwrap();
+        execute_deposit(deps.as_mut(), message_info(&bob, &coins(100, NATIVE_DENOM))).unwrap();
+        let res = execute_deposit(
+            deps.as_mut(),
+            message_info(&alice, &coins(200, NATIVE_DENOM)),
+        )
+        .unwrap();
+        assert_eq!(res.attributes[0].value, "deposit");
+        assert_eq!(res.attributes[2].value, format!("200 {}", NATIVE_DENOM));
+
+        // Totals and per-depositor amounts are tracked per token
+        let denom = Denom::Native(NATIVE_DENOM.to_string());
+        let res = query_depositors(deps.as_ref(), denom.clone(), None, None).unwrap();
+        assert_eq!(res.total, Uint128::new(600));
+        assert_eq!(res.depositors.len(), 2);
+
+        let res = query_depositor(deps.as_ref(), alice.to_string()).unwrap();
+        assert_eq!(
+            res.deposits,
+            vec![TokenAmount {
+                denom,
+                amount: Uint128::new(500),
+            }]
+        );
+
+        // Tokens the faucet does not hand out are rejected
+        let err =
+            execute_deposit(deps.as_mut(), message_info(&bob, &coins(100, "uatom"))).unwrap_err();
+        assert_eq!(err, ContractError::TokenNotDistributed {});
+
+        // Empty deposits are rejected
+        let err = execute_deposit(deps.as_mut(), message_info(&bob, &[])).unwrap_err();
+        assert_eq!(err, ContractError::NoFunds {});
+    }
+
+    #[test]
+    fn test_receive_cw20_deposit() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = message_info(&addr("creator"), &[]);
+        instantiate(deps.as_mut(), env.clone(), info, default_instantiate_msg()).unwrap();
+
+        let alice = deps.api.addr_make("alice");
+        let msg = Cw20ReceiveMsg {
+            sender: alice.to_string(),
+            amount: Uint128::new(5_000),
+            msg: to_json_binary(&ReceiveMsg::Deposit {}).unwrap(),
+        };
+
+        let token_info = message_info(&addr("cw20_token"), &[]);
+        let res = execute_receive(deps.as_mut(), token_info, msg.clone()).unwrap();
+        assert_eq!(res.attributes[1].value, alice.to_string());
+        assert_eq!(
+            res.attributes[2].value,
+            format!("5000 CW20:{}", addr("cw20_token"))
+        );
+
+        let res =
+            query_depositors(deps.as_ref(), Denom::Cw20(addr("cw20_token")), None, None).unwrap();
+        assert_eq!(res.total, Uint128::new(5_000));
+        assert_eq!(res.depositors[0].address, alice.to_string());
+
+        // Unknown CW20 contracts cannot deposit
+        let other_info = message_info(&addr("other_token"), &[]);
+        let err = execute_receive(deps.as_mut(), other_info, msg).unwrap_err();
+        assert_eq!(err, ContractError::TokenNotDistributed {});
+    }
+
+    #[test]
+    fn test_query_depositors_pagination() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = message_info(&addr("creator"), &[]);
+        instantiate(deps.as_mut(), env.clone(), info, default_instantiate_msg()).unwrap();
+
+        let mut depositors: Vec<Addr> = (0..5)
+            .map(|i| deps.api.addr_make(&format!("depositor{}", i)))
+            .collect();
+        for depositor in depositors.iter() {
+            execute_deposit(
+                deps.as_mut(),
+                message_info(depositor, &coins(10, NATIVE_DENOM)),
+            )
+            .unwrap();
+        }
+        depositors.sort();
+
+        let denom = Denom::Native(NATIVE_DENOM.to_string());
+        let page = query_depositors(deps.as_ref(), denom.clone(), None, Some(3)).unwrap();
+        assert_eq!(page.depositors.len(), 3);
+        assert_eq!(page.total, Uint128::new(50));
+
+        let start_after = page.depositors.last().unwrap().address.clone();
+        let page = query_depositors(deps.as_ref(), denom, Some(start_after), Some(3)).unwrap();
+        let addresses: Vec<String> = page.depositors.into_iter().map(|d| d.address).collect();
+        assert_eq!(
+            addresses,
+            vec![depositors[3].to_string(), depositors[4].to_string()]
+        );
+    }
+
+    #[test]
+    fn test_withdraw_admin() {
+        let mut deps = mock_dependencies_with_balance(&coins(1_000, NATIVE_DENOM));
+        let env = mock_env();
+        let info = message_info(&addr("creator"), &[]);
+        instantiate(deps.as_mut(), env.clone(), info, default_instantiate_msg()).unwrap();
+
+        let denom = Denom::Native(NATIVE_DENOM.to_string());
+
+        // Non-admins cannot withdraw while depositor withdrawals are disabled
+        let user_info = message_info(&addr("user"), &[]);
+        let err = execute_withdraw(deps.as_mut(), env.clone(), user_info, denom.clone(), None)
+            .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // Admin cannot withdraw more than the balance
+        let admin_info = message_info(&addr("admin"), &[]);
+        let err = execute_withdraw(
+            deps.as_mut(),
+            env.clone(),
+            admin_info.clone(),
+            denom.clone(),
+            Some(Uint128::new(1_001)),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InsufficientFunds {});
+
+        // Admin withdraws the full balance by default
+        let res = execute_withdraw(deps.as_mut(), env, admin_info, denom, None).unwrap();
+        assert_eq!(res.attributes[1].value, "admin");
+        match &res.messages[0].msg {
+            CosmosMsg::Bank(BankMsg::Send { to_address, amount }) => {
+                assert_eq!(to_address, addr("admin").as_str());
+                assert_eq!(amount, &coins(1_000, NATIVE_DENOM));
+            }
+            _ => panic!("Unexpected message: {:?}", &res.messages[0].msg),
+        }
+    }
+
+    #[test]
+    fn test_withdraw_depositors_pro_rata() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = message_info(&addr("creator"), &[]);
+        instantiate(deps.as_mut(), env.clone(), info, default_instantiate_msg()).unwrap();
+
+        let alice = deps.api.addr_make("alice");
+        let bob = deps.api.addr_make("bob");
+        execute_deposit(
+            deps.as_mut(),
+            message_info(&alice, &coins(300, NATIVE_DENOM)),
+        )
+        .unwrap();
+        execute_deposit(deps.as_mut(), message_info(&bob, &coins(100, NATIVE_DENOM))).unwrap();
+
+        // Half of the deposited tokens were claimed during the event
+        deps.querier
+            .bank
+            .update_balance(env.contract.address.clone(), coins(200, NATIVE_DENOM));
+
+        let denom = Denom::Native(NATIVE_DENOM.to_string());
+        let err = execute_withdraw(
+            deps.as_mut(),
+            env.clone(),
+            message_info(&alice, &[]),
+            denom.clone(),
+            None,
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        execute_set_depositor_withdrawals(deps.as_mut(), message_info(&addr("admin"), &[]), true)
+            .unwrap();
+
+        // Depositors cannot pick an amount
+        let err = execute_withdraw(
+            deps.as_mut(),
+            env.clone(),
+            message_info(&alice, &[]),
+            denom.clone(),
+            Some(Uint128::new(10)),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::PartialDepositorWithdrawal {});
+
+        // Alice contributed 75% and receives 75% of what is left
+        let res = execute_withdraw(
+            deps.as_mut(),
+            env.clone(),
+            message_info(&alice, &[]),
+            denom.clone(),
+            None,
+        )
+        .unwrap();
+        assert_eq!(res.attributes[1].value, "pro_rata");
+        match &res.messages[0].msg {
+            CosmosMsg::Bank(BankMsg::Send { to_address, amount }) => {
+                assert_eq!(to_address, &alice.to_string());
+                assert_eq!(amount, &coins(150, NATIVE_DENOM));
+            }
+            _ => panic!("Unexpected message: {:?}", &res.messages[0].msg),
+        }
+        deps.querier
+            .bank
+            .update_balance(env.contract.address.clone(), coins(50, NATIVE_DENOM));
+
+        // Alice's contribution is cleared, Bob is left with the remainder
+        let err = execute_withdraw(
+            deps.as_mut(),
+            env.clone(),
+            message_info(&alice, &[]),
+            denom.clone(),
+            None,
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::NoDeposit {});
+
+        let res = execute_withdraw(
+            deps.as_mut(),
+            env,
+            message_info(&bob, &[]),
+            denom.clone(),
+            None,
+        )
+        .unwrap();
+        match &res.messages[0].msg {
+            CosmosMsg::Bank(BankMsg::Send { amount, .. }) => {
+                assert_eq!(amount, &coins(50, NATIVE_DENOM));
+            }
+            _ => panic!("Unexpected message: {:?}", &res.messages[0].msg),
+        }
+
+        let res = query_depositors(deps.as_ref(), denom, None, None).unwrap();
+        assert_eq!(res.total, Uint128::zero());
+        assert!(res.depositors.is_empty());
+    }
+
+    #[test]
+    fn test_withdraw_depositors_ignores_untracked_funds() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = message_info(&addr("creator"), &[]);
+        instantiate(deps.as_mut(), env.clone(), info, default_instantiate_msg()).unwrap();
+        execute_set_depositor_withdrawals(deps.as_mut(), message_info(&addr("admin"), &[]), true)
+            .unwrap();
+
+        // The operator funded the faucet with a plain bank send
+        let alice = deps.api.addr_make("alice");
+        execute_deposit(deps.as_mut(), message_info(&alice, &coins(1, NATIVE_DENOM))).unwrap();
+        deps.querier
+            .bank
+            .update_balance(env.contract.address.clone(), coins(10_001, NATIVE_DENOM));
+
+        // Alice only gets her own deposit back
+        let denom = Denom::Native(NATIVE_DENOM.to_string());
+        let res =
+            execute_withdraw(deps.as_mut(), env, message_info(&alice, &[]), denom, None).unwrap();
+        match &res.messages[0].msg {
+            CosmosMsg::Bank(BankMsg::Send { amount, .. }) => {
+                assert_eq!(amount, &coins(1, NATIVE_DENOM));
+            }
+            _ => panic!("Unexpected message: {:?}", &res.messages[0].msg),
+        }
+    }
+
+    fn instantiate_native_faucet(deps: DepsMut) -> Env {
+        let env = mock_env();
+        let msg = InstantiateMsg {
+            admin: Some(addr("admin")),
+            tokens: vec![TokenConfig {
+                denom: Denom::Native(NATIVE_DENOM.to_string()),
+                amount: Uint128::new(DEFAULT_NATIVE_AMOUNT),
+                stream: None,
+                price: None,
+            }],
+            rate_limit_seconds: Some(DEFAULT_RATE_LIMIT),
+            opens_at: None,
+            closes_at: None,
+        };
+        let info = message_info(&addr("creator"), &[]);
+        instantiate(deps, env.clone(), info, msg).unwrap();
+        env
+    }
+
+    #[test]
+    fn test_claim_history_and_stats() {
+        let mut deps = mock_dependencies_with_balance(&coins(1_000_000, NATIVE_DENOM));
+        let mut env = instantiate_native_faucet(deps.as_mut());
+
+        let user1 = deps.api.addr_make("user1");
+        let user2 = deps.api.addr_make("user2");
+
+        execute_claim(deps.as_mut(), env.clone(), message_info(&user1, &[]), None).unwrap();
+        env.block.time = env.block.time.plus_seconds(10);
+        execute_claim(deps.as_mut(), env.clone(), message_info(&user2, &[]), None).unwrap();
+        env.block.time = env.block.time.plus_seconds(DEFAULT_RATE_LIMIT);
+        execute_claim(deps.as_mut(), env.clone(), message_info(&user1, &[]), None).unwrap();
+
+        // History is returned most recent first
+        let res = query_claim_history(deps.as_ref(), user1.to_string(), None, None).unwrap();
+        let ids: Vec<u64> = res.claims.iter().map(|claim| claim.id).collect();
+        assert_eq!(ids, vec![3, 1]);
+        assert_eq!(res.claims[0].claimer, user1);
+        assert_eq!(res.claims[0].time, env.block.time.seconds());
+        assert_eq!(
+            res.claims[0].tokens,
+            vec![TokenAmount {
+                denom: Denom::Native(NATIVE_DENOM.to_string()),
+                amount: Uint128::new(DEFAULT_NATIVE_AMOUNT),
+            }]
+        );
+
+        let res = query_claim_history(deps.as_ref(), user1.to_string(), Some(3), None).unwrap();
+        let ids: Vec<u64> = res.claims.iter().map(|claim| claim.id).collect();
+        assert_eq!(ids, vec![1]);
+
+        let res = query_stats(deps.as_ref()).unwrap();
+        assert_eq!(res.total_claims, 3);
+        assert_eq!(res.unique_claimers, 2);
+        assert_eq!(
+            res.distributed,
+            vec![TokenAmount {
+                denom: Denom::Native(NATIVE_DENOM.to_string()),
+                amount: Uint128::new(3 * DEFAULT_NATIVE_AMOUNT),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_airdrop() {
+        let mut deps = mock_dependencies_with_balance(&[
+            Coin::new(1_000u128, NATIVE_DENOM),
+            Coin::new(500u128, "uatom"),
+        ]);
+        let env = instantiate_native_faucet(deps.as_mut());
+        let admin = message_info(&addr("admin"), &[]);
+        let alice = deps.api.addr_make("alice");
+        let bob = deps.api.addr_make("bob");
+
+        let native = |denom: &str, amount: u128| TokenConfig {
+            denom: Denom::Native(denom.to_string()),
+            amount: Uint128::new(amount),
+            stream: None,
+            price: None,
+        };
+
+        // Only the admin can airdrop
+        let err = execute_airdrop(
+            deps.as_mut(),
+            env.clone(),
+            message_info(&alice, &[]),
+            vec![(alice.to_string(), vec![native(NATIVE_DENOM, 100)])],
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // Batches are capped
+        let recipients = (0..=MAX_AIRDROP_RECIPIENTS)
+            .map(|i| {
+                let recipient = deps.api.addr_make(&format!("winner{}", i));
+                (recipient.to_string(), vec![native(NATIVE_DENOM, 1)])
+            })
+            .collect();
+        let err =
+            execute_airdrop(deps.as_mut(), env.clone(), admin.clone(), recipients).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::TooManyRecipients {
+                max: MAX_AIRDROP_RECIPIENTS
+            }
+        );
+
+        // Every address is validated
+        let err = execute_airdrop(
+            deps.as_mut(),
+            env.clone(),
+            admin.clone(),
+            vec![("alice".to_string(), vec![native(NATIVE_DENOM, 100)])],
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::InvalidAddress { .. }));
+
+        // Each recipient fits the balance on its own, but not the batch as a whole
+        let err = execute_airdrop(
+            deps.as_mut(),
+            env.clone(),
+            admin.clone(),
+            vec![
+                (alice.to_string(), vec![native(NATIVE_DENOM, 600)]),
+                (bob.to_string(), vec![native(NATIVE_DENOM, 600)]),
+            ],
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InsufficientFunds {});
+
+        let res = execute_airdrop(
+            deps.as_mut(),
+            env.clone(),
+            admin,
+            vec![
+                (
+                    alice.to_string(),
+                    vec![
+                        native(NATIVE_DENOM, 100),
+                        native("uatom", 50),
+                        native(NATIVE_DENOM, 200),
+                    ],
+                ),
+                (bob.to_string(), vec![native(NATIVE_DENOM, 400)]),
+            ],
+        )
+        .unwrap();
+
+        // One bank send per recipient with native coins merged
+        assert_eq!(
+            res.messages
+                .iter()
+                .map(|msg| msg.msg.clone())
+                .collect::<Vec<_>>(),
+            vec![
+                CosmosMsg::Bank(BankMsg::Send {
+                    to_address: alice.to_string(),
+                    amount: vec![Coin::new(50u128, "uatom"), Coin::new(300u128, NATIVE_DENOM)],
+                }),
+                CosmosMsg::Bank(BankMsg::Send {
+                    to_address: bob.to_string(),
+                    amount: vec![Coin::new(400u128, NATIVE_DENOM)],
+                }),
+            ]
+        );
+        assert_eq!(res.events.len(), 2);
+
+        // Airdrops show up in claim history without starting the rate limit
+        let res = query_claim_history(deps.as_ref(), alice.to_string(), None, None).unwrap();
+        assert_eq!(res.claims.len(), 1);
+        assert_eq!(
+            res.claims[0].tokens,
+            vec![
+                TokenAmount {
+                    denom: Denom::Native(NATIVE_DENOM.to_string()),
+                    amount: Uint128::new(300),
+                },
+                TokenAmount {
+                    denom: Denom::Native("uatom".to_string()),
+                    amount: Uint128::new(50),
+                },
+            ]
+        );
+        let res = query_stats(deps.as_ref()).unwrap();
+        assert_eq!(res.total_claims, 2);
+        assert_eq!(res.unique_claimers, 0);
+        let res = query_can_user_claim(deps.as_ref(), env, bob.to_string()).unwrap();
+        assert!(res.can_claim);
+    }
+
+    #[test]
+    fn test_query_claims_in_range() {
+        let mut deps = mock_dependencies_with_balance(&coins(1_000_000, NATIVE_DENOM));
+        let mut env = instantiate_native_faucet(deps.as_mut());
+        let start = env.block.time.seconds();
+
+        // Three claims on the first day, one the following day
+        for name in ["user1", "user2", "user3"] {
+            let user = deps.api.addr_make(name);
+            execute_claim(deps.as_mut(), env.clone(), message_info(&user, &[]), None).unwrap();
+            env.block.time = env.block.time.plus_seconds(60);
+        }
+        env.block.time = env.block.time.plus_seconds(DEFAULT_RATE_LIMIT);
+        let user1 = deps.api.addr_make("user1");
+        execute_claim(deps.as_mut(), env.clone(), message_info(&user1, &[]), None).unwrap();
+
+        let ids = |claims: Vec<ClaimRecord>| -> Vec<u64> { claims.iter().map(|c| c.id).collect() };
+
+        let res = query_claims_in_range(deps.as_ref(), start, start + 60, None, None).unwrap();
+        assert_eq!(ids(res.claims), vec![1, 2]);
+
+        let res = query_claims_in_range(deps.as_ref(), start + 1, start + 120, None, None).unwrap();
+        assert_eq!(ids(res.claims), vec![2, 3]);
+
+        let end = env.block.time.seconds();
+        let res = query_claims_in_range(deps.as_ref(), start, end, None, Some(2)).unwrap();
+        assert_eq!(ids(res.claims), vec![1, 2]);
+        let res = query_claims_in_range(deps.as_ref(), start, end, Some(2), Some(2)).unwrap();
+        assert_eq!(ids(res.claims), vec![3, 4]);
+
+        let res = query_claims_in_range(deps.as_ref(), end, end, None, None).unwrap();
+        assert_eq!(ids(res.claims), vec![4]);
+    }
+
+    #[test]
+    fn test_query_claimers() {
+        let mut deps = mock_dependencies_with_balance(&coins(1_000_000, NATIVE_DENOM));
+        let mut env = instantiate_native_faucet(deps.as_mut());
+        let start = env.block.time;
+
+        let mut users: Vec<Addr> = (1..=12)
+            .map(|i| deps.api.addr_make(&format!("user{}", i)))
+            .collect();
+        for user in users.iter().take(4) {
+            execute_claim(deps.as_mut(), env.clone(), message_info(user, &[]), None).unwrap();
+        }
+        env.block.time = start.plus_seconds(DEFAULT_RATE_LIMIT / 2);
+        for user in users.iter().skip(4) {
+            execute_claim(deps.as_mut(), env.clone(), message_info(user, &[]), None).unwrap();
+        }
+        users.sort();
+
+        // Pages follow address order and default to DEFAULT_QUERY_LIMIT entries
+        let res = query_all_claimers(deps.as_ref(), env.clone(), None, None).unwrap();
+        assert_eq!(res.claimers.len(), DEFAULT_QUERY_LIMIT as usize);
+        let last = res.claimers.last().unwrap().address.clone();
+        let res = query_all_claimers(deps.as_ref(), env.clone(), Some(last), Some(100)).unwrap();
+        let addresses: Vec<String> = res
+            .claimers
+            .iter()
+            .map(|claimer| claimer.address.clone())
+            .collect();
+        assert_eq!(
+            addresses,
+            vec![users[10].to_string(), users[11].to_string()]
+        );
+
+        // Large limits are capped at MAX_QUERY_LIMIT, which still fits everyone
+        let res = query_all_claimers(deps.as_ref(), env.clone(), None, Some(1_000)).unwrap();
+        assert_eq!(res.claimers.len(), 12);
+        assert!(res
+            .claimers
+            .iter()
+            .all(|claimer| claimer.seconds_remaining > 0));
+
+        // Once the first claims' cooldown ends only the later claimers remain
+        let at_time = start.plus_seconds(DEFAULT_RATE_LIMIT);
+        let res = query_claimers_in_cooldown(deps.as_ref(), at_time, None, Some(100)).unwrap();
+        assert_eq!(res.claimers.len(), 8);
+        assert!(res.claimers.iter().all(|claimer| {
+            claimer.last_claim_time == start.seconds() + DEFAULT_RATE_LIMIT / 2
+                && claimer.seconds_remaining == DEFAULT_RATE_LIMIT / 2
+        }));
+
+        let res = query_claimers_in_cooldown(
+            deps.as_ref(),
+            start.plus_seconds(2 * DEFAULT_RATE_LIMIT),
+            None,
+            None,
+        )
+        .unwrap();
+        assert!(res.claimers.is_empty());
+
+        // Claims made after `at_time` aren't counted
+        let res = query_claimers_in_cooldown(
+            deps.as_ref(),
+            start.plus_seconds(DEFAULT_RATE_LIMIT / 2 - 1),
+            None,
+            Some(100),
+        )
+        .unwrap();
+        assert_eq!(res.claimers.len(), 4);
+        assert!(res
+            .claimers
+            .iter()
+            .all(|claimer| claimer.last_claim_time == start.seconds()));
+
+        // Cooldown pages follow the last claim time, then the address
+        let (early, late): (Vec<_>, Vec<_>) =
+            query_all_claimers(deps.as_ref(), env.clone(), None, Some(100))
+                .unwrap()
+                .claimers
+                .into_iter()
+                .partition(|claimer| claimer.last_claim_time == start.seconds());
+        let mut expected = early
+            .iter()
+            .chain(late.iter())
+            .map(|claimer| claimer.address.clone());
+        let mut start_after = None;
+        loop {
+            let res =
+                query_claimers_in_cooldown(deps.as_ref(), env.block.time, start_after, Some(5))
+                    .unwrap();
+            if res.claimers.is_empty() {
+                break;
+            }
+            for claimer in res.claimers.iter() {
+                assert_eq!(Some(claimer.address.clone()), expected.next());
+            }
+            start_after = res.claimers.last().map(|claimer| claimer.address.clone());
+        }
+        assert_eq!(expected.next(), None);
+
+        let err =
+            query_all_claimers(deps.as_ref(), env, Some("USER1".to_string()), None).unwrap_err();
+        assert!(err.to_string().contains("Invalid address"));
+    }
+
+    #[test]
+    fn test_migrate() {
+        let mut deps = mock_dependencies();
+        let info = message_info(&addr("creator"), &[]);
+        instantiate(deps.as_mut(), mock_env(), info, default_instantiate_msg()).unwrap();
+
+        // Same version is a no-op apart from rewriting the version
+        let res = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+        assert_eq!(res.attributes[1].value, CONTRACT_VERSION);
+        assert!(!STATE.load(&deps.storage).unwrap().depositor_withdrawals);
+
+        // Downgrades are rejected
+        cw2::set_contract_version(&mut deps.storage, CONTRACT_NAME, "99.0.0").unwrap();
+        let err = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Cannot migrate from newer version"));
+
+        // Other contracts are rejected
+        cw2::set_contract_version(&mut deps.storage, "crates.io:other", "0.1.0").unwrap();
+        let err = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap_err();
+        assert!(err.to_string().contains("Cannot migrate from contract"));
+    }
 }
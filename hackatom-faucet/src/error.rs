@@ -0,0 +1,169 @@
+use cosmwasm_std::{StdError, Uint128};
+use thiserror::Error;
+
+use crate::msg::ClaimBlockedReason;
+use crate::state::Role;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("Invalid address {address}: {reason}")]
+    InvalidAddress { address: String, reason: String },
+
+    #[error("Cannot migrate from contract {contract}")]
+    InvalidMigrationContract { contract: String },
+
+    #[error("Cannot migrate from newer version {from} to {to}")]
+    MigrationDowngrade { from: String, to: String },
+
+    #[error("Invalid contract version {version}: {reason}")]
+    InvalidVersion { version: String, reason: String },
+
+    #[error("Faucet is paused")]
+    Paused {},
+
+    #[error("Faucet opens at {opens_at}")]
+    NotOpenYet { opens_at: u64 },
+
+    #[error("Faucet closed at {closed_at}")]
+    Closed { closed_at: u64 },
+
+    #[error("Rate limit exceeded. You can claim again in {seconds_remaining} seconds")]
+    RateLimited { seconds_remaining: u64 },
+
+    #[error("Insufficient funds in faucet for all token types")]
+    NothingToClaim {},
+
+    #[error("Insufficient funds in faucet")]
+    InsufficientFunds {},
+
+    #[error("Invalid token index")]
+    InvalidTokenIndex {},
+
+    #[error("Token is not distributed by this faucet")]
+    TokenNotDistributed {},
+
+    #[error("Cannot set more than {max} tiers")]
+    TooManyTiers { max: usize },
+
+    #[error("Tier multiplier must be at least 1")]
+    InvalidTierMultiplier {},
+
+    #[error("The owner role is transferred with SetAdmin")]
+    OwnerRoleNotGrantable {},
+
+    #[error("{address} does not have the {role:?} role")]
+    RoleNotGranted { address: String, role: Role },
+
+    #[error("Claim window must open before it closes")]
+    InvalidClaimWindow {},
+
+    #[error("No funds sent")]
+    NoFunds {},
+
+    #[error("Invalid zero amount")]
+    InvalidZeroAmount {},
+
+    #[error("Depositors can only withdraw their full share")]
+    PartialDepositorWithdrawal {},
+
+    #[error("No deposit to withdraw")]
+    NoDeposit {},
+
+    #[error("Nothing to withdraw")]
+    NothingToWithdraw {},
+
+    #[error("Nothing has vested yet")]
+    NothingVested {},
+
+    #[error("Price value must be positive and min_amount at most max_amount")]
+    InvalidPriceConfig {},
+
+    #[error("Oracle returned an invalid price for {denom}")]
+    InvalidPrice { denom: String },
+
+    #[error("Priced amount {amount} of {denom} is outside the bounds {min} to {max}")]
+    PriceOutOfBounds {
+        denom: String,
+        amount: Uint128,
+        min: Uint128,
+        max: Uint128,
+    },
+
+    #[error("Nothing to airdrop")]
+    EmptyAirdrop {},
+
+    #[error("Cannot airdrop to more than {max} recipients at once")]
+    TooManyRecipients { max: usize },
+
+    #[error("Duplicate airdrop recipient {address}")]
+    DuplicateRecipient { address: String },
+
+    #[error("Difficulty cannot exceed {max} bits")]
+    DifficultyTooHigh { max: u8 },
+
+    #[error("Epoch must be at least one block")]
+    InvalidEpoch {},
+
+    #[error("Proof of work nonce required")]
+    PowNonceRequired {},
+
+    #[error("Invalid proof of work")]
+    InvalidProofOfWork {},
+
+    #[error("Proof of work is not enabled")]
+    PowDisabled {},
+
+    #[error("Proof of work seed expired, rotate it before claiming")]
+    PowSeedExpired {},
+
+    #[error("Sybil gate is not enabled")]
+    SybilGateDisabled {},
+
+    #[error("No Cosmos Hub account linked")]
+    HubAccountNotLinked {},
+
+    #[error("Cosmos Hub account query result is stale")]
+    StaleHubResult {},
+
+    #[error("Cosmos Hub account {requirement} is below the required minimum")]
+    HubRequirementNotMet { requirement: String },
+
+    #[error("Interchain query does not match the sybil gate")]
+    HubQueryMismatch {},
+
+    #[error("Interchain query watches a different account")]
+    HubAccountMismatch {},
+}
+
+impl From<ClaimBlockedReason> for ContractError {
+    fn from(reason: ClaimBlockedReason) -> Self {
+        match reason {
+            ClaimBlockedReason::Paused => ContractError::Paused {},
+            ClaimBlockedReason::NotOpenYet { opens_at } => ContractError::NotOpenYet {
+                opens_at: opens_at.seconds(),
+            },
+            ClaimBlockedReason::Closed { closed_at } => ContractError::Closed {
+                closed_at: closed_at.seconds(),
+            },
+            ClaimBlockedReason::RateLimited { seconds_remaining } => {
+                ContractError::RateLimited { seconds_remaining }
+            }
+        }
+    }
+}
+
+// Queries return `StdResult`, keep the message when they hit a contract error
+impl From<ContractError> for StdError {
+    fn from(err: ContractError) -> Self {
+        match err {
+            ContractError::Std(err) => err,
+            err => StdError::generic_err(err.to_string()),
+        }
+    }
+}
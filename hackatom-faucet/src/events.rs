@@ -0,0 +1,79 @@
+//! Typed events for indexers.
+//!
+//! Every event carries a `schema_version` attribute. Attribute keys and value formats
+//! only change together with a version bump, so indexers can parse events without
+//! splitting strings. Tokens are keyed by `state::denom_key`, e.g. `native:untrn` or
+//! `cw20:<contract>`, amounts are plain integers and times are unix seconds. Optional
+//! values are left out when unset.
+
+use cosmwasm_std::{Addr, Decimal, Event};
+use cw20::Denom;
+
+use crate::state::{denom_key, TokenAmount};
+
+pub const EVENT_SCHEMA_VERSION: &str = "1";
+
+pub const CLAIM_EVENT: &str = "faucet_claim";
+pub const CONFIG_UPDATE_EVENT: &str = "faucet_config_update";
+
+/// Why a claim left out a token
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SkipReason {
+    /// The faucet can't cover the amount after vesting reservations
+    InsufficientFunds,
+    /// The oracle failed or quoted an invalid price
+    PriceUnavailable,
+    /// The priced amount is outside the configured bounds
+    PriceOutOfBounds,
+}
+
+impl SkipReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SkipReason::InsufficientFunds => "insufficient_funds",
+            SkipReason::PriceUnavailable => "price_unavailable",
+            SkipReason::PriceOutOfBounds => "price_out_of_bounds",
+        }
+    }
+}
+
+/// `faucet_claim` with a `sent.<denom key>` attribute per token transferred right away,
+/// a `streamed.<denom key>` attribute per token vesting over time and a
+/// `skipped.<denom key>` attribute per token left out, holding the reason
+pub fn claim_event(
+    claim_id: u64,
+    claimer: &Addr,
+    multiplier: Decimal,
+    sent: &[TokenAmount],
+    streamed: &[TokenAmount],
+    skipped: &[(Denom, SkipReason)],
+) -> Event {
+    let mut event = Event::new(CLAIM_EVENT)
+        .add_attribute("schema_version", EVENT_SCHEMA_VERSION)
+        .add_attribute("claim_id", claim_id.to_string())
+        .add_attribute("claimer", claimer.to_string())
+        .add_attribute("multiplier", multiplier.to_string());
+    for token in sent {
+        event = event.add_attribute(
+            format!("sent.{}", denom_key(&token.denom)),
+            token.amount.to_string(),
+        );
+    }
+    for token in streamed {
+        event = event.add_attribute(
+            format!("streamed.{}", denom_key(&token.denom)),
+            token.amount.to_string(),
+        );
+    }
+    for (denom, reason) in skipped {
+        event = event.add_attribute(format!("skipped.{}", denom_key(denom)), reason.as_str());
+    }
+    event
+}
+
+/// `faucet_config_update` naming the changed `setting`. Callers add the new values.
+pub fn config_update_event(setting: &str) -> Event {
+    Event::new(CONFIG_UPDATE_EVENT)
+        .add_attribute("schema_version", EVENT_SCHEMA_VERSION)
+        .add_attribute("setting", setting)
+}
@@ -0,0 +1,184 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{
+    coin, to_json_binary, Addr, Api, BankMsg, CosmosMsg, Decimal, Deps, StdResult, Storage,
+    Timestamp, Uint128, WasmMsg,
+};
+use cw20::{Cw20ExecuteMsg, Denom};
+
+use crate::error::ContractError;
+use crate::msg::ClaimBlockedReason;
+use crate::state::{
+    denom_key, Role, State, Tier, TierRequirement, TokenConfig, UserInfo, ROLES, VESTING_TOTALS,
+};
+
+/// Validate a bech32 address, rejecting wrong prefixes and non-normalized casing
+pub fn validate_address(api: &dyn Api, address: &str) -> Result<Addr, ContractError> {
+    api.addr_validate(address)
+        .map_err(|err| ContractError::InvalidAddress {
+            address: address.to_string(),
+            reason: err.to_string(),
+        })
+}
+
+/// Validate the contract address of a CW20 denom. Native denoms are accepted as is.
+pub fn validate_denom(api: &dyn Api, denom: &Denom) -> Result<(), ContractError> {
+    if let Denom::Cw20(contract_addr) = denom {
+        validate_address(api, contract_addr.as_str())?;
+    }
+    Ok(())
+}
+
+/// Validate a token config before it is stored
+pub fn validate_token(api: &dyn Api, token: &TokenConfig) -> Result<(), ContractError> {
+    validate_denom(api, &token.denom)?;
+    if let Some(price) = &token.price {
+        validate_address(api, price.oracle.as_str())?;
+        if price.value.is_zero() || price.min_amount > price.max_amount {
+            return Err(ContractError::InvalidPriceConfig {});
+        }
+    }
+    Ok(())
+}
+
+/// Query the balance `holder` has of a native or CW20 token
+pub fn query_token_balance(deps: Deps, holder: &Addr, denom: &Denom) -> StdResult<Uint128> {
+    match denom {
+        Denom::Native(denom) => Ok(deps.querier.query_balance(holder, denom)?.amount),
+        Denom::Cw20(contract_addr) => {
+            let balance: cw20::BalanceResponse = deps.querier.query_wasm_smart(
+                contract_addr,
+                &cw20::Cw20QueryMsg::Balance {
+                    address: holder.to_string(),
+                },
+            )?;
+            Ok(balance.balance)
+        }
+    }
+}
+
+/// Faucet balance of a token minus what is still owed to vesting schedules
+pub fn query_available_balance(deps: Deps, contract: &Addr, denom: &Denom) -> StdResult<Uint128> {
+    let balance = query_token_balance(deps, contract, denom)?;
+    let reserved = VESTING_TOTALS
+        .may_load(deps.storage, &denom_key(denom))?
+        .unwrap_or_default();
+    Ok(balance.saturating_sub(reserved))
+}
+
+/// Build the message transferring `amount` of a native or CW20 token to `recipient`
+pub fn transfer_msg(denom: &Denom, recipient: &Addr, amount: Uint128) -> StdResult<CosmosMsg> {
+    match denom {
+        Denom::Native(denom) => Ok(CosmosMsg::Bank(BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: vec![coin(amount.u128(), denom)],
+        })),
+        Denom::Cw20(contract_addr) => Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: contract_addr.to_string(),
+            msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: recipient.to_string(),
+                amount,
+            })?,
+            funds: vec![],
+        })),
+    }
+}
+
+/// Why a user with the given claim record can't claim at `now`, if anything stops them
+pub fn claim_blocked_reason(
+    state: &State,
+    user_info: Option<&UserInfo>,
+    now: Timestamp,
+) -> Option<ClaimBlockedReason> {
+    if state.paused {
+        return Some(ClaimBlockedReason::Paused);
+    }
+
+    if let Some(opens_at) = state.opens_at {
+        if now < opens_at {
+            return Some(ClaimBlockedReason::NotOpenYet { opens_at });
+        }
+    }
+
+    if let Some(closes_at) = state.closes_at {
+        if now >= closes_at {
+            return Some(ClaimBlockedReason::Closed {
+                closed_at: closes_at,
+            });
+        }
+    }
+
+    if let Some(user_info) = user_info {
+        let time_since_last_claim = now.seconds().saturating_sub(user_info.last_claim_time);
+        if time_since_last_claim < state.rate_limit_seconds {
+            return Some(ClaimBlockedReason::RateLimited {
+                seconds_remaining: state.rate_limit_seconds - time_since_last_claim,
+            });
+        }
+    }
+
+    None
+}
+
+/// Fails with `Unauthorized` unless `sender` is the admin or has been granted `role`
+pub fn assert_role(
+    storage: &dyn Storage,
+    state: &State,
+    sender: &Addr,
+    role: Role,
+) -> Result<(), ContractError> {
+    // The admin holds every role
+    if *sender == state.admin {
+        return Ok(());
+    }
+
+    let roles = ROLES.may_load(storage, sender)?.unwrap_or_default();
+    if role != Role::Owner && roles.contains(&role) {
+        return Ok(());
+    }
+
+    Err(ContractError::Unauthorized {})
+}
+
+/// The subset of the CW721 query API needed to check ownership
+#[cw_serde]
+pub enum Cw721QueryMsg {
+    Tokens {
+        owner: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+}
+
+#[cw_serde]
+pub struct Cw721TokensResponse {
+    pub tokens: Vec<String>,
+}
+
+/// Multiplier of the best tier `claimer` qualifies for, or one if none match.
+/// `tiers` must be ordered by descending multiplier.
+pub fn claim_multiplier(deps: Deps, tiers: &[Tier], claimer: &Addr) -> StdResult<Decimal> {
+    for tier in tiers {
+        let qualifies = match &tier.requirement {
+            TierRequirement::Cw721 { collection } => {
+                let res: Cw721TokensResponse = deps.querier.query_wasm_smart(
+                    collection,
+                    &Cw721QueryMsg::Tokens {
+                        owner: claimer.to_string(),
+                        start_after: None,
+                        limit: Some(1),
+                    },
+                )?;
+                !res.tokens.is_empty()
+            }
+            TierRequirement::Cw20 { token, min_balance } => {
+                let balance = query_token_balance(deps, claimer, &Denom::Cw20(token.clone()))?;
+                balance >= *min_balance
+            }
+        };
+        if qualifies {
+            return Ok(tier.multiplier);
+        }
+    }
+
+    Ok(Decimal::one())
+}
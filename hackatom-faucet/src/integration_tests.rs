@@ -0,0 +1,1188 @@
+mod tiers {
+    use cosmwasm_schema::cw_serde;
+    use cosmwasm_std::{
+        coins, to_json_binary, Addr, Binary, Decimal, Deps, DepsMut, Empty, Env, MessageInfo,
+        Response, StdError, StdResult, Uint128,
+    };
+    use cw20::{BalanceResponse, Cw20QueryMsg, Denom};
+    use cw_multi_test::{App, ContractWrapper, Executor};
+    use cw_storage_plus::Item;
+
+    use crate::helpers::{Cw721QueryMsg, Cw721TokensResponse};
+    use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg, TiersResponse};
+    use crate::state::{Tier, TierRequirement, TokenConfig, NATIVE_DENOM};
+
+    const AMOUNT: u128 = 1_000;
+
+    /// Addresses and amounts a mock token contract reports holdings for
+    #[cw_serde]
+    struct MockInstantiateMsg {
+        holders: Vec<(String, Uint128)>,
+    }
+
+    const HOLDERS: Item<Vec<(String, Uint128)>> = Item::new("holders");
+
+    fn mock_instantiate(
+        deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        msg: MockInstantiateMsg,
+    ) -> StdResult<Response> {
+        HOLDERS.save(deps.storage, &msg.holders)?;
+        Ok(Response::default())
+    }
+
+    fn mock_execute(
+        _deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        _msg: Empty,
+    ) -> StdResult<Response> {
+        Err(StdError::generic_err("Mock token does not accept messages"))
+    }
+
+    fn holding(deps: Deps, address: &str) -> StdResult<Uint128> {
+        Ok(HOLDERS
+            .load(deps.storage)?
+            .into_iter()
+            .find(|(holder, _)| holder == address)
+            .map(|(_, amount)| amount)
+            .unwrap_or_default())
+    }
+
+    fn mock_cw721_query(deps: Deps, _env: Env, msg: Cw721QueryMsg) -> StdResult<Binary> {
+        match msg {
+            Cw721QueryMsg::Tokens { owner, .. } => {
+                let tokens = if holding(deps, &owner)?.is_zero() {
+                    vec![]
+                } else {
+                    vec!["badge-1".to_string()]
+                };
+                to_json_binary(&Cw721TokensResponse { tokens })
+            }
+        }
+    }
+
+    fn mock_cw20_query(deps: Deps, _env: Env, msg: Cw20QueryMsg) -> StdResult<Binary> {
+        match msg {
+            Cw20QueryMsg::Balance { address } => to_json_binary(&BalanceResponse {
+                balance: holding(deps, &address)?,
+            }),
+            _ => Err(StdError::generic_err("Unsupported query")),
+        }
+    }
+
+    struct Suite {
+        app: App,
+        faucet: Addr,
+        admin: Addr,
+        badge: Addr,
+        token: Addr,
+    }
+
+    fn setup(badge_holders: &[&Addr], token_holders: &[(&Addr, u128)]) -> Suite {
+        let mut app = App::default();
+        let admin = app.api().addr_make("admin");
+
+        let cw721_code = app.store_code(Box::new(ContractWrapper::new(
+            mock_execute,
+            mock_instantiate,
+            mock_cw721_query,
+        )));
+        let cw20_code = app.store_code(Box::new(ContractWrapper::new(
+            mock_execute,
+            mock_instantiate,
+            mock_cw20_query,
+        )));
+        let faucet_code = app.store_code(Box::new(ContractWrapper::new(
+            crate::contract::execute,
+            crate::contract::instantiate,
+            crate::contract::query,
+        )));
+
+        let badge = app
+            .instantiate_contract(
+                cw721_code,
+                admin.clone(),
+                &MockInstantiateMsg {
+                    holders: badge_holders
+                        .iter()
+                        .map(|holder| (holder.to_string(), Uint128::one()))
+                        .collect(),
+                },
+                &[],
+                "badge",
+                None,
+            )
+            .unwrap();
+        let token = app
+            .instantiate_contract(
+                cw20_code,
+                admin.clone(),
+                &MockInstantiateMsg {
+                    holders: token_holders
+                        .iter()
+                        .map(|(holder, amount)| (holder.to_string(), Uint128::new(*amount)))
+                        .collect(),
+                },
+                &[],
+                "token",
+                None,
+            )
+            .unwrap();
+        let faucet = app
+            .instantiate_contract(
+                faucet_code,
+                admin.clone(),
+                &InstantiateMsg {
+                    admin: Some(admin.clone()),
+                    tokens: vec![TokenConfig {
+                        denom: Denom::Native(NATIVE_DENOM.to_string()),
+                        amount: Uint128::new(AMOUNT),
+                        stream: None,
+                        price: None,
+                    }],
+                    rate_limit_seconds: None,
+                    opens_at: None,
+                    closes_at: None,
+                },
+                &[],
+                "faucet",
+                None,
+            )
+            .unwrap();
+
+        app.init_modules(|router, _, storage| {
+            router
+                .bank
+                .init_balance(storage, &faucet, coins(1_000_000, NATIVE_DENOM))
+        })
+        .unwrap();
+
+        Suite {
+            app,
+            faucet,
+            admin,
+            badge,
+            token,
+        }
+    }
+
+    impl Suite {
+        fn set_tiers(&mut self) {
+            let tiers = vec![
+                Tier {
+                    requirement: TierRequirement::Cw20 {
+                        token: self.token.clone(),
+                        min_balance: Uint128::new(500),
+                    },
+                    multiplier: Decimal::percent(150),
+                },
+                Tier {
+                    requirement: TierRequirement::Cw721 {
+                        collection: self.badge.clone(),
+                    },
+                    multiplier: Decimal::percent(300),
+                },
+            ];
+            self.app
+                .execute_contract(
+                    self.admin.clone(),
+                    self.faucet.clone(),
+                    &ExecuteMsg::SetTiers { tiers },
+                    &[],
+                )
+                .unwrap();
+        }
+
+        fn claim(&mut self, claimer: &Addr) -> u128 {
+            self.app
+                .execute_contract(
+                    claimer.clone(),
+                    self.faucet.clone(),
+                    &ExecuteMsg::Claim { nonce: None },
+                    &[],
+                )
+                .unwrap();
+            self.app
+                .wrap()
+                .query_balance(claimer, NATIVE_DENOM)
+                .unwrap()
+                .amount
+                .u128()
+        }
+    }
+
+    #[test]
+    fn claim_amount_scales_with_best_tier() {
+        let app = App::default();
+        let anonymous = app.api().addr_make("anonymous");
+        let participant = app.api().addr_make("participant");
+        let holder = app.api().addr_make("holder");
+        let whale = app.api().addr_make("whale");
+        let small = app.api().addr_make("small");
+
+        let mut suite = setup(
+            &[&participant, &whale],
+            &[(&holder, 500), (&whale, 10_000), (&small, 499)],
+        );
+        suite.set_tiers();
+
+        // Tiers are stored best first
+        let res: TiersResponse = suite
+            .app
+            .wrap()
+            .query_wasm_smart(&suite.faucet, &QueryMsg::Tiers {})
+            .unwrap();
+        assert_eq!(res.tiers[0].multiplier, Decimal::percent(300));
+
+        assert_eq!(suite.claim(&anonymous), AMOUNT);
+        assert_eq!(suite.claim(&participant), AMOUNT * 3);
+        assert_eq!(suite.claim(&holder), AMOUNT * 3 / 2);
+        assert_eq!(suite.claim(&small), AMOUNT);
+        // Holding both badges only applies the best multiplier
+        assert_eq!(suite.claim(&whale), AMOUNT * 3);
+    }
+
+    #[test]
+    fn tiers_are_validated() {
+        let mut suite = setup(&[], &[]);
+        let stranger = suite.app.api().addr_make("stranger");
+        let tier = Tier {
+            requirement: TierRequirement::Cw721 {
+                collection: suite.badge.clone(),
+            },
+            multiplier: Decimal::percent(50),
+        };
+
+        // Only operators can change tiers
+        let err = suite
+            .app
+            .execute_contract(
+                stranger,
+                suite.faucet.clone(),
+                &ExecuteMsg::SetTiers {
+                    tiers: vec![tier.clone()],
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert!(err.root_cause().to_string().contains("Unauthorized"));
+
+        // Tiers can't reduce the amount
+        let err = suite
+            .app
+            .execute_contract(
+                suite.admin.clone(),
+                suite.faucet.clone(),
+                &ExecuteMsg::SetTiers { tiers: vec![tier] },
+                &[],
+            )
+            .unwrap_err();
+        assert!(err
+            .root_cause()
+            .to_string()
+            .contains("multiplier must be at least 1"));
+    }
+}
+
+mod pricing {
+    use cosmwasm_schema::cw_serde;
+    use cosmwasm_std::testing::MockApi;
+    use cosmwasm_std::{
+        coins, to_json_binary, Addr, Binary, Decimal, Deps, DepsMut, Env, MessageInfo, Response,
+        StdResult, Uint128,
+    };
+    use cw20::Denom;
+    use cw_multi_test::{App, ContractWrapper, Executor};
+    use cw_storage_plus::Item;
+
+    use crate::error::ContractError;
+    use crate::events::CLAIM_EVENT;
+    use crate::msg::{ExecuteMsg, InstantiateMsg};
+    use crate::oracle::{OracleQueryMsg, PriceResponse};
+    use crate::state::{PriceConfig, TokenConfig, NATIVE_DENOM};
+
+    /// Price the mock oracle quotes for every token
+    #[cw_serde]
+    struct MockOracleMsg {
+        price: Decimal,
+    }
+
+    const PRICE: Item<Decimal> = Item::new("price");
+
+    fn mock_instantiate(
+        deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        msg: MockOracleMsg,
+    ) -> StdResult<Response> {
+        PRICE.save(deps.storage, &msg.price)?;
+        Ok(Response::default())
+    }
+
+    fn mock_execute(
+        deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        msg: MockOracleMsg,
+    ) -> StdResult<Response> {
+        PRICE.save(deps.storage, &msg.price)?;
+        Ok(Response::default())
+    }
+
+    fn mock_query(deps: Deps, _env: Env, msg: OracleQueryMsg) -> StdResult<Binary> {
+        match msg {
+            OracleQueryMsg::Price { .. } => to_json_binary(&PriceResponse {
+                price: PRICE.load(deps.storage)?,
+            }),
+        }
+    }
+
+    struct Suite {
+        app: App,
+        faucet: Addr,
+        admin: Addr,
+        oracle: Addr,
+    }
+
+    fn price_config(oracle: &Addr) -> PriceConfig {
+        PriceConfig {
+            oracle: oracle.clone(),
+            quote: "usd".to_string(),
+            // $5 per claim
+            value: Decimal::percent(500),
+            min_amount: Uint128::new(1_000),
+            max_amount: Uint128::new(100_000),
+        }
+    }
+
+    /// Faucet handing out $5 of untrn at `price`, plus `extra_tokens`
+    fn setup(price: Decimal, extra_tokens: Vec<TokenConfig>) -> Suite {
+        let mut app = App::default();
+        let admin = app.api().addr_make("admin");
+
+        let oracle_code = app.store_code(Box::new(ContractWrapper::new(
+            mock_execute,
+            mock_instantiate,
+            mock_query,
+        )));
+        let faucet_code = app.store_code(Box::new(ContractWrapper::new(
+            crate::contract::execute,
+            crate::contract::instantiate,
+            crate::contract::query,
+        )));
+
+        let oracle = app
+            .instantiate_contract(
+                oracle_code,
+                admin.clone(),
+                &MockOracleMsg { price },
+                &[],
+                "oracle",
+                None,
+            )
+            .unwrap();
+        let faucet = app
+            .instantiate_contract(
+                faucet_code,
+                admin.clone(),
+                &InstantiateMsg {
+                    admin: Some(admin.clone()),
+                    tokens: [TokenConfig {
+                        denom: Denom::Native(NATIVE_DENOM.to_string()),
+                        amount: Uint128::zero(),
+                        stream: None,
+                        price: Some(price_config(&oracle)),
+                    }]
+                    .into_iter()
+                    .chain(extra_tokens)
+                    .collect(),
+                    rate_limit_seconds: Some(0),
+                    opens_at: None,
+                    closes_at: None,
+                },
+                &[],
+                "faucet",
+                None,
+            )
+            .unwrap();
+
+        app.init_modules(|router, _, storage| {
+            router
+                .bank
+                .init_balance(storage, &faucet, coins(1_000_000, NATIVE_DENOM))
+        })
+        .unwrap();
+
+        Suite {
+            app,
+            faucet,
+            admin,
+            oracle,
+        }
+    }
+
+    impl Suite {
+        fn set_price(&mut self, price: Decimal) {
+            self.app
+                .execute_contract(
+                    self.admin.clone(),
+                    self.oracle.clone(),
+                    &MockOracleMsg { price },
+                    &[],
+                )
+                .unwrap();
+        }
+
+        fn balance(&self, address: &Addr) -> u128 {
+            self.app
+                .wrap()
+                .query_balance(address, NATIVE_DENOM)
+                .unwrap()
+                .amount
+                .u128()
+        }
+    }
+
+    #[test]
+    fn claim_amount_follows_oracle_price() {
+        // $0.0001 per untrn
+        let mut suite = setup(Decimal::from_ratio(1u128, 10_000u128), vec![]);
+        let user = suite.app.api().addr_make("user");
+        let claim = ExecuteMsg::Claim { nonce: None };
+
+        suite
+            .app
+            .execute_contract(user.clone(), suite.faucet.clone(), &claim, &[])
+            .unwrap();
+        assert_eq!(suite.balance(&user), 50_000);
+
+        // The price doubled, so the same value is half the units
+        suite.set_price(Decimal::from_ratio(2u128, 10_000u128));
+        suite
+            .app
+            .execute_contract(user.clone(), suite.faucet.clone(), &claim, &[])
+            .unwrap();
+        assert_eq!(suite.balance(&user), 75_000);
+
+        // A broken quote would hand out far more than the maximum, so the token is
+        // skipped and there is nothing left to claim
+        suite.set_price(Decimal::raw(1));
+        let err = suite
+            .app
+            .execute_contract(user.clone(), suite.faucet.clone(), &claim, &[])
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<ContractError>(),
+            Some(&ContractError::NothingToClaim {})
+        );
+        assert_eq!(suite.balance(&user), 75_000);
+    }
+
+    #[test]
+    fn broken_oracle_only_skips_its_token() {
+        // No contract answers at the oracle address, so the token is never priced or
+        // transferred
+        let api = MockApi::default();
+        let token = api.addr_make("token");
+        let broken_oracle = api.addr_make("broken_oracle");
+        let mut suite = setup(
+            Decimal::from_ratio(1u128, 10_000u128),
+            vec![TokenConfig {
+                denom: Denom::Cw20(token.clone()),
+                amount: Uint128::zero(),
+                stream: None,
+                price: Some(price_config(&broken_oracle)),
+            }],
+        );
+        let user = suite.app.api().addr_make("user");
+
+        let res = suite
+            .app
+            .execute_contract(
+                user.clone(),
+                suite.faucet.clone(),
+                &ExecuteMsg::Claim { nonce: None },
+                &[],
+            )
+            .unwrap();
+        assert_eq!(suite.balance(&user), 50_000);
+
+        let event = res
+            .events
+            .iter()
+            .find(|event| event.ty == format!("wasm-{}", CLAIM_EVENT))
+            .unwrap();
+        let attribute = |key: String| {
+            event
+                .attributes
+                .iter()
+                .find(|attribute| attribute.key == key)
+                .map(|attribute| attribute.value.as_str())
+        };
+        assert_eq!(
+            attribute(format!("sent.native:{}", NATIVE_DENOM)),
+            Some("50000")
+        );
+        assert_eq!(
+            attribute(format!("skipped.cw20:{}", token)),
+            Some("price_unavailable")
+        );
+    }
+
+    #[test]
+    fn price_config_is_validated() {
+        let mut suite = setup(Decimal::one(), vec![]);
+        let oracle = suite.oracle.clone();
+
+        let err = suite
+            .app
+            .execute_contract(
+                suite.admin.clone(),
+                suite.faucet.clone(),
+                &ExecuteMsg::UpdateTokenConfig {
+                    token_index: 0,
+                    new_config: TokenConfig {
+                        denom: Denom::Native(NATIVE_DENOM.to_string()),
+                        amount: Uint128::zero(),
+                        stream: None,
+                        price: Some(PriceConfig {
+                            min_amount: Uint128::new(10),
+                            max_amount: Uint128::new(1),
+                            ..price_config(&oracle)
+                        }),
+                    },
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert!(err
+            .root_cause()
+            .to_string()
+            .contains("min_amount at most max_amount"));
+    }
+}
+
+mod sudo {
+    use cosmwasm_std::{coins, Addr, Uint128};
+    use cw20::Denom;
+    use cw_multi_test::{App, ContractWrapper, Executor};
+
+    use crate::msg::{
+        AdminResponse, ClaimWindowResponse, ExecuteMsg, InstantiateMsg, QueryMsg,
+        RateLimitResponse, SudoMsg,
+    };
+    use crate::state::{TokenConfig, DEFAULT_NATIVE_AMOUNT, NATIVE_DENOM};
+
+    fn setup() -> (App, Addr) {
+        let mut app = App::default();
+        let code = ContractWrapper::new(
+            crate::contract::execute,
+            crate::contract::instantiate,
+            crate::contract::query,
+        )
+        .with_sudo(crate::contract::sudo);
+        let code_id = app.store_code(Box::new(code));
+
+        let admin = app.api().addr_make("lost_admin");
+        let faucet = app
+            .instantiate_contract(
+                code_id,
+                admin.clone(),
+                &InstantiateMsg {
+                    admin: Some(admin),
+                    tokens: vec![TokenConfig {
+                        denom: Denom::Native(NATIVE_DENOM.to_string()),
+                        amount: Uint128::new(DEFAULT_NATIVE_AMOUNT),
+                        stream: None,
+                        price: None,
+                    }],
+                    rate_limit_seconds: None,
+                    opens_at: None,
+                    closes_at: None,
+                },
+                &[],
+                "faucet",
+                None,
+            )
+            .unwrap();
+
+        app.init_modules(|router, _, storage| {
+            router
+                .bank
+                .init_balance(storage, &faucet, coins(1_000_000, NATIVE_DENOM))
+        })
+        .unwrap();
+
+        (app, faucet)
+    }
+
+    #[test]
+    fn governance_can_replace_admin() {
+        let (mut app, faucet) = setup();
+        let new_admin = app.api().addr_make("new_admin");
+
+        app.wasm_sudo(
+            faucet.clone(),
+            &SudoMsg::ForceSetAdmin {
+                admin: new_admin.to_string(),
+            },
+        )
+        .unwrap();
+
+        let res: AdminResponse = app
+            .wrap()
+            .query_wasm_smart(&faucet, &QueryMsg::GetAdmin {})
+            .unwrap();
+        assert_eq!(res.admin, new_admin.to_string());
+
+        // The new admin has full control
+        app.execute_contract(
+            new_admin,
+            faucet,
+            &ExecuteMsg::UpdateRateLimit { seconds: 60 },
+            &[],
+        )
+        .unwrap();
+
+        // Addresses are validated
+        let (mut app, faucet) = setup();
+        app.wasm_sudo(
+            faucet,
+            &SudoMsg::ForceSetAdmin {
+                admin: "not-an-address".to_string(),
+            },
+        )
+        .unwrap_err();
+    }
+
+    #[test]
+    fn governance_can_update_config() {
+        let (mut app, faucet) = setup();
+
+        app.wasm_sudo(
+            faucet.clone(),
+            &SudoMsg::ForceUpdateConfig {
+                tokens: None,
+                rate_limit_seconds: Some(60),
+                paused: Some(true),
+            },
+        )
+        .unwrap();
+
+        let res: RateLimitResponse = app
+            .wrap()
+            .query_wasm_smart(&faucet, &QueryMsg::GetRateLimit {})
+            .unwrap();
+        assert_eq!(res.rate_limit_seconds, 60);
+        let res: ClaimWindowResponse = app
+            .wrap()
+            .query_wasm_smart(&faucet, &QueryMsg::ClaimWindow {})
+            .unwrap();
+        assert!(res.paused);
+
+        // Unset settings are left unchanged
+        let claimer = app.api().addr_make("claimer");
+        let err = app
+            .execute_contract(
+                claimer,
+                faucet.clone(),
+                &ExecuteMsg::Claim { nonce: None },
+                &[],
+            )
+            .unwrap_err();
+        assert!(err.root_cause().to_string().contains("Faucet is paused"));
+
+        // Tokens are validated like in UpdateTokenConfig
+        let err = app
+            .wasm_sudo(
+                faucet,
+                &SudoMsg::ForceUpdateConfig {
+                    tokens: Some(vec![TokenConfig {
+                        denom: Denom::Cw20(Addr::unchecked("not-an-address")),
+                        amount: Uint128::new(1),
+                        stream: None,
+                        price: None,
+                    }]),
+                    rate_limit_seconds: None,
+                    paused: None,
+                },
+            )
+            .unwrap_err();
+        assert!(err.root_cause().to_string().contains("not-an-address"));
+    }
+
+    #[test]
+    fn governance_can_drain_faucet() {
+        let (mut app, faucet) = setup();
+        let treasury = app.api().addr_make("treasury");
+
+        app.wasm_sudo(
+            faucet.clone(),
+            &SudoMsg::ForceWithdraw {
+                denom: Denom::Native(NATIVE_DENOM.to_string()),
+                amount: Some(Uint128::new(400_000)),
+                recipient: treasury.to_string(),
+            },
+        )
+        .unwrap();
+        app.wasm_sudo(
+            faucet.clone(),
+            &SudoMsg::ForceWithdraw {
+                denom: Denom::Native(NATIVE_DENOM.to_string()),
+                amount: None,
+                recipient: treasury.to_string(),
+            },
+        )
+        .unwrap();
+
+        let balance = |address: &Addr| {
+            app.wrap()
+                .query_balance(address, NATIVE_DENOM)
+                .unwrap()
+                .amount
+                .u128()
+        };
+        assert_eq!(balance(&treasury), 1_000_000);
+        assert_eq!(balance(&faucet), 0);
+    }
+}
+
+#[cfg(feature = "neutron")]
+mod neutron {
+    use std::collections::HashMap;
+
+    use cosmos_sdk_proto::cosmos::auth::v1beta1::BaseAccount;
+    use cosmos_sdk_proto::traits::Message;
+    use cosmos_sdk_proto::Any;
+    use cosmwasm_std::testing::{MockApi, MockStorage};
+    use cosmwasm_std::{
+        coins, to_json_binary, Addr, Api, Binary, BlockInfo, CustomMsg, CustomQuery, Deps, DepsMut,
+        Empty, Env, MessageInfo, Querier, QuerierWrapper, Response, StdResult, Storage, Uint128,
+    };
+    use cw20::Denom;
+    use cw_multi_test::error::{bail, AnyResult};
+    use cw_multi_test::{
+        App, AppBuilder, AppResponse, BankKeeper, ContractWrapper, CosmosRouter, Executor, Module,
+        WasmKeeper,
+    };
+    use neutron_sdk::bindings::query::{
+        NeutronQuery, QueryRegisteredQueryResponse, QueryRegisteredQueryResultResponse,
+    };
+    use neutron_sdk::bindings::types::{
+        Height, InterchainQueryResult, KVKey, RegisteredQuery, StorageValue,
+    };
+    use neutron_sdk::interchain_queries::types::QueryType;
+    use neutron_sdk::interchain_queries::v047::helpers::create_account_denom_balance_key;
+    use neutron_sdk::interchain_queries::v047::types::BANK_STORE_KEY;
+    use serde::de::DeserializeOwned;
+
+    use crate::error::ContractError;
+    use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
+    use crate::neutron::{create_account_key, HubRequirement, SybilGate, AUTH_STORE_KEY};
+    use crate::state::{TokenConfig, DEFAULT_NATIVE_AMOUNT, NATIVE_DENOM};
+
+    const CONNECTION_ID: &str = "connection-0";
+    const HUB_DENOM: &str = "uatom";
+
+    /// Stands in for the Neutron interchain queries module, serving registered
+    /// queries and their latest KV results.
+    #[derive(Default)]
+    struct NeutronIcqMock {
+        queries: HashMap<u64, (RegisteredQuery, Vec<StorageValue>)>,
+    }
+
+    impl NeutronIcqMock {
+        fn register(&mut self, id: u64, key: KVKey, value: Vec<u8>, submitted_at: u64) {
+            let registered = RegisteredQuery {
+                id,
+                owner: "registrar".to_string(),
+                keys: vec![key.clone()],
+                query_type: QueryType::KV,
+                transactions_filter: String::new(),
+                connection_id: CONNECTION_ID.to_string(),
+                update_period: 10,
+                last_submitted_result_local_height: submitted_at,
+                last_submitted_result_remote_height: Height::default(),
+                deposit: vec![],
+                submit_timeout: 0,
+                registered_at_height: 1,
+            };
+            let value = StorageValue {
+                storage_prefix: key.path,
+                key: key.key,
+                value: Binary::new(value),
+            };
+            self.queries.insert(id, (registered, vec![value]));
+        }
+    }
+
+    impl Module for NeutronIcqMock {
+        type ExecT = Empty;
+        type QueryT = NeutronQuery;
+        type SudoT = Empty;
+
+        fn execute<ExecC, QueryC>(
+            &self,
+            _api: &dyn Api,
+            _storage: &mut dyn Storage,
+            _router: &dyn CosmosRouter<ExecC = ExecC, QueryC = QueryC>,
+            _block: &BlockInfo,
+            _sender: Addr,
+            _msg: Self::ExecT,
+        ) -> AnyResult<AppResponse>
+        where
+            ExecC: CustomMsg + DeserializeOwned + 'static,
+            QueryC: CustomQuery + DeserializeOwned + 'static,
+        {
+            bail!("Neutron messages are not supported")
+        }
+
+        fn query(
+            &self,
+            _api: &dyn Api,
+            _storage: &dyn Storage,
+            _querier: &dyn Querier,
+            _block: &BlockInfo,
+            request: Self::QueryT,
+        ) -> AnyResult<Binary> {
+            match request {
+                NeutronQuery::RegisteredInterchainQuery { query_id } => {
+                    let Some((registered_query, _)) = self.queries.get(&query_id) else {
+                        bail!("Query {} not found", query_id)
+                    };
+                    Ok(to_json_binary(&QueryRegisteredQueryResponse {
+                        registered_query: registered_query.clone(),
+                    })?)
+                }
+                NeutronQuery::InterchainQueryResult { query_id } => {
+                    let Some((_, kv_results)) = self.queries.get(&query_id) else {
+                        bail!("Query {} not found", query_id)
+                    };
+                    Ok(to_json_binary(&QueryRegisteredQueryResultResponse {
+                        result: InterchainQueryResult {
+                            kv_results: kv_results.clone(),
+                            height: 100,
+                            revision: 4,
+                        },
+                    })?)
+                }
+                _ => bail!("Unsupported Neutron query"),
+            }
+        }
+
+        fn sudo<ExecC, QueryC>(
+            &self,
+            _api: &dyn Api,
+            _storage: &mut dyn Storage,
+            _router: &dyn CosmosRouter<ExecC = ExecC, QueryC = QueryC>,
+            _block: &BlockInfo,
+            _msg: Self::SudoT,
+        ) -> AnyResult<AppResponse>
+        where
+            ExecC: CustomMsg + DeserializeOwned + 'static,
+            QueryC: CustomQuery + DeserializeOwned + 'static,
+        {
+            bail!("Neutron sudo messages are not supported")
+        }
+    }
+
+    type NeutronApp =
+        App<BankKeeper, MockApi, MockStorage, NeutronIcqMock, WasmKeeper<Empty, NeutronQuery>>;
+
+    // The faucet entry points use the default querier; adapt them to the Neutron one.
+    fn instantiate(
+        deps: DepsMut<NeutronQuery>,
+        env: Env,
+        info: MessageInfo,
+        msg: InstantiateMsg,
+    ) -> Result<Response, ContractError> {
+        let querier = QuerierWrapper::new(&*deps.querier);
+        let deps = DepsMut {
+            storage: deps.storage,
+            api: deps.api,
+            querier,
+        };
+        crate::contract::instantiate(deps, env, info, msg)
+    }
+
+    fn execute(
+        deps: DepsMut<NeutronQuery>,
+        env: Env,
+        info: MessageInfo,
+        msg: ExecuteMsg,
+    ) -> Result<Response, ContractError> {
+        let querier = QuerierWrapper::new(&*deps.querier);
+        let deps = DepsMut {
+            storage: deps.storage,
+            api: deps.api,
+            querier,
+        };
+        crate::contract::execute(deps, env, info, msg)
+    }
+
+    fn query(deps: Deps<NeutronQuery>, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+        let deps = Deps {
+            storage: deps.storage,
+            api: deps.api,
+            querier: QuerierWrapper::new(&*deps.querier),
+        };
+        crate::contract::query(deps, env, msg)
+    }
+
+    fn setup(requirement: HubRequirement) -> (NeutronApp, Addr, Addr) {
+        let mut app: NeutronApp = AppBuilder::new_custom()
+            .with_custom(NeutronIcqMock::default())
+            .build(|_, _, _| {});
+
+        let code = ContractWrapper::new(execute, instantiate, query);
+        let code_id = app.store_code(Box::new(code));
+
+        let admin = app.api().addr_make("admin");
+        let faucet = app
+            .instantiate_contract(
+                code_id,
+                admin.clone(),
+                &InstantiateMsg {
+                    admin: Some(admin.clone()),
+                    tokens: vec![TokenConfig {
+                        denom: Denom::Native(NATIVE_DENOM.to_string()),
+                        amount: Uint128::new(DEFAULT_NATIVE_AMOUNT),
+                        stream: None,
+                        price: None,
+                    }],
+                    rate_limit_seconds: None,
+                    opens_at: None,
+                    closes_at: None,
+                },
+                &[],
+                "faucet",
+                None,
+            )
+            .unwrap();
+
+        app.init_modules(|router, _, storage| {
+            router
+                .bank
+                .init_balance(storage, &faucet, coins(1_000_000, NATIVE_DENOM))
+        })
+        .unwrap();
+
+        app.execute_contract(
+            admin.clone(),
+            faucet.clone(),
+            &ExecuteMsg::SetSybilGate {
+                gate: Some(SybilGate {
+                    connection_id: CONNECTION_ID.to_string(),
+                    requirement,
+                    max_result_age: 100,
+                }),
+            },
+            &[],
+        )
+        .unwrap();
+
+        (app, faucet, admin)
+    }
+
+    fn register_balance_query(app: &mut NeutronApp, id: u64, account: &Addr, amount: u128) {
+        let height = app.block_info().height;
+        let address = app.api().addr_canonicalize(account.as_str()).unwrap();
+        let key = KVKey {
+            path: BANK_STORE_KEY.to_string(),
+            key: Binary::new(
+                create_account_denom_balance_key(address.as_slice(), HUB_DENOM).unwrap(),
+            ),
+        };
+        app.init_modules(|router, _, _| {
+            router
+                .custom
+                .register(id, key, amount.to_string().into_bytes(), height)
+        });
+    }
+
+    fn register_account_query(app: &mut NeutronApp, id: u64, account: &Addr, sequence: u64) {
+        let height = app.block_info().height;
+        let address = app.api().addr_canonicalize(account.as_str()).unwrap();
+        let key = KVKey {
+            path: AUTH_STORE_KEY.to_string(),
+            key: Binary::new(create_account_key(address.as_slice())),
+        };
+        let value = Any {
+            type_url: "/cosmos.auth.v1beta1.BaseAccount".to_string(),
+            value: BaseAccount {
+                address: "cosmos1hub".to_string(),
+                pub_key: None,
+                account_number: 7,
+                sequence,
+            }
+            .encode_to_vec(),
+        }
+        .encode_to_vec();
+        app.init_modules(|router, _, _| router.custom.register(id, key, value, height));
+    }
+
+    fn claim(app: &mut NeutronApp, faucet: &Addr, claimer: &Addr) -> AnyResult<AppResponse> {
+        app.execute_contract(
+            claimer.clone(),
+            faucet.clone(),
+            &ExecuteMsg::Claim { nonce: None },
+            &[],
+        )
+    }
+
+    fn link(
+        app: &mut NeutronApp,
+        faucet: &Addr,
+        claimer: &Addr,
+        id: u64,
+    ) -> AnyResult<AppResponse> {
+        app.execute_contract(
+            claimer.clone(),
+            faucet.clone(),
+            &ExecuteMsg::LinkHubAccount { query_id: id },
+            &[],
+        )
+    }
+
+    #[test]
+    fn claim_requires_min_hub_balance() {
+        let (mut app, faucet, _) = setup(HubRequirement::MinBalance {
+            denom: HUB_DENOM.to_string(),
+            amount: Uint128::new(1_000_000),
+        });
+        let rich = app.api().addr_make("rich");
+        let poor = app.api().addr_make("poor");
+
+        // Nobody can claim without a linked Hub account
+        let err = claim(&mut app, &faucet, &rich).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<ContractError>(),
+            Some(&ContractError::HubAccountNotLinked {})
+        );
+
+        register_balance_query(&mut app, 1, &rich, 5_000_000);
+        register_balance_query(&mut app, 2, &poor, 10);
+        link(&mut app, &faucet, &rich, 1).unwrap();
+        link(&mut app, &faucet, &poor, 2).unwrap();
+
+        let res: crate::msg::HubAccountResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &faucet,
+                &QueryMsg::HubAccount {
+                    address: rich.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(res.query_id, Some(1));
+
+        claim(&mut app, &faucet, &rich).unwrap();
+        let balance = app.wrap().query_balance(&rich, NATIVE_DENOM).unwrap();
+        assert_eq!(balance.amount, Uint128::new(DEFAULT_NATIVE_AMOUNT));
+
+        let err = claim(&mut app, &faucet, &poor).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<ContractError>(),
+            Some(&ContractError::HubRequirementNotMet {
+                requirement: "balance".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn claim_requires_min_hub_sequence() {
+        let (mut app, faucet, _) = setup(HubRequirement::MinSequence { sequence: 5 });
+        let active = app.api().addr_make("active");
+        let fresh = app.api().addr_make("fresh");
+
+        register_account_query(&mut app, 1, &active, 42);
+        register_account_query(&mut app, 2, &fresh, 0);
+        link(&mut app, &faucet, &active, 1).unwrap();
+        link(&mut app, &faucet, &fresh, 2).unwrap();
+
+        claim(&mut app, &faucet, &active).unwrap();
+
+        let err = claim(&mut app, &faucet, &fresh).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<ContractError>(),
+            Some(&ContractError::HubRequirementNotMet {
+                requirement: "sequence".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn cannot_link_someone_elses_hub_account() {
+        let (mut app, faucet, _) = setup(HubRequirement::MinBalance {
+            denom: HUB_DENOM.to_string(),
+            amount: Uint128::new(1_000_000),
+        });
+        let whale = app.api().addr_make("whale");
+        let sybil = app.api().addr_make("sybil");
+
+        register_balance_query(&mut app, 1, &whale, 5_000_000);
+
+        let err = link(&mut app, &faucet, &sybil, 1).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<ContractError>(),
+            Some(&ContractError::HubAccountMismatch {})
+        );
+
+        // A query for the wrong kind of data is rejected too
+        register_account_query(&mut app, 2, &sybil, 100);
+        let err = link(&mut app, &faucet, &sybil, 2).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<ContractError>(),
+            Some(&ContractError::HubQueryMismatch {})
+        );
+    }
+
+    #[test]
+    fn stale_results_are_rejected() {
+        let (mut app, faucet, _) = setup(HubRequirement::MinBalance {
+            denom: HUB_DENOM.to_string(),
+            amount: Uint128::new(1_000_000),
+        });
+        let user = app.api().addr_make("user");
+
+        register_balance_query(&mut app, 1, &user, 5_000_000);
+        link(&mut app, &faucet, &user, 1).unwrap();
+
+        app.update_block(|block| block.height += 101);
+
+        let err = claim(&mut app, &faucet, &user).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<ContractError>(),
+            Some(&ContractError::StaleHubResult {})
+        );
+    }
+
+    #[test]
+    fn gate_can_be_disabled() {
+        let (mut app, faucet, admin) = setup(HubRequirement::MinSequence { sequence: 5 });
+        let user = app.api().addr_make("user");
+
+        // Only the admin can change the gate
+        let err = app
+            .execute_contract(
+                user.clone(),
+                faucet.clone(),
+                &ExecuteMsg::SetSybilGate { gate: None },
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(err.root_cause().to_string(), "Unauthorized");
+
+        app.execute_contract(
+            admin,
+            faucet.clone(),
+            &ExecuteMsg::SetSybilGate { gate: None },
+            &[],
+        )
+        .unwrap();
+
+        claim(&mut app, &faucet, &user).unwrap();
+    }
+}
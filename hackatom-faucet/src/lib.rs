@@ -1,3 +1,13 @@
 pub mod contract;
+pub mod error;
+pub mod events;
+pub mod helpers;
+#[cfg(test)]
+mod integration_tests;
+pub mod migrations;
 pub mod msg;
+#[cfg(feature = "neutron")]
+pub mod neutron;
+pub mod oracle;
+pub mod pow;
 pub mod state;
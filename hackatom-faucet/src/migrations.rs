@@ -0,0 +1,178 @@
+//! Ordered state migrations, one module per version that changed the storage layout.
+//!
+//! Each step reads the state written by the previous version and rewrites it in the
+//! shape the next version expects. `contract::migrate` runs every step newer than the
+//! stored contract version, oldest first.
+
+pub mod v0_2_0 {
+    use cosmwasm_std::{Order, StdResult, Storage};
+
+    use crate::state::{user_claims, ClaimStats, State, TokenConfig, UserInfo, CLAIM_STATS, STATE};
+
+    /// Layout written by v0.1.x
+    pub mod v0_1 {
+        use cosmwasm_std::{Addr, Uint128};
+        use cw20::Denom;
+        use cw_storage_plus::{Item, Map};
+        use schemars::JsonSchema;
+        use serde::{Deserialize, Serialize};
+
+        #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+        pub struct TokenConfig {
+            pub denom: Denom,
+            pub amount: Uint128,
+        }
+
+        #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+        pub struct State {
+            pub admin: Addr,
+            pub tokens: Vec<TokenConfig>,
+            pub rate_limit_seconds: u64,
+        }
+
+        #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+        pub struct UserInfo {
+            pub last_claim_time: u64,
+        }
+
+        pub const STATE: Item<State> = Item::new("state");
+        // Same namespace as `state::user_claims`, without the index
+        pub const USER_CLAIMS: Map<&Addr, UserInfo> = Map::new("user_claims");
+    }
+
+    pub fn migrate(storage: &mut dyn Storage) -> StdResult<()> {
+        let old = v0_1::STATE.load(storage)?;
+
+        // Tokens keep handing out a fixed amount at once
+        let tokens = old
+            .tokens
+            .into_iter()
+            .map(|token| TokenConfig {
+                denom: token.denom,
+                amount: token.amount,
+                stream: None,
+                price: None,
+            })
+            .collect();
+
+        // Depositor withdrawals start disabled, the faucet stays open
+        STATE.save(
+            storage,
+            &State {
+                admin: old.admin,
+                tokens,
+                rate_limit_seconds: old.rate_limit_seconds,
+                depositor_withdrawals: false,
+                paused: false,
+                opens_at: None,
+                closes_at: None,
+            },
+        )?;
+
+        // Rewrite the last claims through the indexed map to index them by time
+        let user_infos = v0_1::USER_CLAIMS
+            .range(storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        for (address, user_info) in user_infos.iter() {
+            let user_info = UserInfo {
+                last_claim_time: user_info.last_claim_time,
+            };
+            user_claims().save(storage, address, &user_info)?;
+        }
+
+        // Claims made before the claim log existed are not recorded individually, but
+        // every address in USER_CLAIMS has claimed at least once.
+        let claimers = user_infos.len() as u64;
+        CLAIM_STATS.save(
+            storage,
+            &ClaimStats {
+                total_claims: claimers,
+                unique_claimers: claimers,
+            },
+        )?;
+
+        Ok(())
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use cosmwasm_std::testing::mock_dependencies;
+        use cosmwasm_std::{Addr, Uint128};
+        use cw20::Denom;
+
+        use super::*;
+        use crate::state::{DEFAULT_NATIVE_AMOUNT, NATIVE_DENOM};
+
+        #[test]
+        fn migrates_v0_1_state() {
+            let mut deps = mock_dependencies();
+
+            v0_1::STATE
+                .save(
+                    &mut deps.storage,
+                    &v0_1::State {
+                        admin: Addr::unchecked("admin"),
+                        tokens: vec![v0_1::TokenConfig {
+                            denom: Denom::Native(NATIVE_DENOM.to_string()),
+                            amount: Uint128::new(DEFAULT_NATIVE_AMOUNT),
+                        }],
+                        rate_limit_seconds: 3600,
+                    },
+                )
+                .unwrap();
+            for (user, last_claim_time) in [("user1", 1_000), ("user2", 3_000), ("user3", 2_000)] {
+                v0_1::USER_CLAIMS
+                    .save(
+                        &mut deps.storage,
+                        &Addr::unchecked(user),
+                        &v0_1::UserInfo { last_claim_time },
+                    )
+                    .unwrap();
+            }
+
+            // The old layout can't be read as the new one
+            assert!(STATE.load(&deps.storage).is_err());
+
+            migrate(&mut deps.storage).unwrap();
+
+            let state = STATE.load(&deps.storage).unwrap();
+            assert_eq!(
+                state,
+                State {
+                    admin: Addr::unchecked("admin"),
+                    tokens: vec![TokenConfig {
+                        denom: Denom::Native(NATIVE_DENOM.to_string()),
+                        amount: Uint128::new(DEFAULT_NATIVE_AMOUNT),
+                        stream: None,
+                        price: None,
+                    }],
+                    rate_limit_seconds: 3600,
+                    depositor_withdrawals: false,
+                    paused: false,
+                    opens_at: None,
+                    closes_at: None,
+                }
+            );
+
+            let stats = CLAIM_STATS.load(&deps.storage).unwrap();
+            assert_eq!(stats.unique_claimers, 3);
+            assert_eq!(stats.total_claims, 3);
+
+            // Existing claim records are kept and indexed by time
+            let user_info = user_claims()
+                .load(&deps.storage, &Addr::unchecked("user1"))
+                .unwrap();
+            assert_eq!(user_info.last_claim_time, 1_000);
+            let by_time = user_claims()
+                .idx
+                .last_claim
+                .keys(&deps.storage, None, None, Order::Ascending)
+                .collect::<StdResult<Vec<_>>>()
+                .unwrap();
+            assert_eq!(
+                by_time,
+                ["user1", "user3", "user2"].map(Addr::unchecked).to_vec()
+            );
+        }
+    }
+}
@@ -1,19 +1,50 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::Addr;
-use cw20::Balance;
+use cosmwasm_std::{Addr, Timestamp, Uint128};
+use cw20::{Balance, Cw20ReceiveMsg, Denom};
 
-use crate::state::{TokenConfig, UserInfo};
+#[cfg(feature = "neutron")]
+use crate::neutron::SybilGate;
+use crate::pow::{PowChallenge, PowConfig};
+use crate::state::{ClaimRecord, Role, Tier, TokenAmount, TokenConfig, UserInfo, VestingSchedule};
 
 #[cw_serde]
 pub struct InstantiateMsg {
     pub admin: Option<Addr>,
     pub tokens: Vec<TokenConfig>,
     pub rate_limit_seconds: Option<u64>,
+    pub opens_at: Option<Timestamp>,
+    pub closes_at: Option<Timestamp>,
+}
+
+#[cw_serde]
+pub struct MigrateMsg {}
+
+/// Messages chain governance can send without being the admin
+#[cw_serde]
+pub enum SudoMsg {
+    ForceSetAdmin {
+        admin: String,
+    },
+    /// Replace the given settings, leaving the others unchanged
+    ForceUpdateConfig {
+        tokens: Option<Vec<TokenConfig>>,
+        rate_limit_seconds: Option<u64>,
+        paused: Option<bool>,
+    },
+    /// Send faucet funds to `recipient`, defaulting to the full available balance
+    ForceWithdraw {
+        denom: Denom,
+        amount: Option<Uint128>,
+        recipient: String,
+    },
 }
 
 #[cw_serde]
 pub enum ExecuteMsg {
-    Claim {},
+    Claim {
+        /// Proof of work solution, required when the challenge is enabled
+        nonce: Option<u64>,
+    },
     UpdateTokenConfig {
         token_index: u32,
         new_config: TokenConfig,
@@ -24,6 +55,70 @@ pub enum ExecuteMsg {
     SetAdmin {
         admin: String,
     },
+    /// Replace the tier rules scaling claim amounts for badge holders
+    SetTiers {
+        tiers: Vec<Tier>,
+    },
+    /// Require claims to solve a proof of work challenge, or disable it with `None`
+    SetPowConfig {
+        config: Option<PowConfig>,
+    },
+    /// Draw a new proof of work seed once the stored one is from an earlier epoch.
+    /// Anyone may call this.
+    RotatePowSeed {},
+    /// Give `address` an `Operator` or `Pauser` role, admin only
+    GrantRole {
+        address: String,
+        role: Role,
+    },
+    RevokeRole {
+        address: String,
+        role: Role,
+    },
+    /// Refill the faucet with CW20 tokens via `Cw20ExecuteMsg::Send`
+    Receive(Cw20ReceiveMsg),
+    /// Refill the faucet with the native funds attached to the message
+    Deposit {},
+    /// Reclaim unused tokens. The admin may withdraw any amount (defaults to the
+    /// full balance); depositors receive their pro rata share of what is left of the
+    /// deposits when enabled.
+    Withdraw {
+        denom: Denom,
+        amount: Option<Uint128>,
+    },
+    /// Release the vested part of the sender's streamed claims
+    WithdrawVested {},
+    /// Push tokens to a list of recipients, admin only. Tokens with a stream config
+    /// vest like claims, the rest are sent right away with one bank send per recipient.
+    Airdrop {
+        recipients: Vec<(String, Vec<TokenConfig>)>,
+    },
+    SetDepositorWithdrawals {
+        enabled: bool,
+    },
+    /// Stop all claims until `Unpause` is sent
+    Pause {},
+    Unpause {},
+    /// Only allow claims between `opens_at` and `closes_at`, either bound optional
+    SetClaimWindow {
+        opens_at: Option<Timestamp>,
+        closes_at: Option<Timestamp>,
+    },
+    /// Require claimers to prove a Cosmos Hub account via interchain queries
+    #[cfg(feature = "neutron")]
+    SetSybilGate {
+        gate: Option<SybilGate>,
+    },
+    /// Link the registered interchain query watching the sender's Cosmos Hub account
+    #[cfg(feature = "neutron")]
+    LinkHubAccount {
+        query_id: u64,
+    },
+}
+
+#[cw_serde]
+pub enum ReceiveMsg {
+    Deposit {},
 }
 
 #[cw_serde]
@@ -41,6 +136,64 @@ pub enum QueryMsg {
     GetBalance {},
     #[returns(ClaimStatusResponse)]
     CanUserClaim { address: String },
+    #[returns(DepositorsResponse)]
+    Depositors {
+        denom: Denom,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    #[returns(DepositorResponse)]
+    Depositor { address: String },
+    /// Claims made by `address`, most recent first
+    #[returns(ClaimHistoryResponse)]
+    ClaimHistory {
+        address: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Claims made between the `from` and `to` timestamps (in seconds, inclusive)
+    #[returns(ClaimHistoryResponse)]
+    ClaimsInRange {
+        from: u64,
+        to: u64,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    #[returns(StatsResponse)]
+    Stats {},
+    #[returns(ClaimWindowResponse)]
+    ClaimWindow {},
+    #[returns(TiersResponse)]
+    Tiers {},
+    #[returns(PowChallengeResponse)]
+    PowChallenge {},
+    /// Addresses holding roles besides the admin, ordered by address
+    #[returns(RolesResponse)]
+    Roles {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Everyone who has claimed, ordered by address
+    #[returns(ClaimersResponse)]
+    AllClaimers {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Claimers still rate limited at `at_time` (defaults to the current block time),
+    /// ordered by last claim time, then address. Only each claimer's last claim is
+    /// looked at, so for a past `at_time` claimers who claimed again since are left out.
+    #[returns(ClaimersResponse)]
+    ClaimersInCooldown {
+        at_time: Option<Timestamp>,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    #[cfg(feature = "neutron")]
+    #[returns(SybilGateResponse)]
+    SybilGate {},
+    #[cfg(feature = "neutron")]
+    #[returns(HubAccountResponse)]
+    HubAccount { address: String },
 }
 
 #[cw_serde]
@@ -56,6 +209,10 @@ pub struct RateLimitResponse {
 #[cw_serde]
 pub struct UserInfoResponse {
     pub user_info: Option<UserInfo>,
+    /// Open token streams from past claims
+    pub vesting: Vec<VestingSchedule>,
+    /// Vested amounts `WithdrawVested` would release now, per token
+    pub withdrawable: Vec<TokenAmount>,
 }
 
 #[cw_serde]
@@ -72,4 +229,99 @@ pub struct BalanceResponse {
 pub struct ClaimStatusResponse {
     pub can_claim: bool,
     pub seconds_until_next_claim: u64,
+    /// Why the user can't claim, unset when `can_claim` is true
+    pub reason: Option<ClaimBlockedReason>,
+}
+
+#[cw_serde]
+pub enum ClaimBlockedReason {
+    Paused,
+    NotOpenYet { opens_at: Timestamp },
+    Closed { closed_at: Timestamp },
+    RateLimited { seconds_remaining: u64 },
+}
+
+#[cw_serde]
+pub struct ClaimWindowResponse {
+    pub paused: bool,
+    pub opens_at: Option<Timestamp>,
+    pub closes_at: Option<Timestamp>,
+}
+
+#[cw_serde]
+pub struct PowChallengeResponse {
+    pub challenge: Option<PowChallenge>,
+}
+
+#[cw_serde]
+pub struct TiersResponse {
+    pub tiers: Vec<Tier>,
+}
+
+#[cw_serde]
+pub struct RolesResponse {
+    /// The admin, who holds every role
+    pub owner: String,
+    pub members: Vec<RoleMember>,
+}
+
+#[cw_serde]
+pub struct RoleMember {
+    pub address: String,
+    pub roles: Vec<Role>,
+}
+
+#[cw_serde]
+pub struct ClaimersResponse {
+    pub claimers: Vec<ClaimerInfo>,
+}
+
+#[cw_serde]
+pub struct ClaimerInfo {
+    pub address: String,
+    pub last_claim_time: u64,
+    /// Seconds until the rate limit lets the claimer claim again, zero when it already does
+    pub seconds_remaining: u64,
+}
+
+#[cw_serde]
+pub struct DepositorsResponse {
+    pub total: Uint128,
+    pub depositors: Vec<DepositorAmount>,
+}
+
+#[cw_serde]
+pub struct DepositorAmount {
+    pub address: String,
+    pub amount: Uint128,
+}
+
+#[cw_serde]
+pub struct DepositorResponse {
+    pub address: String,
+    pub deposits: Vec<TokenAmount>,
+}
+
+#[cw_serde]
+pub struct ClaimHistoryResponse {
+    pub claims: Vec<ClaimRecord>,
+}
+
+#[cw_serde]
+pub struct StatsResponse {
+    pub total_claims: u64,
+    pub unique_claimers: u64,
+    pub distributed: Vec<TokenAmount>,
+}
+
+#[cfg(feature = "neutron")]
+#[cw_serde]
+pub struct SybilGateResponse {
+    pub gate: Option<SybilGate>,
+}
+
+#[cfg(feature = "neutron")]
+#[cw_serde]
+pub struct HubAccountResponse {
+    pub query_id: Option<u64>,
 }
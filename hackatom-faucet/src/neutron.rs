@@ -0,0 +1,249 @@
+//! Optional anti-sybil gate for Neutron deployments.
+//!
+//! When enabled, a claimer must link a registered interchain query (ICQ) that watches
+//! their own Cosmos Hub account, and the latest query result must show a minimum balance
+//! or account sequence. The Hub account is tied to the claimer by comparing the account
+//! bytes in the query keys with the claimer's canonical address.
+
+use cosmos_sdk_proto::cosmos::auth::v1beta1::BaseAccount;
+use cosmos_sdk_proto::traits::Message;
+use cosmos_sdk_proto::Any;
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Deps, DepsMut, Env, MessageInfo, QuerierWrapper, Response, StdError};
+use cosmwasm_std::{StdResult, Uint128};
+use cw_storage_plus::{Item, Map};
+use neutron_sdk::bindings::query::NeutronQuery;
+use neutron_sdk::bindings::types::{RegisteredQuery, StorageValue};
+use neutron_sdk::interchain_queries::types::{KVReconstruct, QueryType};
+use neutron_sdk::interchain_queries::v047::helpers::deconstruct_account_denom_balance_key;
+use neutron_sdk::interchain_queries::v047::types::{Balances, BANK_STORE_KEY};
+use neutron_sdk::interchain_queries::{get_registered_query, query_kv_result};
+use neutron_sdk::{NeutronError, NeutronResult};
+
+use crate::error::ContractError;
+use crate::events::config_update_event;
+use crate::helpers::assert_role;
+use crate::state::{Role, STATE};
+
+// Auth module store key and account prefix on the remote chain
+pub const AUTH_STORE_KEY: &str = "acc";
+pub const ACCOUNT_PREFIX: u8 = 0x01;
+
+#[cw_serde]
+pub enum HubRequirement {
+    /// The Hub account must hold at least `amount` of `denom`
+    MinBalance { denom: String, amount: Uint128 },
+    /// The Hub account must have sent at least `sequence` transactions
+    MinSequence { sequence: u64 },
+}
+
+#[cw_serde]
+pub struct SybilGate {
+    /// IBC connection to the Cosmos Hub the queries must use
+    pub connection_id: String,
+    pub requirement: HubRequirement,
+    /// Maximum age of a query result, in Neutron blocks
+    pub max_result_age: u64,
+}
+
+pub const SYBIL_GATE: Item<SybilGate> = Item::new("sybil_gate");
+// Registered interchain query id linked to each claimer
+pub const HUB_QUERIES: Map<&Addr, u64> = Map::new("hub_queries");
+
+/// Cosmos Hub account reconstructed from an auth module KV query result
+#[derive(Clone, Debug, PartialEq)]
+pub struct HubAccount {
+    pub address: Vec<u8>,
+    pub sequence: u64,
+}
+
+impl KVReconstruct for HubAccount {
+    fn reconstruct(storage_values: &[StorageValue]) -> NeutronResult<HubAccount> {
+        let kv = storage_values.first().ok_or_else(|| {
+            NeutronError::Std(StdError::generic_err("Empty account query result"))
+        })?;
+
+        let address = deconstruct_account_key(kv.key.as_slice())?;
+        let any = Any::decode(kv.value.as_slice())?;
+        let account = BaseAccount::decode(any.value.as_slice())?;
+
+        Ok(HubAccount {
+            address,
+            sequence: account.sequence,
+        })
+    }
+}
+
+/// Storage key of an account in the remote auth module: `0x01 | address bytes`
+pub fn create_account_key(address: &[u8]) -> Vec<u8> {
+    let mut key = vec![ACCOUNT_PREFIX];
+    key.extend_from_slice(address);
+    key
+}
+
+fn deconstruct_account_key(key: &[u8]) -> StdResult<Vec<u8>> {
+    match key.split_first() {
+        Some((&ACCOUNT_PREFIX, address)) if !address.is_empty() => Ok(address.to_vec()),
+        _ => Err(StdError::generic_err("Invalid account key")),
+    }
+}
+
+pub fn execute_set_sybil_gate(
+    deps: DepsMut,
+    info: MessageInfo,
+    gate: Option<SybilGate>,
+) -> Result<Response, ContractError> {
+    // Load state using Item
+    let state = STATE.load(deps.storage)?;
+
+    // Check if sender is admin
+    assert_role(deps.storage, &state, &info.sender, Role::Owner)?;
+
+    match &gate {
+        Some(gate) => SYBIL_GATE.save(deps.storage, gate)?,
+        None => SYBIL_GATE.remove(deps.storage),
+    }
+
+    Ok(Response::new()
+        .add_event(
+            config_update_event("sybil_gate").add_attribute("enabled", gate.is_some().to_string()),
+        )
+        .add_attribute("action", "set_sybil_gate")
+        .add_attribute("enabled", gate.is_some().to_string()))
+}
+
+pub fn execute_link_hub_account(
+    deps: DepsMut,
+    info: MessageInfo,
+    query_id: u64,
+) -> Result<Response, ContractError> {
+    let gate = SYBIL_GATE
+        .may_load(deps.storage)?
+        .ok_or(ContractError::SybilGateDisabled {})?;
+
+    let registered = get_registered_query(neutron_deps(&deps.as_ref()), query_id)
+        .map_err(neutron_error)?
+        .registered_query;
+    check_query_matches(deps.as_ref(), &gate, &registered, &info.sender)?;
+
+    HUB_QUERIES.save(deps.storage, &info.sender, &query_id)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "link_hub_account")
+        .add_attribute("claimer", info.sender.to_string())
+        .add_attribute("query_id", query_id.to_string()))
+}
+
+/// Rejects the claim unless the gate is disabled or the claimer's linked Hub account
+/// currently meets the configured requirement.
+pub fn check_sybil_gate(deps: Deps, env: &Env, claimer: &Addr) -> Result<(), ContractError> {
+    let gate = match SYBIL_GATE.may_load(deps.storage)? {
+        Some(gate) => gate,
+        None => return Ok(()),
+    };
+
+    let query_id = HUB_QUERIES
+        .may_load(deps.storage, claimer)?
+        .ok_or(ContractError::HubAccountNotLinked {})?;
+
+    let neutron_deps = neutron_deps(&deps);
+    let registered = get_registered_query(neutron_deps, query_id)
+        .map_err(neutron_error)?
+        .registered_query;
+
+    // The query may have been updated since it was linked
+    check_query_matches(deps, &gate, &registered, claimer)?;
+
+    let submitted_at = registered.last_submitted_result_local_height;
+    if submitted_at == 0 || env.block.height.saturating_sub(submitted_at) > gate.max_result_age {
+        return Err(ContractError::StaleHubResult {});
+    }
+
+    match &gate.requirement {
+        HubRequirement::MinBalance { denom, amount } => {
+            let balances: Balances =
+                query_kv_result(neutron_deps, query_id).map_err(neutron_error)?;
+            let balance = balances
+                .coins
+                .iter()
+                .find(|coin| &coin.denom == denom)
+                .map(|coin| coin.amount)
+                .unwrap_or_default();
+            if balance < *amount {
+                return Err(ContractError::HubRequirementNotMet {
+                    requirement: "balance".to_string(),
+                });
+            }
+        }
+        HubRequirement::MinSequence { sequence } => {
+            let account: HubAccount =
+                query_kv_result(neutron_deps, query_id).map_err(neutron_error)?;
+            if account.sequence < *sequence {
+                return Err(ContractError::HubRequirementNotMet {
+                    requirement: "sequence".to_string(),
+                });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks that a registered query watches the claimer's own Hub account for the data
+/// the gate requires.
+fn check_query_matches(
+    deps: Deps,
+    gate: &SybilGate,
+    registered: &RegisteredQuery,
+    claimer: &Addr,
+) -> Result<(), ContractError> {
+    let mismatch = || ContractError::HubQueryMismatch {};
+    if registered.query_type != QueryType::KV || registered.connection_id != gate.connection_id {
+        return Err(mismatch());
+    }
+
+    let claimer_bytes = deps.api.addr_canonicalize(claimer.as_str())?;
+    let key = match registered.keys.as_slice() {
+        [key] => key,
+        _ => return Err(mismatch()),
+    };
+
+    let address = match &gate.requirement {
+        HubRequirement::MinBalance { denom, .. } => {
+            let (address, key_denom) =
+                deconstruct_account_denom_balance_key(key.key.to_vec()).map_err(|_| mismatch())?;
+            if key.path != BANK_STORE_KEY || &key_denom != denom {
+                return Err(mismatch());
+            }
+            address
+        }
+        HubRequirement::MinSequence { .. } => {
+            if key.path != AUTH_STORE_KEY {
+                return Err(mismatch());
+            }
+            deconstruct_account_key(key.key.as_slice()).map_err(|_| mismatch())?
+        }
+    };
+
+    if address.as_slice() != claimer_bytes.as_slice() {
+        return Err(ContractError::HubAccountMismatch {});
+    }
+
+    Ok(())
+}
+
+/// View the contract dependencies with the Neutron custom querier
+fn neutron_deps<'a>(deps: &'a Deps) -> Deps<'a, NeutronQuery> {
+    Deps {
+        storage: deps.storage,
+        api: deps.api,
+        querier: QuerierWrapper::new(&*deps.querier),
+    }
+}
+
+fn neutron_error(err: NeutronError) -> StdError {
+    match err {
+        NeutronError::Std(err) => err,
+        err => StdError::generic_err(err.to_string()),
+    }
+}
@@ -0,0 +1,153 @@
+//! Price-weighted claim amounts.
+//!
+//! A token with a `PriceConfig` hands out `value / price` units per claim, where `price`
+//! is what one unit of the token is worth in the reference asset. Prices come from a
+//! `PriceOracle`; on chain that is a contract answering `OracleQueryMsg`. Amounts
+//! outside the configured bounds are rejected so a broken oracle can neither drain the
+//! faucet nor hand out dust. Tier multipliers apply on top of the priced amount.
+
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{Addr, Decimal, Deps, QuerierWrapper, StdResult, Uint128};
+use cw20::Denom;
+
+use crate::error::ContractError;
+use crate::state::{denom_key, PriceConfig, TokenConfig};
+
+/// Query API an oracle contract must implement
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum OracleQueryMsg {
+    /// Price of one unit of `denom` in the `quote` asset
+    #[returns(PriceResponse)]
+    Price { denom: Denom, quote: String },
+}
+
+#[cw_serde]
+pub struct PriceResponse {
+    pub price: Decimal,
+}
+
+/// Source of token prices in a reference asset
+pub trait PriceOracle {
+    fn price(&self, denom: &Denom, quote: &str) -> StdResult<Decimal>;
+}
+
+/// Oracle contract implementing `OracleQueryMsg`
+pub struct ContractOracle<'a> {
+    querier: QuerierWrapper<'a>,
+    contract: &'a Addr,
+}
+
+impl<'a> ContractOracle<'a> {
+    pub fn new(querier: QuerierWrapper<'a>, contract: &'a Addr) -> Self {
+        ContractOracle { querier, contract }
+    }
+}
+
+impl PriceOracle for ContractOracle<'_> {
+    fn price(&self, denom: &Denom, quote: &str) -> StdResult<Decimal> {
+        let res: PriceResponse = self.querier.query_wasm_smart(
+            self.contract,
+            &OracleQueryMsg::Price {
+                denom: denom.clone(),
+                quote: quote.to_string(),
+            },
+        )?;
+        Ok(res.price)
+    }
+}
+
+/// Units of `denom` worth `config.value` at the oracle price, within the configured bounds
+pub fn priced_amount(
+    oracle: &dyn PriceOracle,
+    denom: &Denom,
+    config: &PriceConfig,
+) -> Result<Uint128, ContractError> {
+    let price = oracle.price(denom, &config.quote)?;
+    let amount = config
+        .value
+        .checked_div(price)
+        .map_err(|_| ContractError::InvalidPrice {
+            denom: denom_key(denom),
+        })?
+        .to_uint_floor();
+
+    if amount < config.min_amount || amount > config.max_amount {
+        return Err(ContractError::PriceOutOfBounds {
+            denom: denom_key(denom),
+            amount,
+            min: config.min_amount,
+            max: config.max_amount,
+        });
+    }
+
+    Ok(amount)
+}
+
+/// Units of a token handed out per claim before tier multipliers
+pub fn token_amount(deps: Deps, token: &TokenConfig) -> Result<Uint128, ContractError> {
+    match &token.price {
+        Some(price) => priced_amount(
+            &ContractOracle::new(deps.querier, &price.oracle),
+            &token.denom,
+            price,
+        ),
+        None => Ok(token.amount),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FixedPrice(Decimal);
+
+    impl PriceOracle for FixedPrice {
+        fn price(&self, _denom: &Denom, _quote: &str) -> StdResult<Decimal> {
+            Ok(self.0)
+        }
+    }
+
+    fn config() -> PriceConfig {
+        PriceConfig {
+            oracle: Addr::unchecked("oracle"),
+            quote: "usd".to_string(),
+            value: Decimal::percent(500),
+            min_amount: Uint128::new(1_000),
+            max_amount: Uint128::new(100_000),
+        }
+    }
+
+    #[test]
+    fn divides_value_by_price() {
+        let denom = Denom::Native("untrn".to_string());
+        // $5 at $0.0001 per unit
+        let price = Decimal::from_ratio(1u128, 10_000u128);
+        let amount = priced_amount(&FixedPrice(price), &denom, &config()).unwrap();
+        assert_eq!(amount, Uint128::new(50_000));
+    }
+
+    #[test]
+    fn rejects_prices_outside_bounds() {
+        let denom = Denom::Native("untrn".to_string());
+
+        let err = priced_amount(&FixedPrice(Decimal::zero()), &denom, &config()).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidPrice { .. }));
+
+        // Price crashed, the amount would exceed the maximum
+        let err = priced_amount(&FixedPrice(Decimal::raw(1)), &denom, &config()).unwrap_err();
+        assert!(matches!(err, ContractError::PriceOutOfBounds { .. }));
+
+        // Price spiked, the amount would be dust
+        let err = priced_amount(&FixedPrice(Decimal::one()), &denom, &config()).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::PriceOutOfBounds {
+                denom: "native:untrn".to_string(),
+                amount: Uint128::new(5),
+                min: Uint128::new(1_000),
+                max: Uint128::new(100_000),
+            }
+        );
+    }
+}
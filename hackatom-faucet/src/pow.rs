@@ -0,0 +1,274 @@
+//! Optional proof-of-work challenge for claims.
+//!
+//! When enabled, a claim must carry a nonce such that
+//! `sha256(sender || nonce || seed)` starts with `difficulty` zero bits, where `sender`
+//! is the bech32 address string and `nonce` is a big-endian `u64`.
+//!
+//! The seed rotates every epoch of `epoch_blocks` blocks, on the first claim of the
+//! epoch, to `sha256(previous seed || block time || block height || tx index)`. Seeds
+//! can't be derived ahead of time, so nonces can only be searched once the seed is
+//! published. Solutions for the previous epoch's seed are still accepted so a claim sent
+//! just before the seed rotates does not fail.
+//!
+//! A seed older than that has expired. Claims fail until anyone sends `RotatePowSeed`
+//! to draw a fresh one, since a failing claim can't rotate it.
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Storage};
+use cw_storage_plus::Item;
+use sha2::{Digest, Sha256};
+
+use crate::error::ContractError;
+use crate::events::config_update_event;
+use crate::helpers::assert_role;
+use crate::state::{Role, STATE};
+
+// Higher difficulties take too long to solve in a browser
+pub const MAX_DIFFICULTY: u8 = 32;
+
+#[cw_serde]
+pub struct PowConfig {
+    /// Required number of leading zero bits in the hash
+    pub difficulty: u8,
+    /// Number of blocks after which the seed rotates
+    pub epoch_blocks: u64,
+}
+
+pub const POW_CONFIG: Item<PowConfig> = Item::new("pow_config");
+pub const POW_SEED: Item<PowSeed> = Item::new("pow_seed");
+
+/// Seed of the latest epoch a claim was made in
+#[cw_serde]
+pub struct PowSeed {
+    pub epoch: u64,
+    pub seed: Binary,
+    /// Seed of the epoch before, still accepted
+    pub previous: Option<Binary>,
+}
+
+/// Seed claimers must currently solve for
+#[cw_serde]
+pub struct PowChallenge {
+    pub difficulty: u8,
+    pub epoch: u64,
+    pub seed: Binary,
+    /// First block height of the next epoch, the seed rotates on the first claim from
+    /// then on
+    pub next_epoch_height: u64,
+    /// First block height the seed is no longer accepted at, unless it was rotated
+    pub expires_at_height: u64,
+}
+
+pub fn execute_set_pow_config(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    config: Option<PowConfig>,
+) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+
+    // Check if sender is an operator
+    assert_role(deps.storage, &state, &info.sender, Role::Operator)?;
+
+    match &config {
+        Some(config) => {
+            if config.difficulty > MAX_DIFFICULTY {
+                return Err(ContractError::DifficultyTooHigh {
+                    max: MAX_DIFFICULTY,
+                });
+            }
+            if config.epoch_blocks == 0 {
+                return Err(ContractError::InvalidEpoch {});
+            }
+            POW_CONFIG.save(deps.storage, config)?;
+
+            // Start over from a fresh seed, epochs are counted differently now
+            let seed = PowSeed {
+                epoch: env.block.height / config.epoch_blocks,
+                seed: Binary::from(next_seed(env.contract.address.as_bytes(), &env)),
+                previous: None,
+            };
+            POW_SEED.save(deps.storage, &seed)?;
+        }
+        None => {
+            POW_CONFIG.remove(deps.storage);
+            POW_SEED.remove(deps.storage);
+        }
+    }
+
+    let mut event =
+        config_update_event("pow").add_attribute("enabled", config.is_some().to_string());
+    if let Some(config) = &config {
+        event = event
+            .add_attribute("difficulty", config.difficulty.to_string())
+            .add_attribute("epoch_blocks", config.epoch_blocks.to_string());
+    }
+
+    Ok(Response::new()
+        .add_event(event)
+        .add_attribute("action", "set_pow_config")
+        .add_attribute(
+            "difficulty",
+            config
+                .map(|config| config.difficulty.to_string())
+                .unwrap_or_else(|| "none".to_string()),
+        ))
+}
+
+pub fn query_pow_challenge(deps: Deps) -> StdResult<Option<PowChallenge>> {
+    let Some(config) = POW_CONFIG.may_load(deps.storage)? else {
+        return Ok(None);
+    };
+    let seed = POW_SEED.load(deps.storage)?;
+
+    Ok(Some(PowChallenge {
+        difficulty: config.difficulty,
+        epoch: seed.epoch,
+        seed: seed.seed,
+        next_epoch_height: (seed.epoch + 1) * config.epoch_blocks,
+        expires_at_height: (seed.epoch + 2) * config.epoch_blocks,
+    }))
+}
+
+/// Draws a new seed if the current one is from an earlier epoch. Anyone may call this.
+pub fn execute_rotate_pow_seed(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let config = POW_CONFIG
+        .may_load(deps.storage)?
+        .ok_or(ContractError::PowDisabled {})?;
+    let seed = rotate_seed(deps.storage, &env, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "rotate_pow_seed")
+        .add_attribute("epoch", seed.epoch.to_string()))
+}
+
+/// Rejects the claim unless the challenge is disabled or `nonce` solves the current or
+/// previous seed. Rotates the seed on the first claim of a new epoch.
+pub fn check_pow(
+    storage: &mut dyn Storage,
+    env: &Env,
+    claimer: &Addr,
+    nonce: Option<u64>,
+) -> Result<(), ContractError> {
+    let Some(config) = POW_CONFIG.may_load(storage)? else {
+        return Ok(());
+    };
+    let nonce = nonce.ok_or(ContractError::PowNonceRequired {})?;
+
+    // Nobody can have solved a seed drawn by this claim, so the stored one must still
+    // be current or previous
+    let epoch = env.block.height / config.epoch_blocks;
+    if epoch > POW_SEED.load(storage)?.epoch + 1 {
+        return Err(ContractError::PowSeedExpired {});
+    }
+    let seed = rotate_seed(storage, env, &config)?;
+
+    let solved = [Some(&seed.seed), seed.previous.as_ref()]
+        .into_iter()
+        .flatten()
+        .any(|seed| {
+            leading_zero_bits(&pow_hash(claimer.as_str(), nonce, seed)) >= config.difficulty.into()
+        });
+    if !solved {
+        return Err(ContractError::InvalidProofOfWork {});
+    }
+
+    Ok(())
+}
+
+/// Replaces the stored seed if it is from an earlier epoch, keeping it as the previous
+/// seed only if it is from the epoch right before
+fn rotate_seed(storage: &mut dyn Storage, env: &Env, config: &PowConfig) -> StdResult<PowSeed> {
+    let seed = POW_SEED.load(storage)?;
+    let epoch = env.block.height / config.epoch_blocks;
+    if epoch <= seed.epoch {
+        return Ok(seed);
+    }
+
+    let rotated = PowSeed {
+        epoch,
+        seed: Binary::from(next_seed(&seed.seed, env)),
+        previous: (seed.epoch + 1 == epoch).then_some(seed.seed),
+    };
+    POW_SEED.save(storage, &rotated)?;
+    Ok(rotated)
+}
+
+/// `sha256(previous || time || height || tx index)` with the block time in nanoseconds
+/// and all numbers as big-endian integers
+pub fn next_seed(previous: &[u8], env: &Env) -> [u8; 32] {
+    let tx_index = env.transaction.as_ref().map_or(0, |tx| tx.index);
+    Sha256::new()
+        .chain_update(previous)
+        .chain_update(env.block.time.nanos().to_be_bytes())
+        .chain_update(env.block.height.to_be_bytes())
+        .chain_update(tx_index.to_be_bytes())
+        .finalize()
+        .into()
+}
+
+/// `sha256(sender || nonce || seed)` with the nonce as a big-endian `u64`
+pub fn pow_hash(sender: &str, nonce: u64, seed: &[u8]) -> [u8; 32] {
+    Sha256::new()
+        .chain_update(sender.as_bytes())
+        .chain_update(nonce.to_be_bytes())
+        .chain_update(seed)
+        .finalize()
+        .into()
+}
+
+pub fn leading_zero_bits(hash: &[u8]) -> u32 {
+    let mut bits = 0;
+    for byte in hash {
+        bits += byte.leading_zeros();
+        if *byte != 0 {
+            break;
+        }
+    }
+    bits
+}
+
+/// Finds the lowest nonce solving the challenge for `sender`. Meant for tests and as a
+/// reference for client implementations; expect around `2^difficulty` hashes. `None` if
+/// no nonce solves it.
+pub fn solve(sender: &str, seed: &[u8], difficulty: u8) -> Option<u64> {
+    (0..=u64::MAX)
+        .find(|nonce| leading_zero_bits(&pow_hash(sender, *nonce, seed)) >= difficulty.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::mock_env;
+
+    use super::*;
+
+    #[test]
+    fn counts_leading_zero_bits() {
+        assert_eq!(leading_zero_bits(&[0xff, 0x00]), 0);
+        assert_eq!(leading_zero_bits(&[0x00, 0x10, 0x00]), 11);
+        assert_eq!(leading_zero_bits(&[0x00, 0x00]), 16);
+    }
+
+    #[test]
+    fn solved_nonce_meets_difficulty() {
+        let seed = next_seed(b"faucet", &mock_env());
+        let nonce = solve("claimer", &seed, 12).unwrap();
+        assert!(leading_zero_bits(&pow_hash("claimer", nonce, &seed)) >= 12);
+    }
+
+    #[test]
+    fn next_seed_depends_on_the_block() {
+        let env = mock_env();
+        let seed = next_seed(b"seed", &env);
+
+        let mut later = env.clone();
+        later.block.time = later.block.time.plus_nanos(1);
+        assert_ne!(next_seed(b"seed", &later), seed);
+
+        let mut other_tx = env.clone();
+        other_tx.transaction.as_mut().unwrap().index += 1;
+        assert_ne!(next_seed(b"seed", &other_tx), seed);
+
+        assert_ne!(next_seed(b"other", &env), seed);
+    }
+}
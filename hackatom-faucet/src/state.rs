@@ -2,8 +2,8 @@ use cw20::Denom;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{Addr, Uint128};
-use cw_storage_plus::{Item, Map};
+use cosmwasm_std::{Addr, Decimal, Timestamp, Uint128};
+use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex};
 
 // Default amount for each token type
 pub const DEFAULT_NATIVE_AMOUNT: u128 = 100_000; // 0.1 untrn
@@ -15,10 +15,46 @@ pub const DEFAULT_RATE_LIMIT: u64 = 60 * 60 * 24;
 // Native token denom
 pub const NATIVE_DENOM: &str = "untrn";
 
+// Width of the time buckets claims are indexed by (1 day)
+pub const CLAIM_BUCKET_SECONDS: u64 = 60 * 60 * 24;
+
+// Maximum number of tier rules, each costs a query per claim
+pub const MAX_TIERS: usize = 10;
+
+// Maximum number of airdrop recipients per message, keeps a batch under the gas limit
+pub const MAX_AIRDROP_RECIPIENTS: usize = 50;
+
+// Pagination limits for list queries
+pub const DEFAULT_QUERY_LIMIT: u32 = 10;
+pub const MAX_QUERY_LIMIT: u32 = 30;
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct TokenConfig {
     pub denom: Denom,
     pub amount: Uint128,
+    // Stream the amount to claimers over time instead of sending it at once
+    pub stream: Option<StreamConfig>,
+    // Hand out a fixed value in a reference asset instead of `amount` units
+    pub price: Option<PriceConfig>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StreamConfig {
+    // Seconds over which a claim vests linearly
+    pub duration_seconds: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PriceConfig {
+    // Oracle contract quoting the token in the reference asset
+    pub oracle: Addr,
+    // Reference asset the value is expressed in, e.g. "usd"
+    pub quote: String,
+    // Value handed out per claim, in units of the reference asset
+    pub value: Decimal,
+    // Bounds on the computed unit amount, guarding against bad oracle prices
+    pub min_amount: Uint128,
+    pub max_amount: Uint128,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -31,7 +67,176 @@ pub struct State {
     pub admin: Addr,
     pub tokens: Vec<TokenConfig>,
     pub rate_limit_seconds: u64,
+    // Whether depositors may reclaim their pro rata share of unused tokens
+    pub depositor_withdrawals: bool,
+    // Claims are rejected while paused
+    pub paused: bool,
+    // Optional window outside of which claims are rejected
+    pub opens_at: Option<Timestamp>,
+    pub closes_at: Option<Timestamp>,
+}
+
+/// Tokens streamed to a claimer, released linearly between `start_time` and `end_time`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VestingSchedule {
+    pub id: u64,
+    pub denom: Denom,
+    pub amount: Uint128,
+    pub released: Uint128,
+    pub start_time: u64,
+    pub end_time: u64,
+}
+
+impl VestingSchedule {
+    /// Total amount vested at `time`, including what was already released
+    pub fn vested(&self, time: u64) -> Uint128 {
+        if time >= self.end_time {
+            return self.amount;
+        }
+        let elapsed = time.saturating_sub(self.start_time);
+        self.amount
+            .multiply_ratio(elapsed, self.end_time - self.start_time)
+    }
+
+    /// Vested amount not released yet
+    pub fn withdrawable(&self, time: u64) -> Uint128 {
+        self.vested(time) - self.released
+    }
+}
+
+/// What a claimer must hold to qualify for a tier
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TierRequirement {
+    /// Own at least one NFT from a CW721 collection
+    Cw721 { collection: Addr },
+    /// Hold at least `min_balance` of a CW20 token
+    Cw20 { token: Addr, min_balance: Uint128 },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Tier {
+    pub requirement: TierRequirement,
+    // Applied to every TokenConfig amount
+    pub multiplier: Decimal,
+}
+
+/// Permissions that can be granted to addresses besides the admin, who acts as owner
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    /// Full control, held by `State.admin` and transferred with `SetAdmin`
+    Owner,
+    /// May tune token amounts, the rate limit and the claim window
+    Operator,
+    /// May pause and unpause claims
+    Pauser,
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Owner => "owner",
+            Role::Operator => "operator",
+            Role::Pauser => "pauser",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TokenAmount {
+    pub denom: Denom,
+    pub amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ClaimRecord {
+    pub id: u64,
+    pub claimer: Addr,
+    pub time: u64,
+    pub tokens: Vec<TokenAmount>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct ClaimStats {
+    pub total_claims: u64,
+    pub unique_claimers: u64,
+}
+
+pub struct ClaimIndexes<'a> {
+    pub claimer: MultiIndex<'a, Addr, ClaimRecord, u64>,
+    pub bucket: MultiIndex<'a, u64, ClaimRecord, u64>,
+}
+
+impl IndexList<ClaimRecord> for ClaimIndexes<'_> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<ClaimRecord>> + '_> {
+        let v: Vec<&dyn Index<ClaimRecord>> = vec![&self.claimer, &self.bucket];
+        Box::new(v.into_iter())
+    }
+}
+
+/// Claim log keyed by claim id, indexed by claimer and by day bucket
+pub fn claims<'a>() -> IndexedMap<u64, ClaimRecord, ClaimIndexes<'a>> {
+    let indexes = ClaimIndexes {
+        claimer: MultiIndex::new(
+            |_pk, record: &ClaimRecord| record.claimer.clone(),
+            "claims",
+            "claims__claimer",
+        ),
+        bucket: MultiIndex::new(
+            |_pk, record: &ClaimRecord| record.time / CLAIM_BUCKET_SECONDS,
+            "claims",
+            "claims__bucket",
+        ),
+    };
+    IndexedMap::new("claims", indexes)
+}
+
+pub struct UserClaimIndexes<'a> {
+    pub last_claim: MultiIndex<'a, u64, UserInfo, &'a Addr>,
+}
+
+impl IndexList<UserInfo> for UserClaimIndexes<'_> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<UserInfo>> + '_> {
+        let v: Vec<&dyn Index<UserInfo>> = vec![&self.last_claim];
+        Box::new(v.into_iter())
+    }
+}
+
+/// Last claim of each claimer, indexed by its time
+pub fn user_claims<'a>() -> IndexedMap<&'a Addr, UserInfo, UserClaimIndexes<'a>> {
+    let indexes = UserClaimIndexes {
+        last_claim: MultiIndex::new(
+            |_pk, user_info: &UserInfo| user_info.last_claim_time,
+            "user_claims",
+            "user_claims__last_claim",
+        ),
+    };
+    IndexedMap::new("user_claims", indexes)
+}
+
+/// Storage key for a token, e.g. `native:untrn` or `cw20:<contract address>`.
+pub fn denom_key(denom: &Denom) -> String {
+    match denom {
+        Denom::Native(denom) => format!("native:{}", denom),
+        Denom::Cw20(addr) => format!("cw20:{}", addr),
+    }
 }
 
 pub const STATE: Item<State> = Item::new("state");
-pub const USER_CLAIMS: Map<&Addr, UserInfo> = Map::new("user_claims");
+// Tier rules, ordered by descending multiplier
+pub const TIERS: Item<Vec<Tier>> = Item::new("tiers");
+// Roles granted to addresses other than the admin
+pub const ROLES: Map<&Addr, Vec<Role>> = Map::new("roles");
+// Vesting schedules created by streamed claims, keyed by claimer and schedule id
+pub const VESTING: Map<(&Addr, u64), VestingSchedule> = Map::new("vesting");
+pub const VESTING_SEQ: Item<u64> = Item::new("vesting_seq");
+// Unreleased streamed amounts per token, reserved out of the faucet balance
+pub const VESTING_TOTALS: Map<&str, Uint128> = Map::new("vesting_totals");
+// Contributions per (token key, depositor)
+pub const DEPOSITS: Map<(&str, &Addr), Uint128> = Map::new("deposits");
+// Total contributions per token key
+pub const DEPOSIT_TOTALS: Map<&str, TokenAmount> = Map::new("deposit_totals");
+pub const CLAIM_STATS: Item<ClaimStats> = Item::new("claim_stats");
+// Total distributed per token key
+pub const DISTRIBUTED: Map<&str, TokenAmount> = Map::new("distributed");
@@ -1,16 +1,34 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
-use cosmwasm_std::{to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
-use cw2::set_contract_version;
+use cosmwasm_std::{
+    to_json_binary, Binary, Decimal, Deps, DepsMut, Env, MessageInfo, Order, Response, StdResult,
+    Storage, Timestamp,
+};
+use cw2::{get_contract_version, set_contract_version};
 use execute::{
-    comment_on_post, create_post, create_profile, delete_post, follow, like_post, unfollow,
+    add_moderator, change_username, comment_on_post, create_post, create_profile, delete_comment,
+    delete_post, edit_comment, follow, like_post, receive, remove_moderator, report,
+    resolve_report, set_banned, set_post_hidden, tip_post, unfollow, unlike_post, update_config,
     update_post, update_profile,
 };
-use query::{query_index, query_post, query_profile, query_user_posts};
+use query::{
+    query_author_earnings, query_comments, query_config, query_feed, query_followers,
+    query_following, query_global_feed, query_index, query_is_banned, query_is_following,
+    query_likers, query_moderators, query_post, query_post_tips, query_profile, query_reports,
+    query_stats, query_thread, query_top_tipped_posts, query_user_posts, query_username_history,
+};
 
 use crate::error::ContractError;
-use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
-use crate::state::{Index, Post, Profile, POSTS, POST_INDEX, PROFILES, PROFILE_INDEX, USER_POSTS};
+use crate::migrations;
+use crate::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
+use crate::state::{
+    denom_key, Comment, Config, FollowCounts, Index, Limits, Post, PostId, Profile, Report,
+    ReportTarget, Reservation, Stats, TokenAmount, UsernameChange, ADDR_TO_USERNAME,
+    AUTHOR_EARNINGS, AUTHOR_POSTS, BANNED, COMMENTS, COMMENT_SEQ, CONFIG, FOLLOWERS, FOLLOWING,
+    FOLLOW_COUNTS, LIKES, MODERATORS, POSTS, POST_INDEX, POST_TIPS, PROFILES, PROFILE_INDEX,
+    REPLIES, REPORTED, REPORTS, REPORT_SEQ, RESERVED_USERNAMES, STATS, TIP_RANKING,
+    USERNAME_CHANGE_COOLDOWN_SECONDS, USERNAME_HISTORY, USERNAME_RESERVATION_SECONDS,
+};
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:socialmedia";
@@ -21,19 +39,48 @@ pub fn instantiate(
     deps: DepsMut,
     _env: Env,
     info: MessageInfo,
-    _msg: InstantiateMsg,
+    msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
     // Set contract version. Useful for migration.
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
-    // Initialize index
+    let admin = match msg.admin {
+        Some(admin) => deps.api.addr_validate(&admin)?,
+        None => info.sender.clone(),
+    };
+    let config = Config {
+        admin,
+        limits: validate_limits(msg.limits.unwrap_or_default())?,
+        tip_fee: validate_tip_fee(msg.tip_fee.unwrap_or_default())?,
+    };
+    CONFIG.save(deps.storage, &config)?;
+
+    // Initialize indexes and totals
     let index = Index { current_index: 0 };
+    PROFILE_INDEX.save(deps.storage, &index)?;
     POST_INDEX.save(deps.storage, &index)?;
+    STATS.save(deps.storage, &Stats::default())?;
 
     // Emit event for logging
     Ok(Response::new()
         .add_attribute("action", "instantiate")
-        .add_attribute("owner", info.sender))
+        .add_attribute("owner", info.sender)
+        .add_attribute("admin", config.admin))
+}
+
+// Tip fees are a share of the tip, at most all of it
+fn validate_tip_fee(tip_fee: Decimal) -> Result<Decimal, ContractError> {
+    if tip_fee > Decimal::one() {
+        return Err(ContractError::InvalidTipFee {});
+    }
+    Ok(tip_fee)
+}
+
+fn validate_limits(limits: Limits) -> Result<Limits, ContractError> {
+    if !limits.is_valid() {
+        return Err(ContractError::InvalidLimits {});
+    }
+    Ok(limits)
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -44,6 +91,11 @@ pub fn execute(
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     match msg {
+        ExecuteMsg::UpdateConfig {
+            admin,
+            limits,
+            tip_fee,
+        } => update_config(deps, info, admin, limits, tip_fee),
         ExecuteMsg::CreateProfile {
             user_name,
             about,
@@ -54,28 +106,95 @@ pub fn execute(
             about,
             image_url,
         } => update_profile(deps, env, info, user_name, Some(about), Some(image_url)),
+        ExecuteMsg::ChangeUsername { new_user_name } => {
+            change_username(deps, env, info, new_user_name)
+        }
         ExecuteMsg::Follow {
-            my_user_name,
             following_user_name,
-        } => follow(deps, my_user_name, following_user_name),
+        } => follow(deps, env, info, following_user_name),
         ExecuteMsg::Unfollow {
-            my_user_name,
             following_user_name,
-        } => unfollow(deps, my_user_name, following_user_name),
-        ExecuteMsg::CreatePost { title, content } => create_post(deps, env, info, title, content),
+        } => unfollow(deps, info, following_user_name),
+        ExecuteMsg::CreatePost {
+            title,
+            content,
+            parent_id,
+            quote_of,
+        } => create_post(deps, env, info, title, content, parent_id, quote_of),
         ExecuteMsg::UpdatePost { id, title, content } => {
             update_post(deps, env, info, id, Some(title), Some(content))
         }
         ExecuteMsg::LikePost { id } => like_post(deps, info, id),
-        ExecuteMsg::Comment { id, comment } => comment_on_post(deps, info, id, comment),
+        ExecuteMsg::UnlikePost { id } => unlike_post(deps, info, id),
+        ExecuteMsg::Comment { id, comment } => comment_on_post(deps, env, info, id, comment),
+        ExecuteMsg::EditComment {
+            id,
+            comment_id,
+            comment,
+        } => edit_comment(deps, env, info, id, comment_id, comment),
+        ExecuteMsg::DeleteComment { id, comment_id } => delete_comment(deps, info, id, comment_id),
         ExecuteMsg::DeletePost { id } => delete_post(deps, info, id),
+        ExecuteMsg::AddModerator { address } => add_moderator(deps, info, address),
+        ExecuteMsg::RemoveModerator { address } => remove_moderator(deps, info, address),
+        ExecuteMsg::ReportPost { id, reason } => {
+            report(deps, env, info, ReportTarget::Post { id }, reason)
+        }
+        ExecuteMsg::ReportComment {
+            id,
+            comment_id,
+            reason,
+        } => report(
+            deps,
+            env,
+            info,
+            ReportTarget::Comment { id, comment_id },
+            reason,
+        ),
+        ExecuteMsg::ResolveReport { report_id } => resolve_report(deps, info, report_id),
+        ExecuteMsg::HidePost { id } => set_post_hidden(deps, info, id, true),
+        ExecuteMsg::UnhidePost { id } => set_post_hidden(deps, info, id, false),
+        ExecuteMsg::TipPost { id } => tip_post(deps, info, id),
+        ExecuteMsg::Receive(msg) => receive(deps, info, msg),
+        ExecuteMsg::BanUser { address } => set_banned(deps, info, address, true),
+        ExecuteMsg::UnbanUser { address } => set_banned(deps, info, address, false),
     }
 }
 
 pub mod execute {
+    use cosmwasm_std::{from_json, Addr, BankMsg, Coin, CosmosMsg, Uint128, WasmMsg};
+    use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, Denom};
+
+    use crate::msg::ReceiveMsg;
+    use crate::state::{Username, IMAGE_URL_SCHEMES, MAX_USERNAME_LENGTH, MIN_USERNAME_LENGTH};
 
     use super::*;
 
+    pub fn update_config(
+        deps: DepsMut,
+        info: MessageInfo,
+        admin: Option<String>,
+        limits: Option<Limits>,
+        tip_fee: Option<Decimal>,
+    ) -> Result<Response, ContractError> {
+        let mut config = assert_admin(deps.storage, &info.sender)?;
+
+        if let Some(admin) = admin {
+            config.admin = deps.api.addr_validate(&admin)?;
+        }
+        if let Some(limits) = limits {
+            config.limits = validate_limits(limits)?;
+        }
+        if let Some(tip_fee) = tip_fee {
+            config.tip_fee = validate_tip_fee(tip_fee)?;
+        }
+
+        CONFIG.save(deps.storage, &config)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "update_config")
+            .add_attribute("admin", config.admin))
+    }
+
     pub fn create_profile(
         deps: DepsMut,
         env: Env,
@@ -87,20 +206,29 @@ pub mod execute {
         // Get post id.
         let profile_id = PROFILE_INDEX.load(deps.storage)?.current_index + 1;
 
-        // Ensure max length is fixed.
-        if about.trim().len() > 240 {
-            return Err(ContractError::AboutTooLong {});
+        let limits = CONFIG.load(deps.storage)?.limits;
+        let about = validate_text(
+            &about,
+            limits.max_about_length,
+            true,
+            ContractError::AboutTooLong {},
+        )?;
+        let image_url = validate_image_url(&image_url, &limits)?;
+
+        // One profile per address, one address per username
+        if ADDR_TO_USERNAME.has(deps.storage, &info.sender) {
+            return Err(ContractError::ProfileAlreadyExists {});
         }
+        let key = validate_user_name(&user_name)?;
+        claim_user_name(deps.storage, &env, &info.sender, &key)?;
 
         // Construct new profile
         let new_profile = Profile {
             id: profile_id,
-            addr: info.sender,
-            user_name: user_name.trim().to_lowercase(),
-            about: about.trim().to_string(),
+            addr: info.sender.clone(),
+            user_name: key.clone(),
+            about,
             image: image_url,
-            followers: vec![],
-            following: vec![],
             created_at: env.block.time.seconds(),
             updated_at: env.block.time.seconds(),
         };
@@ -114,7 +242,9 @@ pub mod execute {
         )?;
 
         // Add to profile storage
-        PROFILES.save(deps.storage, user_name.trim().to_lowercase(), &new_profile)?;
+        PROFILES.save(deps.storage, key.clone(), &new_profile)?;
+        ADDR_TO_USERNAME.save(deps.storage, &info.sender, &key)?;
+        update_stats(deps.storage, |stats| stats.profiles += 1)?;
 
         Ok(Response::new()
             .add_attribute("action", "create_profile")
@@ -136,15 +266,18 @@ pub mod execute {
             return Err(ContractError::Unauthorized {});
         }
 
+        let limits = CONFIG.load(deps.storage)?.limits;
         if let Some(about) = about {
-            if about.trim().len() > 240 {
-                return Err(ContractError::AboutTooLong {});
-            }
-            profile.about = about.trim().to_string();
+            profile.about = validate_text(
+                &about,
+                limits.max_about_length,
+                true,
+                ContractError::AboutTooLong {},
+            )?;
         }
 
         if let Some(image_url) = image_url {
-            profile.image = image_url;
+            profile.image = validate_image_url(&image_url, &limits)?;
         }
 
         profile.updated_at = env.block.time.seconds();
@@ -156,83 +289,114 @@ pub mod execute {
             .add_attribute("user_name", user_name))
     }
 
-    pub fn follow(
+    pub fn change_username(
         deps: DepsMut,
-        my_user_name: String,
-        following_user_name: String,
+        env: Env,
+        info: MessageInfo,
+        new_user_name: String,
     ) -> Result<Response, ContractError> {
-        let mut my_profile = PROFILES.load(deps.storage, my_user_name.trim().to_lowercase())?;
-        my_profile.following.push(following_user_name.clone());
+        let old_user_name = sender_user_name(deps.storage, &info.sender)?;
+        let new_user_name = validate_user_name(&new_user_name)?;
+
+        let last_change = USERNAME_HISTORY
+            .prefix(&info.sender)
+            .range(deps.storage, None, None, Order::Descending)
+            .next()
+            .transpose()?;
+        if let Some((_, change)) = &last_change {
+            if env.block.time
+                < change
+                    .changed_at
+                    .plus_seconds(USERNAME_CHANGE_COOLDOWN_SECONDS)
+            {
+                return Err(ContractError::UsernameChangeCooldown {});
+            }
+        }
 
-        let mut following_profile =
-            PROFILES.load(deps.storage, following_user_name.trim().to_lowercase())?;
-        following_profile.followers.push(my_user_name.clone());
+        claim_user_name(deps.storage, &env, &info.sender, &new_user_name)?;
+
+        // Move the profile
+        let mut profile = PROFILES.load(deps.storage, old_user_name.clone())?;
+        profile.user_name = new_user_name.clone();
+        profile.updated_at = env.block.time.seconds();
+        PROFILES.remove(deps.storage, old_user_name.clone());
+        PROFILES.save(deps.storage, new_user_name.clone(), &profile)?;
+        ADDR_TO_USERNAME.save(deps.storage, &info.sender, &new_user_name)?;
 
-        PROFILES.save(
+        // Keep the old name for the sender until the reservation expires
+        RESERVED_USERNAMES.save(
             deps.storage,
-            my_user_name.trim().to_lowercase(),
-            &my_profile,
+            old_user_name.clone(),
+            &Reservation {
+                addr: info.sender.clone(),
+                expires_at: env.block.time.plus_seconds(USERNAME_RESERVATION_SECONDS),
+            },
         )?;
-        PROFILES.save(
+
+        let change_id = last_change.map_or(0, |(last, _)| last + 1);
+        USERNAME_HISTORY.save(
             deps.storage,
-            following_user_name.trim().to_lowercase(),
-            &following_profile,
+            (&info.sender, change_id),
+            &UsernameChange {
+                old_user_name: old_user_name.clone(),
+                new_user_name: new_user_name.clone(),
+                changed_at: env.block.time,
+            },
         )?;
 
         Ok(Response::new()
-            .add_attribute("action", "follow")
-            .add_attribute("user_name", my_user_name)
-            .add_attribute("follow", following_user_name))
+            .add_attribute("action", "change_username")
+            .add_attribute("old_user_name", old_user_name)
+            .add_attribute("new_user_name", new_user_name))
     }
 
-    pub fn unfollow(
+    pub fn follow(
         deps: DepsMut,
-        my_user_name: String,
+        env: Env,
+        info: MessageInfo,
         following_user_name: String,
     ) -> Result<Response, ContractError> {
-        // remove from user profile
-        let mut my_profile = PROFILES.load(deps.storage, my_user_name.trim().to_lowercase())?;
-        let mut following = my_profile.following;
-
-        let following_index = following
-            .iter()
-            .position(|user_name| user_name == &following_user_name);
-
-        match following_index {
-            Some(following_index) => {
-                following.remove(following_index);
-                my_profile.following = following;
-            }
-            None => return Err(ContractError::ProfileNotFound {}),
+        // The acting profile is always the sender's
+        let my_user_name = sender_user_name(deps.storage, &info.sender)?;
+        let following_user_name = following_user_name.trim().to_lowercase();
+        if following_user_name == my_user_name {
+            return Err(ContractError::CannotFollowSelf {});
+        }
+        let following = PROFILES
+            .may_load(deps.storage, following_user_name.clone())?
+            .ok_or(ContractError::ProfileNotFound {})?
+            .addr;
+        if FOLLOWING.has(deps.storage, (&info.sender, &following)) {
+            return Err(ContractError::AlreadyFollowing {});
         }
 
-        // remove from follower profile
-        let mut following_profile =
-            PROFILES.load(deps.storage, following_user_name.trim().to_lowercase())?;
-        let mut followers = following_profile.followers;
+        add_follow(deps.storage, &info.sender, &following, env.block.time)?;
 
-        let follower_index = followers
-            .iter()
-            .position(|user_name| user_name == &my_user_name);
+        Ok(Response::new()
+            .add_attribute("action", "follow")
+            .add_attribute("user_name", my_user_name)
+            .add_attribute("follow", following_user_name))
+    }
 
-        match follower_index {
-            Some(follower_index) => {
-                followers.remove(follower_index);
-                following_profile.followers = followers;
+    pub fn unfollow(
+        deps: DepsMut,
+        info: MessageInfo,
+        following_user_name: String,
+    ) -> Result<Response, ContractError> {
+        // The acting profile is always the sender's
+        let my_user_name = sender_user_name(deps.storage, &info.sender)?;
+        let following_user_name = following_user_name.trim().to_lowercase();
+        let following = match PROFILES.may_load(deps.storage, following_user_name.clone())? {
+            Some(profile) if FOLLOWING.has(deps.storage, (&info.sender, &profile.addr)) => {
+                profile.addr
             }
-            None => return Err(ContractError::ProfileNotFound {}),
-        }
+            _ => return Err(ContractError::NotFollowing {}),
+        };
 
-        PROFILES.save(
-            deps.storage,
-            my_user_name.trim().to_lowercase(),
-            &my_profile,
-        )?;
-        PROFILES.save(
-            deps.storage,
-            following_user_name.trim().to_lowercase(),
-            &following_profile,
-        )?;
+        FOLLOWING.remove(deps.storage, (&info.sender, &following));
+        FOLLOWERS.remove(deps.storage, (&following, &info.sender));
+        update_follow_counts(deps.storage, &info.sender, |counts| counts.following -= 1)?;
+        update_follow_counts(deps.storage, &following, |counts| counts.followers -= 1)?;
 
         Ok(Response::new()
             .add_attribute("action", "follow")
@@ -246,13 +410,39 @@ pub mod execute {
         info: MessageInfo,
         title: String,
         content: String,
+        parent_id: Option<PostId>,
+        quote_of: Option<PostId>,
     ) -> Result<Response, ContractError> {
+        assert_not_banned(deps.storage, &info.sender)?;
+
         // Get post id.
         let post_id = POST_INDEX.load(deps.storage)?.current_index + 1;
 
         // Remove trailing space in title and content.
-        let title = title.trim().to_string();
-        let content = content.trim().to_string();
+        let limits = CONFIG.load(deps.storage)?.limits;
+        let title = validate_text(
+            &title,
+            limits.max_title_length,
+            false,
+            ContractError::TitleTooLong {},
+        )?;
+        let content = validate_text(
+            &content,
+            limits.max_content_length,
+            true,
+            ContractError::ContentTooLong {},
+        )?;
+
+        // Replies and quotes must point at a live post
+        if let Some(parent_id) = parent_id {
+            let mut parent = load_live_post(deps.storage, parent_id)?;
+            parent.replies += 1;
+            POSTS.save(deps.storage, parent_id, &parent)?;
+            REPLIES.save(deps.storage, (parent_id, post_id), &())?;
+        }
+        if let Some(quote_of) = quote_of {
+            load_live_post(deps.storage, quote_of)?;
+        }
 
         // Construct new post
         let new_post = Post {
@@ -261,8 +451,12 @@ pub mod execute {
             content,
             author: info.sender.clone(),
             likes: 0,
-            likers: vec![],
-            comments: vec![],
+            comments: 0,
+            hidden: false,
+            parent_id,
+            quote_of,
+            replies: 0,
+            deleted: false,
             created_at: env.block.time.seconds(),
             updated_at: env.block.time.seconds(),
         };
@@ -277,13 +471,8 @@ pub mod execute {
 
         // Add to post storage
         POSTS.save(deps.storage, post_id, &new_post)?;
-
-        // Add to user post storage
-        let mut posts = USER_POSTS
-            .may_load(deps.storage, info.sender.to_string())?
-            .unwrap_or_default();
-        posts.push(post_id);
-        USER_POSTS.save(deps.storage, info.sender.to_string(), &posts)?;
+        AUTHOR_POSTS.save(deps.storage, (&info.sender, post_id), &())?;
+        update_stats(deps.storage, |stats| stats.posts += 1)?;
 
         Ok(Response::new()
             .add_attribute("action", "create_post")
@@ -298,19 +487,31 @@ pub mod execute {
         title: Option<String>,
         content: Option<String>,
     ) -> Result<Response, ContractError> {
-        let mut post = POSTS.load(deps.storage, id)?;
+        assert_not_banned(deps.storage, &info.sender)?;
+        let mut post = load_live_post(deps.storage, id)?;
 
         // Only author can update
         if post.author != info.sender {
             return Err(ContractError::Unauthorized {});
         }
 
+        let limits = CONFIG.load(deps.storage)?.limits;
         if let Some(new_title) = title {
-            post.title = new_title.clone();
+            post.title = validate_text(
+                &new_title,
+                limits.max_title_length,
+                false,
+                ContractError::TitleTooLong {},
+            )?;
         }
 
         if let Some(new_content) = content {
-            post.content = new_content.clone();
+            post.content = validate_text(
+                &new_content,
+                limits.max_content_length,
+                true,
+                ContractError::ContentTooLong {},
+            )?;
         }
 
         post.updated_at = env.block.time.seconds();
@@ -323,32 +524,129 @@ pub mod execute {
     }
 
     pub fn like_post(deps: DepsMut, info: MessageInfo, id: u64) -> Result<Response, ContractError> {
-        let mut post = POSTS.load(deps.storage, id)?;
-        post.likes += 1;
-        post.likers.push(info.sender);
+        assert_not_banned(deps.storage, &info.sender)?;
+        let mut post = load_live_post(deps.storage, id)?;
+        if LIKES.has(deps.storage, (id, &info.sender)) {
+            return Err(ContractError::AlreadyLiked {});
+        }
 
+        LIKES.save(deps.storage, (id, &info.sender), &())?;
+        post.likes += 1;
         POSTS.save(deps.storage, id, &post)?;
+        update_stats(deps.storage, |stats| stats.likes += 1)?;
 
         Ok(Response::new()
             .add_attribute("action", "like_post")
             .add_attribute("post_id", id.to_string()))
     }
 
+    pub fn unlike_post(
+        deps: DepsMut,
+        info: MessageInfo,
+        id: u64,
+    ) -> Result<Response, ContractError> {
+        let mut post = load_live_post(deps.storage, id)?;
+        if !LIKES.has(deps.storage, (id, &info.sender)) {
+            return Err(ContractError::NotLiked {});
+        }
+
+        LIKES.remove(deps.storage, (id, &info.sender));
+        post.likes -= 1;
+        POSTS.save(deps.storage, id, &post)?;
+        update_stats(deps.storage, |stats| stats.likes -= 1)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "unlike_post")
+            .add_attribute("post_id", id.to_string()))
+    }
+
     pub fn comment_on_post(
         deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        id: u64,
+        comment: String,
+    ) -> Result<Response, ContractError> {
+        assert_not_banned(deps.storage, &info.sender)?;
+        let mut post = load_live_post(deps.storage, id)?;
+        let comment = validate_comment(deps.storage, &comment)?;
+        let comment_id = COMMENT_SEQ.may_load(deps.storage)?.unwrap_or_default() + 1;
+
+        let new_comment = Comment {
+            id: comment_id,
+            post_id: id,
+            author: info.sender,
+            content: comment,
+            created_at: env.block.time.seconds(),
+            updated_at: env.block.time.seconds(),
+        };
+
+        COMMENT_SEQ.save(deps.storage, &comment_id)?;
+        COMMENTS.save(deps.storage, (id, comment_id), &new_comment)?;
+        post.comments += 1;
+        POSTS.save(deps.storage, id, &post)?;
+        update_stats(deps.storage, |stats| stats.comments += 1)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "comment")
+            .add_attribute("post_id", id.to_string())
+            .add_attribute("comment_id", comment_id.to_string()))
+    }
+
+    pub fn edit_comment(
+        deps: DepsMut,
+        env: Env,
         info: MessageInfo,
         id: u64,
+        comment_id: u64,
         comment: String,
     ) -> Result<Response, ContractError> {
-        let mut post = POSTS.load(deps.storage, id)?;
+        assert_not_banned(deps.storage, &info.sender)?;
+        load_live_post(deps.storage, id)?;
+        let mut existing = COMMENTS
+            .may_load(deps.storage, (id, comment_id))?
+            .ok_or(ContractError::CommentNotFound {})?;
+
+        // Only author can edit
+        if existing.author != info.sender {
+            return Err(ContractError::Unauthorized {});
+        }
+
+        existing.content = validate_comment(deps.storage, &comment)?;
+        existing.updated_at = env.block.time.seconds();
+        COMMENTS.save(deps.storage, (id, comment_id), &existing)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "edit_comment")
+            .add_attribute("post_id", id.to_string())
+            .add_attribute("comment_id", comment_id.to_string()))
+    }
+
+    pub fn delete_comment(
+        deps: DepsMut,
+        info: MessageInfo,
+        id: u64,
+        comment_id: u64,
+    ) -> Result<Response, ContractError> {
+        let mut post = load_live_post(deps.storage, id)?;
+        let comment = COMMENTS
+            .may_load(deps.storage, (id, comment_id))?
+            .ok_or(ContractError::CommentNotFound {})?;
 
-        post.comments.push((info.sender, comment));
+        // Comment author or post author can delete
+        if comment.author != info.sender && post.author != info.sender {
+            return Err(ContractError::Unauthorized {});
+        }
 
+        COMMENTS.remove(deps.storage, (id, comment_id));
+        post.comments -= 1;
         POSTS.save(deps.storage, id, &post)?;
+        update_stats(deps.storage, |stats| stats.comments -= 1)?;
 
         Ok(Response::new()
-            .add_attribute("action", "like_post")
-            .add_attribute("post_id", id.to_string()))
+            .add_attribute("action", "delete_comment")
+            .add_attribute("post_id", id.to_string())
+            .add_attribute("comment_id", comment_id.to_string()))
     }
 
     pub fn delete_post(
@@ -356,249 +654,3295 @@ pub mod execute {
         info: MessageInfo,
         id: u64,
     ) -> Result<Response, ContractError> {
-        let post = POSTS.load(deps.storage, id)?;
+        let mut post = load_live_post(deps.storage, id)?;
 
         // Only author can delete
         if post.author != info.sender {
             return Err(ContractError::Unauthorized {});
         }
 
-        POSTS.remove(deps.storage, id);
+        // Likes and comments stay behind, clearing them could run out of gas on busy posts.
+        // Every handler and query reaching them checks the post first, which is now gone or
+        // a tombstone.
+        AUTHOR_POSTS.remove(deps.storage, (&info.sender, id));
+        update_stats(deps.storage, |stats| {
+            stats.posts -= 1;
+            stats.likes -= post.likes;
+            stats.comments -= post.comments;
+        })?;
+        if post.replies > 0 {
+            // Leave a tombstone so the replies stay in the thread
+            post.title.clear();
+            post.content.clear();
+            post.likes = 0;
+            post.comments = 0;
+            post.deleted = true;
+            POSTS.save(deps.storage, id, &post)?;
+        } else {
+            remove_post(deps.storage, post)?;
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "delete_post")
+            .add_attribute("post_id", id.to_string()))
+    }
 
-        let mut posts = USER_POSTS
-            .may_load(deps.storage, info.sender.to_string())?
-            .unwrap_or_default();
+    pub fn add_moderator(
+        deps: DepsMut,
+        info: MessageInfo,
+        address: String,
+    ) -> Result<Response, ContractError> {
+        assert_admin(deps.storage, &info.sender)?;
+        let moderator = deps.api.addr_validate(&address)?;
+        MODERATORS.save(deps.storage, &moderator, &())?;
 
-        let post_index = posts.iter().position(|post_id| post_id == &id);
+        Ok(Response::new()
+            .add_attribute("action", "add_moderator")
+            .add_attribute("moderator", moderator))
+    }
+
+    pub fn remove_moderator(
+        deps: DepsMut,
+        info: MessageInfo,
+        address: String,
+    ) -> Result<Response, ContractError> {
+        assert_admin(deps.storage, &info.sender)?;
+        let moderator = deps.api.addr_validate(&address)?;
+        MODERATORS.remove(deps.storage, &moderator);
 
-        match post_index {
-            Some(index) => {
-                posts.remove(index);
-                USER_POSTS.save(deps.storage, info.sender.to_string(), &posts)?;
+        Ok(Response::new()
+            .add_attribute("action", "remove_moderator")
+            .add_attribute("moderator", moderator))
+    }
 
-                Ok(Response::new()
-                    .add_attribute("action", "delete_post")
-                    .add_attribute("post_id", id.to_string()))
+    pub fn report(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        target: ReportTarget,
+        reason: String,
+    ) -> Result<Response, ContractError> {
+        match target {
+            ReportTarget::Post { id } => {
+                load_live_post(deps.storage, id)?;
+            }
+            ReportTarget::Comment { id, comment_id } => {
+                load_live_post(deps.storage, id)?;
+                if !COMMENTS.has(deps.storage, (id, comment_id)) {
+                    return Err(ContractError::CommentNotFound {});
+                }
             }
-            None => return Err(ContractError::PostNotFound {}),
         }
-    }
-}
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    match msg {
-        QueryMsg::CurrentIndex {} => to_json_binary(&query_index(deps)?),
-        QueryMsg::GetProfile { user_name } => to_json_binary(&query_profile(deps, user_name)?),
-        QueryMsg::GetPost { id } => to_json_binary(&query_post(deps, id)?),
-        QueryMsg::GetUserPosts { user } => to_json_binary(&query_user_posts(deps, user)?),
-    }
-}
+        let limits = CONFIG.load(deps.storage)?.limits;
+        let reason = validate_text(
+            &reason,
+            limits.max_reason_length,
+            true,
+            ContractError::ReasonTooLong {},
+        )?;
 
-pub mod query {
-    use crate::msg::{GetIndexResponse, GetPostResponse, GetProfileResponse, GetUserPostsResponse};
+        let (id, comment_id) = target.key();
+        if REPORTED.has(deps.storage, (&info.sender, id, comment_id)) {
+            return Err(ContractError::AlreadyReported {});
+        }
 
-    use super::*;
+        let report_id = REPORT_SEQ.may_load(deps.storage)?.unwrap_or_default() + 1;
+        let report = Report {
+            id: report_id,
+            target,
+            reporter: info.sender.clone(),
+            reason,
+            created_at: env.block.time.seconds(),
+        };
+        REPORT_SEQ.save(deps.storage, &report_id)?;
+        REPORTS.save(deps.storage, report_id, &report)?;
+        REPORTED.save(deps.storage, (&info.sender, id, comment_id), &report_id)?;
 
-    pub fn query_index(deps: Deps) -> StdResult<GetIndexResponse> {
-        let current_index = POST_INDEX.load(deps.storage)?.current_index;
+        Ok(Response::new()
+            .add_attribute("action", "report")
+            .add_attribute("report_id", report_id.to_string())
+            .add_attribute("post_id", id.to_string()))
+    }
 
-        Ok(GetIndexResponse { current_index })
+    pub fn resolve_report(
+        deps: DepsMut,
+        info: MessageInfo,
+        report_id: u64,
+    ) -> Result<Response, ContractError> {
+        assert_moderator(deps.storage, &info.sender)?;
+        let report = REPORTS
+            .may_load(deps.storage, report_id)?
+            .ok_or(ContractError::ReportNotFound {})?;
+
+        // The reporter may report the same target again
+        let (id, comment_id) = report.target.key();
+        REPORTS.remove(deps.storage, report_id);
+        REPORTED.remove(deps.storage, (&report.reporter, id, comment_id));
+
+        Ok(Response::new()
+            .add_attribute("action", "resolve_report")
+            .add_attribute("report_id", report_id.to_string()))
     }
 
-    pub fn query_profile(deps: Deps, user_name: String) -> StdResult<GetProfileResponse> {
-        let profile = PROFILES.load(deps.storage, user_name.trim().to_lowercase())?;
+    pub fn set_post_hidden(
+        deps: DepsMut,
+        info: MessageInfo,
+        id: u64,
+        hidden: bool,
+    ) -> Result<Response, ContractError> {
+        assert_moderator(deps.storage, &info.sender)?;
+        let mut post = load_live_post(deps.storage, id)?;
+        post.hidden = hidden;
+        POSTS.save(deps.storage, id, &post)?;
 
-        Ok(GetProfileResponse { profile })
+        let action = if hidden { "hide_post" } else { "unhide_post" };
+        Ok(Response::new()
+            .add_attribute("action", action)
+            .add_attribute("post_id", id.to_string()))
     }
 
-    pub fn query_post(deps: Deps, id: u64) -> StdResult<GetPostResponse> {
-        let post = POSTS.load(deps.storage, id)?;
+    pub fn set_banned(
+        deps: DepsMut,
+        info: MessageInfo,
+        address: String,
+        banned: bool,
+    ) -> Result<Response, ContractError> {
+        assert_moderator(deps.storage, &info.sender)?;
+        let user = deps.api.addr_validate(&address)?;
+        if banned {
+            BANNED.save(deps.storage, &user, &())?;
+        } else {
+            BANNED.remove(deps.storage, &user);
+        }
 
-        Ok(GetPostResponse { post })
+        let action = if banned { "ban_user" } else { "unban_user" };
+        Ok(Response::new()
+            .add_attribute("action", action)
+            .add_attribute("user", user))
     }
 
-    pub fn query_user_posts(deps: Deps, user: String) -> StdResult<GetUserPostsResponse> {
-        let state = USER_POSTS.load(deps.storage, user)?;
-        Ok(GetUserPostsResponse { posts: state })
+    pub fn tip_post(deps: DepsMut, info: MessageInfo, id: u64) -> Result<Response, ContractError> {
+        if info.funds.is_empty() {
+            return Err(ContractError::NoFunds {});
+        }
+        let tips = info
+            .funds
+            .into_iter()
+            .map(|coin| (Denom::Native(coin.denom), coin.amount))
+            .collect();
+        tip(deps, info.sender, id, tips)
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use crate::msg::{GetIndexResponse, GetPostResponse};
+    pub fn receive(
+        deps: DepsMut,
+        info: MessageInfo,
+        wrapper: Cw20ReceiveMsg,
+    ) -> Result<Response, ContractError> {
+        // The sender is the token contract, the tipper is who sent the tokens
+        let tipper = deps.api.addr_validate(&wrapper.sender)?;
+        match from_json(&wrapper.msg)? {
+            ReceiveMsg::TipPost { id } => tip(
+                deps,
+                tipper,
+                id,
+                vec![(Denom::Cw20(info.sender), wrapper.amount)],
+            ),
+        }
+    }
 
-    use super::*;
-    use cosmwasm_std::testing::{message_info, mock_dependencies, mock_env};
-    use cosmwasm_std::{coins, from_json, Addr};
-    use cw_storage_plus::KeyDeserialize;
+    // Forward tips to the post author and the fee to the admin, recording the totals
+    fn tip(
+        deps: DepsMut,
+        tipper: Addr,
+        id: u64,
+        tips: Vec<(Denom, Uint128)>,
+    ) -> Result<Response, ContractError> {
+        let post = load_live_post(deps.storage, id)?;
+        if post.author == tipper {
+            return Err(ContractError::CannotTipOwnPost {});
+        }
+        let config = CONFIG.load(deps.storage)?;
 
-    #[test]
-    fn proper_initialization() {
-        let mut deps = mock_dependencies();
+        let mut messages: Vec<CosmosMsg> = vec![];
+        let (mut author_coins, mut fee_coins) = (vec![], vec![]);
+        for (denom, amount) in tips {
+            if amount.is_zero() {
+                return Err(ContractError::InvalidZeroAmount {});
+            }
+            let fee = amount.mul_floor(config.tip_fee);
+            let net = amount - fee;
+            record_tip(deps.storage, &post, &denom, amount, net)?;
+
+            match denom {
+                Denom::Native(denom) => {
+                    if !net.is_zero() {
+                        author_coins.push(Coin::new(net, denom.clone()));
+                    }
+                    if !fee.is_zero() {
+                        fee_coins.push(Coin::new(fee, denom));
+                    }
+                }
+                Denom::Cw20(token) => {
+                    for (recipient, amount) in [(&post.author, net), (&config.admin, fee)] {
+                        if !amount.is_zero() {
+                            messages.push(cw20_transfer_msg(&token, recipient, amount)?);
+                        }
+                    }
+                }
+            }
+        }
+        for (recipient, coins) in [(&post.author, author_coins), (&config.admin, fee_coins)] {
+            if !coins.is_empty() {
+                messages.push(
+                    BankMsg::Send {
+                        to_address: recipient.to_string(),
+                        amount: coins,
+                    }
+                    .into(),
+                );
+            }
+        }
 
-        let msg = InstantiateMsg {};
-        let info = message_info(
-            &Addr::from_vec("creator".as_bytes().to_vec()).unwrap(),
-            &coins(1_000_000, "hackATOM"),
-        );
+        Ok(Response::new()
+            .add_messages(messages)
+            .add_attribute("action", "tip_post")
+            .add_attribute("post_id", id.to_string())
+            .add_attribute("tipper", tipper)
+            .add_attribute("author", post.author))
+    }
 
-        // we can just call .unwrap() to assert this was a success
-        let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
-        assert_eq!(0, res.messages.len());
+    fn record_tip(
+        storage: &mut dyn Storage,
+        post: &Post,
+        denom: &Denom,
+        amount: Uint128,
+        net: Uint128,
+    ) -> Result<(), ContractError> {
+        let key = denom_key(denom);
+
+        let previous = POST_TIPS
+            .may_load(storage, (post.id, &key))?
+            .map(|tips| tips.amount)
+            .unwrap_or_default();
+        let total = previous + amount;
+        POST_TIPS.save(
+            storage,
+            (post.id, &key),
+            &TokenAmount {
+                denom: denom.clone(),
+                amount: total,
+            },
+        )?;
+        TIP_RANKING.remove(storage, (&key, previous.u128(), post.id));
+        TIP_RANKING.save(storage, (&key, total.u128(), post.id), &())?;
 
-        // it worked, let's query the state
-        let res = query(deps.as_ref(), mock_env(), QueryMsg::CurrentIndex {}).unwrap();
-        let value: GetIndexResponse = from_json(&res).unwrap();
-        assert_eq!(0, value.current_index);
+        let earned = AUTHOR_EARNINGS
+            .may_load(storage, (&post.author, &key))?
+            .map(|earnings| earnings.amount)
+            .unwrap_or_default();
+        AUTHOR_EARNINGS.save(
+            storage,
+            (&post.author, &key),
+            &TokenAmount {
+                denom: denom.clone(),
+                amount: earned + net,
+            },
+        )?;
+        Ok(())
     }
 
-    #[test]
-    fn create_post() {
-        let mut deps = mock_dependencies();
+    fn cw20_transfer_msg(
+        token: &Addr,
+        recipient: &Addr,
+        amount: Uint128,
+    ) -> Result<CosmosMsg, ContractError> {
+        Ok(WasmMsg::Execute {
+            contract_addr: token.to_string(),
+            msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: recipient.to_string(),
+                amount,
+            })?,
+            funds: vec![],
+        }
+        .into())
+    }
 
-        // Instantiate contract
-        let msg = InstantiateMsg {};
-        let info = message_info(
-            &Addr::from_vec("creator".as_bytes().to_vec()).unwrap(),
-            &coins(1_000_000, "hackATOM"),
-        );
-        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+    fn assert_admin(storage: &dyn Storage, sender: &Addr) -> Result<Config, ContractError> {
+        let config = CONFIG.load(storage)?;
+        if config.admin != sender {
+            return Err(ContractError::Unauthorized {});
+        }
+        Ok(config)
+    }
 
-        // Create post
-        let info = message_info(
-            &Addr::from_vec("anyone".as_bytes().to_vec()).unwrap(),
-            &coins(10, "hackATOM"),
-        );
-        let title = "title".to_string();
+    // The admin moderates without being listed
+    fn assert_moderator(storage: &dyn Storage, sender: &Addr) -> Result<(), ContractError> {
+        if CONFIG.load(storage)?.admin != sender && !MODERATORS.has(storage, sender) {
+            return Err(ContractError::Unauthorized {});
+        }
+        Ok(())
+    }
+
+    // Tombstones only hold their thread together, they can't be acted on
+    fn load_live_post(storage: &dyn Storage, id: PostId) -> Result<Post, ContractError> {
+        match POSTS.may_load(storage, id)? {
+            Some(post) if !post.deleted => Ok(post),
+            _ => Err(ContractError::PostNotFound {}),
+        }
+    }
+
+    // Remove a post, along with the tombstones that were only kept for its sake
+    fn remove_post(storage: &mut dyn Storage, mut post: Post) -> StdResult<()> {
+        loop {
+            POSTS.remove(storage, post.id);
+            let Some(parent_id) = post.parent_id else {
+                return Ok(());
+            };
+            REPLIES.remove(storage, (parent_id, post.id));
+            let mut parent = POSTS.load(storage, parent_id)?;
+            parent.replies -= 1;
+            if !parent.deleted || parent.replies > 0 {
+                return POSTS.save(storage, parent_id, &parent);
+            }
+            post = parent;
+        }
+    }
+
+    fn assert_not_banned(storage: &dyn Storage, sender: &Addr) -> Result<(), ContractError> {
+        if BANNED.has(storage, sender) {
+            return Err(ContractError::Banned {});
+        }
+        Ok(())
+    }
+
+    // Store a follow edge in both directions and bump the counts
+    pub(crate) fn add_follow(
+        storage: &mut dyn Storage,
+        follower: &Addr,
+        following: &Addr,
+        time: Timestamp,
+    ) -> Result<(), ContractError> {
+        FOLLOWING.save(storage, (follower, following), &time)?;
+        FOLLOWERS.save(storage, (following, follower), &time)?;
+        update_follow_counts(storage, follower, |counts| counts.following += 1)?;
+        update_follow_counts(storage, following, |counts| counts.followers += 1)?;
+        Ok(())
+    }
+
+    fn update_follow_counts(
+        storage: &mut dyn Storage,
+        addr: &Addr,
+        update: impl FnOnce(&mut FollowCounts),
+    ) -> Result<(), ContractError> {
+        let mut counts = FOLLOW_COUNTS.may_load(storage, addr)?.unwrap_or_default();
+        update(&mut counts);
+        FOLLOW_COUNTS.save(storage, addr, &counts)?;
+        Ok(())
+    }
+
+    fn update_stats(
+        storage: &mut dyn Storage,
+        update: impl FnOnce(&mut Stats),
+    ) -> Result<(), ContractError> {
+        let mut stats = STATS.load(storage)?;
+        update(&mut stats);
+        STATS.save(storage, &stats)?;
+        Ok(())
+    }
+
+    // Trimmed text, if it fits in `max_length` bytes and has no control characters.
+    // Multiline text may contain line breaks and tabs.
+    fn validate_text(
+        text: &str,
+        max_length: u32,
+        multiline: bool,
+        too_long: ContractError,
+    ) -> Result<String, ContractError> {
+        let text = text.trim();
+        if text.len() > max_length as usize {
+            return Err(too_long);
+        }
+        if text
+            .chars()
+            .any(|c| c.is_control() && !(multiline && matches!(c, '\n' | '\r' | '\t')))
+        {
+            return Err(ContractError::ControlCharacter {});
+        }
+        Ok(text.to_string())
+    }
+
+    fn validate_comment(storage: &dyn Storage, comment: &str) -> Result<String, ContractError> {
+        let limits = CONFIG.load(storage)?.limits;
+        validate_text(
+            comment,
+            limits.max_comment_length,
+            true,
+            ContractError::CommentTooLong {},
+        )
+    }
+
+    // Image URLs may be empty, otherwise they need an allowed scheme
+    fn validate_image_url(image_url: &str, limits: &Limits) -> Result<String, ContractError> {
+        let image_url = validate_text(
+            image_url,
+            limits.max_image_url_length,
+            false,
+            ContractError::ImageUrlTooLong {},
+        )?;
+        if !image_url.is_empty()
+            && !IMAGE_URL_SCHEMES
+                .iter()
+                .any(|scheme| image_url.starts_with(scheme))
+        {
+            return Err(ContractError::InvalidImageUrl {});
+        }
+        Ok(image_url)
+    }
+
+    // Normalized username, if it follows the length and charset policy
+    fn validate_user_name(user_name: &str) -> Result<Username, ContractError> {
+        let user_name = user_name.trim().to_lowercase();
+        if !(MIN_USERNAME_LENGTH..=MAX_USERNAME_LENGTH).contains(&user_name.len()) {
+            return Err(ContractError::InvalidUsernameLength {});
+        }
+        if !user_name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+        {
+            return Err(ContractError::InvalidUsernameCharacter {});
+        }
+        Ok(user_name)
+    }
+
+    // Check that `sender` may take `user_name`, releasing a reservation they hold on it
+    fn claim_user_name(
+        storage: &mut dyn Storage,
+        env: &Env,
+        sender: &Addr,
+        user_name: &str,
+    ) -> Result<(), ContractError> {
+        if PROFILES.has(storage, user_name.to_string()) {
+            return Err(ContractError::UsernameTaken {});
+        }
+        if let Some(reservation) = RESERVED_USERNAMES.may_load(storage, user_name.to_string())? {
+            if reservation.addr != sender && reservation.expires_at > env.block.time {
+                return Err(ContractError::UsernameReserved {});
+            }
+            RESERVED_USERNAMES.remove(storage, user_name.to_string());
+        }
+        Ok(())
+    }
+
+    // Username of the sender's profile
+    fn sender_user_name(storage: &dyn Storage, sender: &Addr) -> Result<Username, ContractError> {
+        ADDR_TO_USERNAME
+            .may_load(storage, sender)?
+            .ok_or(ContractError::ProfileNotFound {})
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(mut deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    let stored = get_contract_version(deps.storage)?;
+    if stored.contract != CONTRACT_NAME {
+        return Err(ContractError::InvalidMigrationContract {});
+    }
+    let from = parse_version(&stored.version)?;
+    let to = parse_version(CONTRACT_VERSION)?;
+    if from > to {
+        return Err(ContractError::MigrationDowngrade {});
+    }
+
+    // Run every storage migration newer than the stored version, oldest first
+    let mut response = Response::new()
+        .add_attribute("action", "migrate")
+        .add_attribute("from_version", from.to_string())
+        .add_attribute("to_version", to.to_string());
+    if from < semver::Version::new(0, 2, 0) {
+        let attributes = migrations::v0_2_0::migrate(deps.branch(), &env, msg.admin)?;
+        response = response.add_attributes(attributes);
+    }
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    Ok(response)
+}
+
+fn parse_version(version: &str) -> Result<semver::Version, ContractError> {
+    version
+        .parse()
+        .map_err(|_| ContractError::InvalidVersion {})
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::CurrentIndex {} => to_json_binary(&query_index(deps)?),
+        QueryMsg::Config {} => to_json_binary(&query_config(deps)?),
+        QueryMsg::Stats {} => to_json_binary(&query_stats(deps)?),
+        QueryMsg::PostTips { id } => to_json_binary(&query_post_tips(deps, id)?),
+        QueryMsg::TopTippedPosts { denom, limit } => {
+            to_json_binary(&query_top_tipped_posts(deps, denom, limit)?)
+        }
+        QueryMsg::AuthorEarnings { address } => {
+            to_json_binary(&query_author_earnings(deps, address)?)
+        }
+        QueryMsg::Moderators { start_after, limit } => {
+            to_json_binary(&query_moderators(deps, start_after, limit)?)
+        }
+        QueryMsg::IsBanned { address } => to_json_binary(&query_is_banned(deps, address)?),
+        QueryMsg::Reports { start_after, limit } => {
+            to_json_binary(&query_reports(deps, start_after, limit)?)
+        }
+        QueryMsg::GetProfile { user_name } => to_json_binary(&query_profile(deps, user_name)?),
+        QueryMsg::GetPost { id } => to_json_binary(&query_post(deps, id)?),
+        QueryMsg::GetUserPosts {
+            user,
+            start_after,
+            limit,
+        } => to_json_binary(&query_user_posts(deps, user, start_after, limit)?),
+        QueryMsg::UsernameHistory {
+            user_name,
+            start_after,
+            limit,
+        } => to_json_binary(&query_username_history(
+            deps,
+            user_name,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::Followers {
+            user_name,
+            start_after,
+            limit,
+        } => to_json_binary(&query_followers(deps, user_name, start_after, limit)?),
+        QueryMsg::Following {
+            user_name,
+            start_after,
+            limit,
+        } => to_json_binary(&query_following(deps, user_name, start_after, limit)?),
+        QueryMsg::IsFollowing {
+            follower,
+            following,
+        } => to_json_binary(&query_is_following(deps, follower, following)?),
+        QueryMsg::Comments {
+            id,
+            start_after,
+            limit,
+        } => to_json_binary(&query_comments(deps, id, start_after, limit)?),
+        QueryMsg::Feed {
+            user_name,
+            start_after,
+            limit,
+        } => to_json_binary(&query_feed(deps, user_name, start_after, limit)?),
+        QueryMsg::GlobalFeed { start_after, limit } => {
+            to_json_binary(&query_global_feed(deps, start_after, limit)?)
+        }
+        QueryMsg::Likers {
+            id,
+            start_after,
+            limit,
+        } => to_json_binary(&query_likers(deps, id, start_after, limit)?),
+        QueryMsg::Thread {
+            root_id,
+            depth,
+            limit,
+        } => to_json_binary(&query_thread(deps, root_id, depth, limit)?),
+    }
+}
+
+pub mod query {
+    use std::collections::{BTreeMap, BinaryHeap};
+
+    use cosmwasm_std::{Addr, StdError, Uint128};
+    use cw_storage_plus::{Bound, Map};
+
+    use crate::msg::{
+        CommentsResponse, ConfigResponse, FeedResponse, Follow, FollowersResponse,
+        FollowingResponse, GetIndexResponse, GetPostResponse, GetProfileResponse,
+        GetUserPostsResponse, IsBannedResponse, IsFollowingResponse, LikersResponse,
+        ModeratorsResponse, ReportsResponse, StatsResponse, ThreadNode, ThreadResponse, TippedPost,
+        TipsResponse, TopTippedPostsResponse, UsernameHistoryResponse,
+    };
+    use crate::state::CommentId;
+    use cw20::Denom;
+
+    use super::*;
+
+    const DEFAULT_LIMIT: u32 = 10;
+    const MAX_LIMIT: u32 = 30;
+    const DEFAULT_DEPTH: u32 = 3;
+    const MAX_DEPTH: u32 = 10;
+    const MAX_FEED_AUTHORS: usize = 100;
+
+    pub fn query_index(deps: Deps) -> StdResult<GetIndexResponse> {
+        let current_index = POST_INDEX.load(deps.storage)?.current_index;
+
+        Ok(GetIndexResponse { current_index })
+    }
+
+    pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+        let config = CONFIG.load(deps.storage)?;
+
+        Ok(ConfigResponse { config })
+    }
+
+    pub fn query_moderators(
+        deps: Deps,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<ModeratorsResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        // Only used as a cursor, it doesn't need to be a valid address
+        let start_after = start_after.map(Addr::unchecked);
+
+        let moderators = MODERATORS
+            .keys(
+                deps.storage,
+                start_after.as_ref().map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .take(limit)
+            .collect::<StdResult<_>>()?;
+        Ok(ModeratorsResponse { moderators })
+    }
+
+    pub fn query_is_banned(deps: Deps, address: String) -> StdResult<IsBannedResponse> {
+        let address = deps.api.addr_validate(&address)?;
+        let is_banned = BANNED.has(deps.storage, &address);
+
+        Ok(IsBannedResponse { is_banned })
+    }
+
+    pub fn query_reports(
+        deps: Deps,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<ReportsResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+        let reports = REPORTS
+            .range(
+                deps.storage,
+                start_after.map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .take(limit)
+            .map(|item| item.map(|(_, report)| report))
+            .collect::<StdResult<_>>()?;
+        Ok(ReportsResponse { reports })
+    }
+
+    pub fn query_post_tips(deps: Deps, id: u64) -> StdResult<TipsResponse> {
+        let tips = POST_TIPS
+            .prefix(id)
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| item.map(|(_, tips)| tips))
+            .collect::<StdResult<_>>()?;
+
+        Ok(TipsResponse { tips })
+    }
+
+    // Deleted and hidden posts are skipped
+    pub fn query_top_tipped_posts(
+        deps: Deps,
+        denom: Denom,
+        limit: Option<u32>,
+    ) -> StdResult<TopTippedPostsResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+        let mut posts = vec![];
+        for item in TIP_RANKING.sub_prefix(&denom_key(&denom)).keys(
+            deps.storage,
+            None,
+            None,
+            Order::Descending,
+        ) {
+            let (amount, id) = item?;
+            match POSTS.may_load(deps.storage, id)? {
+                Some(post) if !post.hidden && !post.deleted => posts.push(TippedPost {
+                    id,
+                    author: post.author,
+                    amount: Uint128::new(amount),
+                }),
+                _ => continue,
+            }
+            if posts.len() == limit {
+                break;
+            }
+        }
+
+        Ok(TopTippedPostsResponse { posts })
+    }
+
+    pub fn query_author_earnings(deps: Deps, address: String) -> StdResult<TipsResponse> {
+        let author = deps.api.addr_validate(&address)?;
+        let tips = AUTHOR_EARNINGS
+            .prefix(&author)
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| item.map(|(_, earnings)| earnings))
+            .collect::<StdResult<_>>()?;
+
+        Ok(TipsResponse { tips })
+    }
+
+    pub fn query_stats(deps: Deps) -> StdResult<StatsResponse> {
+        let stats = STATS.load(deps.storage)?;
+
+        Ok(StatsResponse { stats })
+    }
+
+    pub fn query_profile(deps: Deps, user_name: String) -> StdResult<GetProfileResponse> {
+        let user_name = user_name.trim().to_lowercase();
+        let profile = PROFILES.load(deps.storage, user_name)?;
+        let counts = FOLLOW_COUNTS
+            .may_load(deps.storage, &profile.addr)?
+            .unwrap_or_default();
+
+        Ok(GetProfileResponse {
+            profile,
+            followers: counts.followers,
+            following: counts.following,
+        })
+    }
+
+    pub fn query_post(deps: Deps, id: u64) -> StdResult<GetPostResponse> {
+        let post = POSTS.load(deps.storage, id)?;
+
+        Ok(GetPostResponse { post })
+    }
+
+    pub fn query_user_posts(
+        deps: Deps,
+        user: String,
+        start_after: Option<PostId>,
+        limit: Option<u32>,
+    ) -> StdResult<GetUserPostsResponse> {
+        let author = deps.api.addr_validate(&user)?;
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+        let posts = AUTHOR_POSTS
+            .prefix(&author)
+            .keys(
+                deps.storage,
+                start_after.map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .take(limit)
+            .collect::<StdResult<_>>()?;
+        Ok(GetUserPostsResponse { posts })
+    }
+
+    pub fn query_username_history(
+        deps: Deps,
+        user_name: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<UsernameHistoryResponse> {
+        let profile = PROFILES.load(deps.storage, user_name.trim().to_lowercase())?;
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+        let changes = USERNAME_HISTORY
+            .prefix(&profile.addr)
+            .range(
+                deps.storage,
+                start_after.map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .take(limit)
+            .map(|item| item.map(|(_, change)| change))
+            .collect::<StdResult<_>>()?;
+        Ok(UsernameHistoryResponse { changes })
+    }
+
+    pub fn query_followers(
+        deps: Deps,
+        user_name: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<FollowersResponse> {
+        let followers = follows(deps, FOLLOWERS, user_name, start_after, limit)?;
+        Ok(FollowersResponse { followers })
+    }
+
+    pub fn query_following(
+        deps: Deps,
+        user_name: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<FollowingResponse> {
+        let following = follows(deps, FOLLOWING, user_name, start_after, limit)?;
+        Ok(FollowingResponse { following })
+    }
+
+    pub fn query_is_following(
+        deps: Deps,
+        follower: String,
+        following: String,
+    ) -> StdResult<IsFollowingResponse> {
+        let since = match (
+            profile_addr(deps, &follower)?,
+            profile_addr(deps, &following)?,
+        ) {
+            (Some(follower), Some(following)) => {
+                FOLLOWING.may_load(deps.storage, (&follower, &following))?
+            }
+            _ => None,
+        };
+        Ok(IsFollowingResponse {
+            is_following: since.is_some(),
+            since,
+        })
+    }
+
+    pub fn query_comments(
+        deps: Deps,
+        id: u64,
+        start_after: Option<CommentId>,
+        limit: Option<u32>,
+    ) -> StdResult<CommentsResponse> {
+        load_listed_post(deps, id)?;
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+        let comments = COMMENTS
+            .prefix(id)
+            .range(
+                deps.storage,
+                start_after.map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .take(limit)
+            .map(|item| item.map(|(_, comment)| comment))
+            .collect::<StdResult<_>>()?;
+        Ok(CommentsResponse { comments })
+    }
+
+    pub fn query_feed(
+        deps: Deps,
+        user_name: String,
+        start_after: Option<PostId>,
+        limit: Option<u32>,
+    ) -> StdResult<FeedResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let Some(user) = profile_addr(deps, &user_name)? else {
+            return Ok(FeedResponse {
+                posts: vec![],
+                truncated: false,
+            });
+        };
+
+        // Merge the newest-first post ids of each followed author, keeping every author's
+        // next id in a max-heap so only the posts on the page are loaded
+        let mut authors = vec![];
+        let mut heads = BinaryHeap::new();
+        let mut truncated = false;
+        for author in FOLLOWING
+            .prefix(&user)
+            .keys(deps.storage, None, None, Order::Ascending)
+        {
+            if authors.len() == MAX_FEED_AUTHORS {
+                truncated = true;
+                break;
+            }
+            let author = author?;
+            let mut ids = AUTHOR_POSTS.prefix(&author).keys(
+                deps.storage,
+                None,
+                start_after.map(Bound::exclusive),
+                Order::Descending,
+            );
+            if let Some(id) = ids.next() {
+                heads.push((id?, authors.len()));
+            }
+            authors.push(ids);
+        }
+
+        let mut posts = vec![];
+        while posts.len() < limit {
+            let Some((id, author)) = heads.pop() else {
+                break;
+            };
+            if let Some(next) = authors[author].next() {
+                heads.push((next?, author));
+            }
+            let post = POSTS.load(deps.storage, id)?;
+            if !post.hidden {
+                posts.push(post);
+            }
+        }
+
+        Ok(FeedResponse { posts, truncated })
+    }
+
+    pub fn query_global_feed(
+        deps: Deps,
+        start_after: Option<PostId>,
+        limit: Option<u32>,
+    ) -> StdResult<FeedResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+        let posts = POSTS
+            .range(
+                deps.storage,
+                None,
+                start_after.map(Bound::exclusive),
+                Order::Descending,
+            )
+            .filter(|item| !matches!(item, Ok((_, post)) if post.hidden || post.deleted))
+            .take(limit)
+            .map(|item| item.map(|(_, post)| post))
+            .collect::<StdResult<_>>()?;
+        Ok(FeedResponse {
+            posts,
+            truncated: false,
+        })
+    }
+
+    pub fn query_thread(
+        deps: Deps,
+        root_id: u64,
+        depth: Option<u32>,
+        limit: Option<u32>,
+    ) -> StdResult<ThreadResponse> {
+        let root = POSTS.load(deps.storage, root_id)?;
+        let depth = depth.unwrap_or(DEFAULT_DEPTH).min(MAX_DEPTH);
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+        // Walk the thread breadth first so a small limit still shows the top levels
+        let mut levels: Vec<Vec<(PostId, Post)>> = vec![];
+        let mut parents = vec![root_id];
+        let mut count = 0;
+        'levels: for _ in 0..depth {
+            let mut level = vec![];
+            for parent in parents {
+                for id in REPLIES
+                    .prefix(parent)
+                    .keys(deps.storage, None, None, Order::Ascending)
+                {
+                    if count == limit {
+                        levels.push(level);
+                        break 'levels;
+                    }
+                    let reply = POSTS.load(deps.storage, id?)?;
+                    // Hidden replies are left out along with their own replies
+                    if reply.hidden {
+                        continue;
+                    }
+                    level.push((parent, reply));
+                    count += 1;
+                }
+            }
+            parents = level.iter().map(|(_, reply)| reply.id).collect();
+            levels.push(level);
+        }
+
+        // Assemble the tree from the deepest level up
+        let mut children: BTreeMap<PostId, Vec<ThreadNode>> = BTreeMap::new();
+        for level in levels.into_iter().rev() {
+            for (parent, post) in level {
+                let node = ThreadNode {
+                    children: children.remove(&post.id).unwrap_or_default(),
+                    post,
+                };
+                children.entry(parent).or_default().push(node);
+            }
+        }
+        let thread = ThreadNode {
+            post: root,
+            children: children.remove(&root_id).unwrap_or_default(),
+        };
+
+        Ok(ThreadResponse { thread })
+    }
+
+    pub fn query_likers(
+        deps: Deps,
+        id: u64,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<LikersResponse> {
+        load_listed_post(deps, id)?;
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        // Only used as a cursor, it doesn't need to be a valid address
+        let start_after = start_after.map(Addr::unchecked);
+
+        let likers = LIKES
+            .prefix(id)
+            .keys(
+                deps.storage,
+                start_after.as_ref().map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .take(limit)
+            .collect::<StdResult<_>>()?;
+        Ok(LikersResponse { likers })
+    }
+
+    // Comments and likers of a deleted post stay in storage but are no longer listed
+    fn load_listed_post(deps: Deps, id: PostId) -> StdResult<Post> {
+        match POSTS.load(deps.storage, id)? {
+            post if post.deleted => Err(StdError::not_found("post")),
+            post => Ok(post),
+        }
+    }
+
+    // Address of the profile named `user_name`, if there is one
+    fn profile_addr(deps: Deps, user_name: &str) -> StdResult<Option<Addr>> {
+        let profile = PROFILES.may_load(deps.storage, user_name.trim().to_lowercase())?;
+        Ok(profile.map(|profile| profile.addr))
+    }
+
+    // Page of the edges stored under `user_name`'s address in one of the follow maps
+    fn follows(
+        deps: Deps,
+        edges: Map<(&Addr, &Addr), Timestamp>,
+        user_name: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<Follow>> {
+        let Some(user) = profile_addr(deps, &user_name)? else {
+            return Ok(vec![]);
+        };
+        // Only used as a cursor, it doesn't need to be a valid address
+        let start_after = start_after.map(Addr::unchecked);
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+        edges
+            .prefix(&user)
+            .range(
+                deps.storage,
+                start_after.as_ref().map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .take(limit)
+            .map(|item| {
+                let (addr, since) = item?;
+                let user_name = ADDR_TO_USERNAME.load(deps.storage, &addr)?;
+                Ok(Follow {
+                    addr,
+                    user_name,
+                    since,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::msg::{
+        CommentsResponse, ConfigResponse, FeedResponse, FollowersResponse, FollowingResponse,
+        GetIndexResponse, GetPostResponse, GetProfileResponse, GetUserPostsResponse,
+        IsBannedResponse, IsFollowingResponse, LikersResponse, ReceiveMsg, ReportsResponse,
+        StatsResponse, ThreadNode, ThreadResponse, TipsResponse, TopTippedPostsResponse,
+        UsernameHistoryResponse,
+    };
+    use crate::state::MAX_TEXT_LENGTH;
+
+    use super::*;
+    use cosmwasm_std::testing::{
+        message_info, mock_dependencies, mock_env, MockApi, MockQuerier, MockStorage,
+    };
+    use cosmwasm_std::{
+        coin, coins, from_json, Addr, BankMsg, CosmosMsg, OwnedDeps, Uint128, WasmMsg,
+    };
+    use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, Denom};
+    use cw_storage_plus::KeyDeserialize;
+
+    fn addr(name: &str) -> Addr {
+        Addr::from_vec(name.as_bytes().to_vec()).unwrap()
+    }
+
+    // Instantiate the contract and create a profile for each (address, user name) pair
+    fn setup_profiles(mut deps: DepsMut, profiles: &[(&str, &str)]) {
+        let info = message_info(&addr("creator"), &[]);
+        instantiate(
+            deps.branch(),
+            mock_env(),
+            info,
+            InstantiateMsg {
+                admin: None,
+                limits: None,
+                tip_fee: None,
+            },
+        )
+        .unwrap();
+
+        for (sender, user_name) in profiles {
+            let msg = ExecuteMsg::CreateProfile {
+                user_name: user_name.to_string(),
+                about: "about".to_string(),
+                image_url: "ipfs://image".to_string(),
+            };
+            execute(
+                deps.branch(),
+                mock_env(),
+                message_info(&addr(sender), &[]),
+                msg,
+            )
+            .unwrap();
+        }
+    }
+
+    fn load_profile(deps: Deps, user_name: &str) -> GetProfileResponse {
+        let res = query(
+            deps,
+            mock_env(),
+            QueryMsg::GetProfile {
+                user_name: user_name.to_string(),
+            },
+        )
+        .unwrap();
+        from_json(&res).unwrap()
+    }
+
+    fn is_following(deps: Deps, follower: &str, following: &str) -> bool {
+        let msg = QueryMsg::IsFollowing {
+            follower: follower.to_string(),
+            following: following.to_string(),
+        };
+        let res: IsFollowingResponse = from_json(query(deps, mock_env(), msg).unwrap()).unwrap();
+        res.is_following
+    }
+
+    // Create a post as `sender` and return its id
+    fn create_post_as(deps: DepsMut, sender: &str) -> u64 {
+        let msg = ExecuteMsg::CreatePost {
+            title: "title".to_string(),
+            content: "content".to_string(),
+            parent_id: None,
+            quote_of: None,
+        };
+        let res = execute(deps, mock_env(), message_info(&addr(sender), &[]), msg).unwrap();
+        res.attributes[1].value.parse().unwrap()
+    }
+
+    fn reply_as(deps: DepsMut, sender: &str, parent_id: u64) -> u64 {
+        let msg = ExecuteMsg::CreatePost {
+            title: "re".to_string(),
+            content: "reply".to_string(),
+            parent_id: Some(parent_id),
+            quote_of: None,
+        };
+        let res = execute(deps, mock_env(), message_info(&addr(sender), &[]), msg).unwrap();
+        res.attributes[1].value.parse().unwrap()
+    }
+
+    // Renders a thread as "1(2(4),3)"
+    fn thread_shape(deps: Deps, root_id: u64, depth: Option<u32>, limit: Option<u32>) -> String {
+        fn shape(node: &ThreadNode) -> String {
+            if node.children.is_empty() {
+                return node.post.id.to_string();
+            }
+            let children = node.children.iter().map(shape).collect::<Vec<_>>();
+            format!("{}({})", node.post.id, children.join(","))
+        }
+        let msg = QueryMsg::Thread {
+            root_id,
+            depth,
+            limit,
+        };
+        let res = query(deps, mock_env(), msg).unwrap();
+        shape(&from_json::<ThreadResponse>(&res).unwrap().thread)
+    }
+
+    fn feed_ids(deps: Deps, msg: QueryMsg) -> Vec<u64> {
+        let res: FeedResponse = from_json(query(deps, mock_env(), msg).unwrap()).unwrap();
+        res.posts.iter().map(|post| post.id).collect()
+    }
+
+    fn load_post(deps: Deps, id: u64) -> Post {
+        let res = query(deps, mock_env(), QueryMsg::GetPost { id }).unwrap();
+        from_json::<GetPostResponse>(&res).unwrap().post
+    }
+
+    fn follow_as(deps: DepsMut, sender: &str, following_user_name: &str) {
+        let msg = ExecuteMsg::Follow {
+            following_user_name: following_user_name.to_string(),
+        };
+        execute(deps, mock_env(), message_info(&addr(sender), &[]), msg).unwrap();
+    }
+
+    // Profiles for alice and bob, with bob following alice
+    fn setup_follow_feed(deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>) {
+        for user_name in ["alice", "bob"] {
+            let msg = ExecuteMsg::CreateProfile {
+                user_name: user_name.to_string(),
+                about: "about".to_string(),
+                image_url: "".to_string(),
+            };
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                message_info(&addr(user_name), &[]),
+                msg,
+            )
+            .unwrap();
+        }
+        follow_as(deps.as_mut(), "bob", "alice");
+    }
+
+    #[test]
+    fn proper_initialization() {
+        let mut deps = mock_dependencies();
+
+        let msg = InstantiateMsg {
+            admin: None,
+            limits: None,
+            tip_fee: None,
+        };
+        let info = message_info(
+            &Addr::from_vec("creator".as_bytes().to_vec()).unwrap(),
+            &coins(1_000_000, "hackATOM"),
+        );
+
+        // we can just call .unwrap() to assert this was a success
+        let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(0, res.messages.len());
+
+        // it worked, let's query the state
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::CurrentIndex {}).unwrap();
+        let value: GetIndexResponse = from_json(&res).unwrap();
+        assert_eq!(0, value.current_index);
+    }
+
+    #[test]
+    fn create_post() {
+        let mut deps = mock_dependencies();
+
+        // Instantiate contract
+        let msg = InstantiateMsg {
+            admin: None,
+            limits: None,
+            tip_fee: None,
+        };
+        let info = message_info(
+            &Addr::from_vec("creator".as_bytes().to_vec()).unwrap(),
+            &coins(1_000_000, "hackATOM"),
+        );
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // Create post
+        let info = message_info(
+            &Addr::from_vec("anyone".as_bytes().to_vec()).unwrap(),
+            &coins(10, "hackATOM"),
+        );
+        let title = "title".to_string();
         let content = "content".to_string();
         let author = Addr::from_vec("anyone".as_bytes().to_vec()).unwrap();
         let msg = ExecuteMsg::CreatePost {
             title: title.clone(),
             content: content.clone(),
+            parent_id: None,
+            quote_of: None,
+        };
+        let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // Get update current index.
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::CurrentIndex {}).unwrap();
+        let value: GetIndexResponse = from_json(&res).unwrap();
+        let new_index = value.current_index;
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetPost { id: 1 }).unwrap();
+        let value: GetPostResponse = from_json(&res).unwrap();
+
+        // Should be assigned current index
+        assert_eq!(new_index, value.post.id);
+
+        // Check title
+        assert_eq!(title, value.post.title);
+
+        // Check content
+        assert_eq!(content, value.post.content);
+
+        // Check author
+        assert_eq!(author, value.post.author);
+    }
+
+    #[test]
+    fn update_post() {
+        let mut deps = mock_dependencies();
+
+        // Instantiate contract
+        let msg = InstantiateMsg {
+            admin: None,
+            limits: None,
+            tip_fee: None,
+        };
+        let info = message_info(
+            &Addr::from_vec("creator".as_bytes().to_vec()).unwrap(),
+            &coins(1_000_000, "hackATOM"),
+        );
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let info = message_info(
+            &Addr::from_vec("anyone".as_bytes().to_vec()).unwrap(),
+            &coins(10, "hackATOM"),
+        );
+        let title = "title".to_string();
+        let content = "content".to_string();
+
+        let msg = ExecuteMsg::CreatePost {
+            title: title.clone(),
+            content: content.clone(),
+            parent_id: None,
+            quote_of: None,
+        };
+        let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // Get update current index.
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::CurrentIndex {}).unwrap();
+        let value: GetIndexResponse = from_json(&res).unwrap();
+        let new_index = value.current_index;
+
+        // Update post
+        let info = message_info(
+            &Addr::from_vec("anyone".as_bytes().to_vec()).unwrap(),
+            &coins(10, "hackATOM"),
+        );
+        let another_title = "another title".to_string();
+        let another_content = "another content".to_string();
+
+        let msg = ExecuteMsg::UpdatePost {
+            id: new_index,
+            title: another_title.clone(),
+            content: another_content.clone(),
+        };
+        let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // Check updates
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetPost { id: 1 }).unwrap();
+        let value: GetPostResponse = from_json(&res).unwrap();
+
+        // Check title
+        assert_eq!(another_title, value.post.title);
+
+        // Check content
+        assert_eq!(another_content, value.post.content);
+    }
+
+    #[test]
+    fn delete_post() {
+        let mut deps = mock_dependencies();
+
+        // Instantiate contract
+        let msg = InstantiateMsg {
+            admin: None,
+            limits: None,
+            tip_fee: None,
+        };
+        let info = message_info(
+            &Addr::from_vec("creator".as_bytes().to_vec()).unwrap(),
+            &coins(1_000_000, "hackATOM"),
+        );
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let info = message_info(
+            &Addr::from_vec("anyone".as_bytes().to_vec()).unwrap(),
+            &coins(10, "hackATOM"),
+        );
+        let title = "title".to_string();
+        let content = "content".to_string();
+
+        let msg = ExecuteMsg::CreatePost {
+            title: title.clone(),
+            content: content.clone(),
+            parent_id: None,
+            quote_of: None,
+        };
+        let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // Get update current index.
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::CurrentIndex {}).unwrap();
+        let value: GetIndexResponse = from_json(&res).unwrap();
+        let new_index = value.current_index;
+
+        // Delete post
+        let info = message_info(
+            &Addr::from_vec("anyone".as_bytes().to_vec()).unwrap(),
+            &coins(10, "hackATOM"),
+        );
+
+        let msg = ExecuteMsg::DeletePost { id: new_index };
+        let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // Check updates
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetPost { id: 1 });
+
+        // Check it's deleted
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn create_profile() {
+        let mut deps = mock_dependencies();
+
+        // Instantiate contract
+        let msg = InstantiateMsg {
+            admin: None,
+            limits: None,
+            tip_fee: None,
+        };
+        let info = message_info(&addr("creator"), &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // Create profile
+        let msg = ExecuteMsg::CreateProfile {
+            user_name: " Alice ".to_string(),
+            about: " about alice ".to_string(),
+            image_url: "ipfs://image".to_string(),
+        };
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&addr("alice"), &[]),
+            msg,
+        )
+        .unwrap();
+        assert_eq!(res.attributes[0].value, "create_profile");
+
+        // Usernames are looked up case insensitively
+        let value = load_profile(deps.as_ref(), "ALICE");
+        assert_eq!(value.profile.id, 1);
+        assert_eq!(value.profile.addr, addr("alice"));
+        assert_eq!(value.profile.user_name, "alice");
+        assert_eq!(value.profile.about, "about alice");
+        assert_eq!(value.profile.image, "ipfs://image");
+        assert_eq!(value.profile.created_at, mock_env().block.time.seconds());
+        assert_eq!((value.followers, value.following), (0, 0));
+
+        // The next profile gets the next id, posts keep their own counter
+        let msg = ExecuteMsg::CreateProfile {
+            user_name: "bob".to_string(),
+            about: "about bob".to_string(),
+            image_url: "ipfs://image".to_string(),
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&addr("bob"), &[]),
+            msg,
+        )
+        .unwrap();
+        assert_eq!(load_profile(deps.as_ref(), "bob").profile.id, 2);
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::CurrentIndex {}).unwrap();
+        let value: GetIndexResponse = from_json(&res).unwrap();
+        assert_eq!(value.current_index, 0);
+
+        // The owner can update it
+        let msg = ExecuteMsg::UpdateProfile {
+            user_name: "alice".to_string(),
+            about: "updated".to_string(),
+            image_url: "ipfs://updated".to_string(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&addr("bob"), &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&addr("alice"), &[]),
+            msg,
+        )
+        .unwrap();
+        let value = load_profile(deps.as_ref(), "alice");
+        assert_eq!(value.profile.about, "updated");
+        assert_eq!(value.profile.image, "ipfs://updated");
+    }
+
+    #[test]
+    fn stats_track_activity() {
+        let mut deps = mock_dependencies();
+        setup_profiles(deps.as_mut(), &[("alice", "alice"), ("bob", "bob")]);
+        let first = create_post_as(deps.as_mut(), "alice");
+        let second = create_post_as(deps.as_mut(), "alice");
+        for id in [first, second] {
+            let msg = ExecuteMsg::LikePost { id };
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                message_info(&addr("bob"), &[]),
+                msg,
+            )
+            .unwrap();
+            let msg = ExecuteMsg::Comment {
+                id,
+                comment: "comment".to_string(),
+            };
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                message_info(&addr("bob"), &[]),
+                msg,
+            )
+            .unwrap();
+        }
+
+        let stats = |deps: Deps| {
+            let res = query(deps, mock_env(), QueryMsg::Stats {}).unwrap();
+            from_json::<StatsResponse>(&res).unwrap().stats
+        };
+        let expected = Stats {
+            profiles: 2,
+            posts: 2,
+            comments: 2,
+            likes: 2,
+        };
+        assert_eq!(stats(deps.as_ref()), expected);
+
+        // Deleting a post drops its likes and comments from the totals
+        let msg = ExecuteMsg::DeletePost { id: first };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&addr("alice"), &[]),
+            msg,
+        )
+        .unwrap();
+        let expected = Stats {
+            profiles: 2,
+            posts: 1,
+            comments: 1,
+            likes: 1,
+        };
+        assert_eq!(stats(deps.as_ref()), expected);
+
+        // Migrating within the same layout leaves them alone
+        migrate(deps.as_mut(), mock_env(), MigrateMsg { admin: None }).unwrap();
+        assert_eq!(stats(deps.as_ref()), expected);
+    }
+
+    #[test]
+    fn create_profile_rejects_taken_username() {
+        let mut deps = mock_dependencies();
+        setup_profiles(deps.as_mut(), &[("alice", "alice")]);
+
+        // Usernames are case insensitive
+        let msg = ExecuteMsg::CreateProfile {
+            user_name: " Alice ".to_string(),
+            about: "impostor".to_string(),
+            image_url: "ipfs://image".to_string(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&addr("bob"), &[]),
+            msg,
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::UsernameTaken {}));
+
+        // The original profile is untouched
+        assert_eq!(
+            load_profile(deps.as_ref(), "alice").profile.addr,
+            addr("alice")
+        );
+    }
+
+    #[test]
+    fn create_profile_rejects_second_profile() {
+        let mut deps = mock_dependencies();
+        setup_profiles(deps.as_mut(), &[("alice", "alice")]);
+
+        let msg = ExecuteMsg::CreateProfile {
+            user_name: "alice2".to_string(),
+            about: "about".to_string(),
+            image_url: "ipfs://image".to_string(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&addr("alice"), &[]),
+            msg,
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::ProfileAlreadyExists {}));
+        assert!(!PROFILES.has(&deps.storage, "alice2".to_string()));
+    }
+
+    #[test]
+    fn follow_acts_on_sender_profile() {
+        let mut deps = mock_dependencies();
+        setup_profiles(deps.as_mut(), &[("alice", "alice"), ("bob", "bob")]);
+
+        // Senders without a profile can't follow on anyone's behalf
+        let msg = ExecuteMsg::Follow {
+            following_user_name: "bob".to_string(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&addr("mallory"), &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::ProfileNotFound {}));
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&addr("alice"), &[]),
+            msg,
+        )
+        .unwrap();
+        assert!(is_following(deps.as_ref(), "alice", "bob"));
+        assert!(!is_following(deps.as_ref(), "bob", "alice"));
+        assert_eq!(load_profile(deps.as_ref(), "bob").followers, 1);
+
+        // Profiles can't follow themselves
+        let msg = ExecuteMsg::Follow {
+            following_user_name: "Alice".to_string(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&addr("alice"), &[]),
+            msg,
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::CannotFollowSelf {}));
+    }
+
+    #[test]
+    fn unfollow_acts_on_sender_profile() {
+        let mut deps = mock_dependencies();
+        setup_profiles(
+            deps.as_mut(),
+            &[("alice", "alice"), ("bob", "bob"), ("carol", "carol")],
+        );
+        let msg = ExecuteMsg::Follow {
+            following_user_name: "bob".to_string(),
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&addr("alice"), &[]),
+            msg,
+        )
+        .unwrap();
+
+        // Another profile can't remove alice's follow
+        let msg = ExecuteMsg::Unfollow {
+            following_user_name: "bob".to_string(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&addr("carol"), &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::NotFollowing {}));
+        assert!(is_following(deps.as_ref(), "alice", "bob"));
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&addr("alice"), &[]),
+            msg,
+        )
+        .unwrap();
+        assert!(!is_following(deps.as_ref(), "alice", "bob"));
+        assert_eq!(load_profile(deps.as_ref(), "alice").following, 0);
+        assert_eq!(load_profile(deps.as_ref(), "bob").followers, 0);
+    }
+
+    #[test]
+    fn follow_rejects_duplicates() {
+        let mut deps = mock_dependencies();
+        setup_profiles(deps.as_mut(), &[("alice", "alice"), ("bob", "bob")]);
+        follow_as(deps.as_mut(), "alice", "bob");
+
+        let msg = ExecuteMsg::Follow {
+            following_user_name: "BOB".to_string(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&addr("alice"), &[]),
+            msg,
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::AlreadyFollowing {}));
+        assert_eq!(load_profile(deps.as_ref(), "bob").followers, 1);
+
+        // Following a missing profile fails
+        let msg = ExecuteMsg::Follow {
+            following_user_name: "nobody".to_string(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&addr("alice"), &[]),
+            msg,
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::ProfileNotFound {}));
+    }
+
+    #[test]
+    fn query_follows_paginated() {
+        let mut deps = mock_dependencies();
+        setup_profiles(
+            deps.as_mut(),
+            &[
+                ("alice", "alice"),
+                ("bob", "bob"),
+                ("carol", "carol"),
+                ("dave", "dave"),
+            ],
+        );
+        follow_as(deps.as_mut(), "bob", "alice");
+        follow_as(deps.as_mut(), "dave", "alice");
+        follow_as(deps.as_mut(), "carol", "alice");
+        follow_as(deps.as_mut(), "alice", "dave");
+
+        let msg = QueryMsg::Followers {
+            user_name: "alice".to_string(),
+            start_after: None,
+            limit: Some(2),
+        };
+        let res: FollowersResponse =
+            from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        let names: Vec<_> = res.followers.iter().map(|f| f.user_name.as_str()).collect();
+        assert_eq!(names, vec!["bob", "carol"]);
+        assert_eq!(res.followers[0].since, mock_env().block.time);
+
+        let msg = QueryMsg::Followers {
+            user_name: "alice".to_string(),
+            start_after: Some(addr("carol").to_string()),
+            limit: Some(2),
+        };
+        let res: FollowersResponse =
+            from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        let names: Vec<_> = res.followers.iter().map(|f| f.user_name.as_str()).collect();
+        assert_eq!(names, vec!["dave"]);
+
+        let msg = QueryMsg::Following {
+            user_name: "alice".to_string(),
+            start_after: None,
+            limit: None,
+        };
+        let res: FollowingResponse =
+            from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        let names: Vec<_> = res.following.iter().map(|f| f.user_name.as_str()).collect();
+        assert_eq!(names, vec!["dave"]);
+
+        let profile = load_profile(deps.as_ref(), "alice");
+        assert_eq!((profile.followers, profile.following), (3, 1));
+    }
+
+    #[test]
+    fn like_and_unlike_post() {
+        let mut deps = mock_dependencies();
+        setup_profiles(deps.as_mut(), &[]);
+        let id = create_post_as(deps.as_mut(), "alice");
+
+        for sender in ["carol", "bob", "alice"] {
+            let msg = ExecuteMsg::LikePost { id };
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                message_info(&addr(sender), &[]),
+                msg,
+            )
+            .unwrap();
+        }
+        assert_eq!(load_post(deps.as_ref(), id).likes, 3);
+
+        // One like per address
+        let msg = ExecuteMsg::LikePost { id };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&addr("bob"), &[]),
+            msg,
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::AlreadyLiked {}));
+
+        let msg = QueryMsg::Likers {
+            id,
+            start_after: None,
+            limit: Some(2),
         };
-        let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let res: LikersResponse =
+            from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(res.likers, vec![addr("alice"), addr("bob")]);
+        let msg = QueryMsg::Likers {
+            id,
+            start_after: Some(addr("bob").to_string()),
+            limit: Some(2),
+        };
+        let res: LikersResponse =
+            from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(res.likers, vec![addr("carol")]);
+
+        let msg = ExecuteMsg::UnlikePost { id };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&addr("bob"), &[]),
+            msg.clone(),
+        )
+        .unwrap();
+        assert_eq!(load_post(deps.as_ref(), id).likes, 2);
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&addr("bob"), &[]),
+            msg,
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::NotLiked {}));
+    }
 
-        // Get update current index.
-        let res = query(deps.as_ref(), mock_env(), QueryMsg::CurrentIndex {}).unwrap();
-        let value: GetIndexResponse = from_json(&res).unwrap();
-        let new_index = value.current_index;
+    #[test]
+    fn comment_lifecycle() {
+        let mut deps = mock_dependencies();
+        setup_profiles(deps.as_mut(), &[]);
+        let id = create_post_as(deps.as_mut(), "alice");
+
+        for (sender, comment) in [("bob", "first"), ("carol", "second"), ("bob", "third")] {
+            let msg = ExecuteMsg::Comment {
+                id,
+                comment: comment.to_string(),
+            };
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                message_info(&addr(sender), &[]),
+                msg,
+            )
+            .unwrap();
+        }
+        assert_eq!(load_post(deps.as_ref(), id).comments, 3);
 
-        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetPost { id: 1 }).unwrap();
-        let value: GetPostResponse = from_json(&res).unwrap();
+        let msg = QueryMsg::Comments {
+            id,
+            start_after: Some(1),
+            limit: Some(1),
+        };
+        let res: CommentsResponse =
+            from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(res.comments.len(), 1);
+        assert_eq!(res.comments[0].id, 2);
+        assert_eq!(res.comments[0].author, addr("carol"));
+        assert_eq!(res.comments[0].content, "second");
+        assert_eq!(res.comments[0].created_at, mock_env().block.time.seconds());
+
+        // Only the comment author can edit
+        let msg = ExecuteMsg::EditComment {
+            id,
+            comment_id: 2,
+            comment: "edited".to_string(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&addr("bob"), &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(60);
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            message_info(&addr("carol"), &[]),
+            msg,
+        )
+        .unwrap();
+        let comment = COMMENTS.load(&deps.storage, (id, 2)).unwrap();
+        assert_eq!(comment.content, "edited");
+        assert_eq!(comment.updated_at, env.block.time.seconds());
+
+        // Others can't delete, the comment and the post authors can
+        let msg = ExecuteMsg::DeleteComment { id, comment_id: 1 };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&addr("carol"), &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&addr("alice"), &[]),
+            msg.clone(),
+        )
+        .unwrap();
+        let msg = ExecuteMsg::DeleteComment { id, comment_id: 3 };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&addr("bob"), &[]),
+            msg.clone(),
+        )
+        .unwrap();
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&addr("bob"), &[]),
+            msg,
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::CommentNotFound {}));
+
+        assert_eq!(load_post(deps.as_ref(), id).comments, 1);
+        let msg = QueryMsg::Comments {
+            id,
+            start_after: None,
+            limit: None,
+        };
+        let res: CommentsResponse =
+            from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        let ids: Vec<_> = res.comments.iter().map(|comment| comment.id).collect();
+        assert_eq!(ids, vec![2]);
+    }
 
-        // Should be assigned current index
-        assert_eq!(new_index, value.post.id);
+    #[test]
+    fn feed_merges_followed_authors() {
+        let mut deps = mock_dependencies();
+        setup_profiles(
+            deps.as_mut(),
+            &[("alice", "alice"), ("bob", "bob"), ("carol", "carol")],
+        );
+        follow_as(deps.as_mut(), "alice", "bob");
+        follow_as(deps.as_mut(), "alice", "carol");
 
-        // Check title
-        assert_eq!(title, value.post.title);
+        // 1 bob, 2 carol, 3 alice, 4 bob, 5 carol, 6 bob
+        for author in ["bob", "carol", "alice", "bob", "carol", "bob"] {
+            create_post_as(deps.as_mut(), author);
+        }
+        let msg = ExecuteMsg::DeletePost { id: 4 };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&addr("bob"), &[]),
+            msg,
+        )
+        .unwrap();
+
+        let feed = |start_after| QueryMsg::Feed {
+            user_name: "Alice".to_string(),
+            start_after,
+            limit: Some(2),
+        };
+        assert_eq!(feed_ids(deps.as_ref(), feed(None)), vec![6, 5]);
+        assert_eq!(feed_ids(deps.as_ref(), feed(Some(5))), vec![2, 1]);
+        assert!(feed_ids(deps.as_ref(), feed(Some(1))).is_empty());
+
+        // Nobody followed, nothing to show
+        let msg = QueryMsg::Feed {
+            user_name: "bob".to_string(),
+            start_after: None,
+            limit: None,
+        };
+        assert!(feed_ids(deps.as_ref(), msg).is_empty());
+    }
 
-        // Check content
-        assert_eq!(content, value.post.content);
+    #[test]
+    fn feed_flags_left_out_authors() {
+        let mut deps = mock_dependencies();
+        let user_names: Vec<String> = (0..=100).map(|i| format!("user{:03}", i)).collect();
+        let mut profiles = vec![("alice", "alice")];
+        profiles.extend(user_names.iter().map(|name| (name.as_str(), name.as_str())));
+        setup_profiles(deps.as_mut(), &profiles);
+        for user_name in user_names.iter() {
+            follow_as(deps.as_mut(), "alice", user_name);
+        }
+        // Only the last followed profile posts
+        let id = create_post_as(deps.as_mut(), "user100");
+
+        let feed = |deps: Deps| {
+            let msg = QueryMsg::Feed {
+                user_name: "alice".to_string(),
+                start_after: None,
+                limit: None,
+            };
+            from_json::<FeedResponse>(query(deps, mock_env(), msg).unwrap()).unwrap()
+        };
+        let res = feed(deps.as_ref());
+        assert!(res.truncated);
+        assert!(res.posts.is_empty());
 
-        // Check author
-        assert_eq!(author, value.post.author);
+        let msg = ExecuteMsg::Unfollow {
+            following_user_name: "user000".to_string(),
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&addr("alice"), &[]),
+            msg,
+        )
+        .unwrap();
+        let res = feed(deps.as_ref());
+        assert!(!res.truncated);
+        assert_eq!(res.posts[0].id, id);
     }
 
     #[test]
-    fn update_post() {
+    fn user_posts_page_by_author() {
         let mut deps = mock_dependencies();
+        setup_profiles(deps.as_mut(), &[]);
+        let alice = deps.api.addr_make("alice");
+        let bob = deps.api.addr_make("bob");
+        for sender in [&alice, &bob, &alice, &alice] {
+            let msg = ExecuteMsg::CreatePost {
+                title: "title".to_string(),
+                content: "content".to_string(),
+                parent_id: None,
+                quote_of: None,
+            };
+            execute(deps.as_mut(), mock_env(), message_info(sender, &[]), msg).unwrap();
+        }
+        let msg = ExecuteMsg::DeletePost { id: 3 };
+        execute(deps.as_mut(), mock_env(), message_info(&alice, &[]), msg).unwrap();
+
+        let user_posts = |start_after, limit| {
+            let msg = QueryMsg::GetUserPosts {
+                user: alice.to_string(),
+                start_after,
+                limit,
+            };
+            from_json::<GetUserPostsResponse>(query(deps.as_ref(), mock_env(), msg).unwrap())
+                .unwrap()
+                .posts
+        };
+        assert_eq!(user_posts(None, None), vec![1, 4]);
+        assert_eq!(user_posts(None, Some(1)), vec![1]);
+        assert_eq!(user_posts(Some(1), None), vec![4]);
+
+        // Deleting a post that is already gone fails before touching anything
+        let msg = ExecuteMsg::DeletePost { id: 3 };
+        let err = execute(deps.as_mut(), mock_env(), message_info(&alice, &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::PostNotFound {}));
+    }
 
-        // Instantiate contract
-        let msg = InstantiateMsg {};
-        let info = message_info(
-            &Addr::from_vec("creator".as_bytes().to_vec()).unwrap(),
-            &coins(1_000_000, "hackATOM"),
-        );
-        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+    #[test]
+    fn global_feed_pages_newest_first() {
+        let mut deps = mock_dependencies();
+        setup_profiles(deps.as_mut(), &[]);
+        for _ in 0..35 {
+            create_post_as(deps.as_mut(), "alice");
+        }
 
-        let info = message_info(
-            &Addr::from_vec("anyone".as_bytes().to_vec()).unwrap(),
-            &coins(10, "hackATOM"),
-        );
-        let title = "title".to_string();
-        let content = "content".to_string();
+        let msg = QueryMsg::GlobalFeed {
+            start_after: None,
+            limit: Some(3),
+        };
+        assert_eq!(feed_ids(deps.as_ref(), msg), vec![35, 34, 33]);
 
-        let msg = ExecuteMsg::CreatePost {
-            title: title.clone(),
-            content: content.clone(),
+        // Page size is capped
+        let msg = QueryMsg::GlobalFeed {
+            start_after: Some(33),
+            limit: Some(100),
         };
-        let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let ids = feed_ids(deps.as_ref(), msg);
+        assert_eq!(ids.len(), 30);
+        assert_eq!((ids[0], ids[29]), (32, 3));
+    }
 
-        // Get update current index.
-        let res = query(deps.as_ref(), mock_env(), QueryMsg::CurrentIndex {}).unwrap();
-        let value: GetIndexResponse = from_json(&res).unwrap();
-        let new_index = value.current_index;
+    #[test]
+    fn usernames_follow_policy() {
+        let mut deps = mock_dependencies();
+        setup_profiles(deps.as_mut(), &[("alice", "alice")]);
+
+        for (user_name, expected) in [
+            ("al", ContractError::InvalidUsernameLength {}),
+            (
+                "a_very_long_username_1",
+                ContractError::InvalidUsernameLength {},
+            ),
+            ("bob smith", ContractError::InvalidUsernameCharacter {}),
+            ("bób", ContractError::InvalidUsernameCharacter {}),
+        ] {
+            let msg = ExecuteMsg::CreateProfile {
+                user_name: user_name.to_string(),
+                about: "about".to_string(),
+                image_url: "ipfs://image".to_string(),
+            };
+            let err = execute(
+                deps.as_mut(),
+                mock_env(),
+                message_info(&addr("bob"), &[]),
+                msg,
+            )
+            .unwrap_err();
+            assert_eq!(err.to_string(), expected.to_string());
+
+            let msg = ExecuteMsg::ChangeUsername {
+                new_user_name: user_name.to_string(),
+            };
+            let err = execute(
+                deps.as_mut(),
+                mock_env(),
+                message_info(&addr("alice"), &[]),
+                msg,
+            )
+            .unwrap_err();
+            assert_eq!(err.to_string(), expected.to_string());
+        }
+    }
 
-        // Update post
-        let info = message_info(
-            &Addr::from_vec("anyone".as_bytes().to_vec()).unwrap(),
-            &coins(10, "hackATOM"),
+    #[test]
+    fn change_username() {
+        let mut deps = mock_dependencies();
+        setup_profiles(
+            deps.as_mut(),
+            &[("alice", "alice"), ("bob", "bob"), ("carol", "carol")],
         );
-        let another_title = "another title".to_string();
-        let another_content = "another content".to_string();
+        follow_as(deps.as_mut(), "alice", "bob");
+        follow_as(deps.as_mut(), "bob", "carol");
+
+        // Only profile owners can rename, and not onto a taken name
+        let msg = ExecuteMsg::ChangeUsername {
+            new_user_name: "robert".to_string(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&addr("dave"), &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::ProfileNotFound {}));
+        let taken = ExecuteMsg::ChangeUsername {
+            new_user_name: "Carol".to_string(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&addr("bob"), &[]),
+            taken,
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::UsernameTaken {}));
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&addr("bob"), &[]),
+            msg,
+        )
+        .unwrap();
+
+        // The profile and its edges moved
+        let value = load_profile(deps.as_ref(), "robert");
+        assert_eq!(value.profile.addr, addr("bob"));
+        assert_eq!(value.profile.user_name, "robert");
+        assert_eq!((value.followers, value.following), (1, 1));
+        assert!(!PROFILES.has(&deps.storage, "bob".to_string()));
+        assert!(is_following(deps.as_ref(), "alice", "robert"));
+        assert!(is_following(deps.as_ref(), "robert", "carol"));
+        assert!(!is_following(deps.as_ref(), "alice", "bob"));
+        assert!(!is_following(deps.as_ref(), "bob", "carol"));
+        let msg = QueryMsg::Followers {
+            user_name: "carol".to_string(),
+            start_after: None,
+            limit: None,
+        };
+        let res: FollowersResponse =
+            from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(res.followers[0].user_name, "robert");
+
+        // The old name is reserved for its previous owner
+        let msg = ExecuteMsg::CreateProfile {
+            user_name: "bob".to_string(),
+            about: "impostor".to_string(),
+            image_url: "ipfs://image".to_string(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&addr("mallory"), &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::UsernameReserved {}));
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(60);
+        let back = ExecuteMsg::ChangeUsername {
+            new_user_name: "bob".to_string(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            message_info(&addr("bob"), &[]),
+            back.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::UsernameChangeCooldown {}));
+        env.block.time = mock_env()
+            .block
+            .time
+            .plus_seconds(USERNAME_CHANGE_COOLDOWN_SECONDS);
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            message_info(&addr("bob"), &[]),
+            back,
+        )
+        .unwrap();
+        assert_eq!(load_profile(deps.as_ref(), "bob").profile.addr, addr("bob"));
+
+        // After the cooldown anyone can take a released name
+        env.block.time = env.block.time.plus_seconds(USERNAME_RESERVATION_SECONDS);
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            message_info(&addr("mallory"), &[]),
+            msg,
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::UsernameTaken {}));
+        let msg = ExecuteMsg::CreateProfile {
+            user_name: "robert".to_string(),
+            about: "about".to_string(),
+            image_url: "ipfs://image".to_string(),
+        };
+        execute(deps.as_mut(), env, message_info(&addr("mallory"), &[]), msg).unwrap();
+
+        let msg = QueryMsg::UsernameHistory {
+            user_name: "bob".to_string(),
+            start_after: None,
+            limit: None,
+        };
+        let res: UsernameHistoryResponse =
+            from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        let names: Vec<_> = res
+            .changes
+            .iter()
+            .map(|change| (change.old_user_name.as_str(), change.new_user_name.as_str()))
+            .collect();
+        assert_eq!(names, vec![("bob", "robert"), ("robert", "bob")]);
+        assert_eq!(res.changes[0].changed_at, mock_env().block.time);
+
+        let msg = QueryMsg::UsernameHistory {
+            user_name: "bob".to_string(),
+            start_after: Some(0),
+            limit: None,
+        };
+        let res: UsernameHistoryResponse =
+            from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(res.changes.len(), 1);
+    }
+
+    #[test]
+    fn change_username_keeps_follows() {
+        let mut deps = mock_dependencies();
+        let user_names: Vec<String> = (0..300).map(|i| format!("user{:03}", i)).collect();
+        let mut profiles = vec![("alice", "alice")];
+        profiles.extend(user_names.iter().map(|name| (name.as_str(), name.as_str())));
+        setup_profiles(deps.as_mut(), &profiles);
+        for user_name in user_names.iter() {
+            follow_as(deps.as_mut(), user_name, "alice");
+        }
+        follow_as(deps.as_mut(), "alice", "user000");
+
+        let msg = ExecuteMsg::ChangeUsername {
+            new_user_name: "alicia".to_string(),
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&addr("alice"), &[]),
+            msg,
+        )
+        .unwrap();
+
+        let profile = load_profile(deps.as_ref(), "alicia");
+        assert_eq!((profile.followers, profile.following), (300, 1));
+        assert!(is_following(deps.as_ref(), "user299", "alicia"));
+        assert!(is_following(deps.as_ref(), "alicia", "user000"));
+        assert!(!is_following(deps.as_ref(), "user299", "alice"));
+
+        let msg = QueryMsg::Followers {
+            user_name: "user000".to_string(),
+            start_after: None,
+            limit: None,
+        };
+        let res: FollowersResponse =
+            from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(res.followers.len(), 1);
+        assert_eq!(res.followers[0].addr, addr("alice"));
+        assert_eq!(res.followers[0].user_name, "alicia");
+    }
+
+    #[test]
+    fn content_limits() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            admin: None,
+            limits: Some(Limits {
+                max_about_length: 10,
+                max_image_url_length: 20,
+                max_title_length: 5,
+                max_content_length: 10,
+                max_comment_length: 5,
+                max_reason_length: 10,
+            }),
+            tip_fee: None,
+        };
+        instantiate(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&addr("creator"), &[]),
+            msg,
+        )
+        .unwrap();
+        let info = message_info(&addr("alice"), &[]);
+
+        let profile = |about: &str, image_url: &str| ExecuteMsg::CreateProfile {
+            user_name: "alice".to_string(),
+            about: about.to_string(),
+            image_url: image_url.to_string(),
+        };
+        for (msg, expected) in [
+            (profile("about alice!", ""), ContractError::AboutTooLong {}),
+            (profile("about\0", ""), ContractError::ControlCharacter {}),
+            (
+                profile("about", "https://example.com/a.png"),
+                ContractError::ImageUrlTooLong {},
+            ),
+            (
+                profile("about", "http://a.png"),
+                ContractError::InvalidImageUrl {},
+            ),
+            (
+                profile("about", "ipfs://a\nb"),
+                ContractError::ControlCharacter {},
+            ),
+        ] {
+            let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+            assert_eq!(err.to_string(), expected.to_string());
+        }
+        // Surrounding whitespace doesn't count, line breaks are fine in multiline text
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            profile(" ab\ncd ", ""),
+        )
+        .unwrap();
+        assert_eq!(load_profile(deps.as_ref(), "alice").profile.about, "ab\ncd");
+
+        let post = |title: &str, content: &str| ExecuteMsg::CreatePost {
+            title: title.to_string(),
+            content: content.to_string(),
+            parent_id: None,
+            quote_of: None,
+        };
+        for (msg, expected) in [
+            (post("title!", "content"), ContractError::TitleTooLong {}),
+            (post("t\nt", "content"), ContractError::ControlCharacter {}),
+            (
+                post("title", "content is long"),
+                ContractError::ContentTooLong {},
+            ),
+            (
+                post("title", "con\u{7}tent"),
+                ContractError::ControlCharacter {},
+            ),
+        ] {
+            let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+            assert_eq!(err.to_string(), expected.to_string());
+        }
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            post("title", "con\ntent"),
+        )
+        .unwrap();
 
         let msg = ExecuteMsg::UpdatePost {
-            id: new_index,
-            title: another_title.clone(),
-            content: another_content.clone(),
+            id: 1,
+            title: "title".to_string(),
+            content: "content is long".to_string(),
         };
-        let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::ContentTooLong {}));
 
-        // Check updates
-        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetPost { id: 1 }).unwrap();
-        let value: GetPostResponse = from_json(&res).unwrap();
+        let msg = ExecuteMsg::Comment {
+            id: 1,
+            comment: "comment".to_string(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::CommentTooLong {}));
+        let msg = ExecuteMsg::Comment {
+            id: 1,
+            comment: "hi".to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        let msg = ExecuteMsg::EditComment {
+            id: 1,
+            comment_id: 1,
+            comment: "hi\u{1b}".to_string(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::ControlCharacter {}));
 
-        // Check title
-        assert_eq!(another_title, value.post.title);
+        // Report reasons have their own limit
+        let report = |reason: &str| ExecuteMsg::ReportPost {
+            id: 1,
+            reason: reason.to_string(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            report("offensive!!"),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::ReasonTooLong {}));
+        execute(deps.as_mut(), mock_env(), info, report("offensive")).unwrap();
+    }
 
-        // Check content
-        assert_eq!(another_content, value.post.content);
+    #[test]
+    fn update_config() {
+        let mut deps = mock_dependencies();
+        let admin = deps.api.addr_make("admin");
+        let msg = InstantiateMsg {
+            admin: Some(admin.to_string()),
+            limits: None,
+            tip_fee: None,
+        };
+        instantiate(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&addr("creator"), &[]),
+            msg,
+        )
+        .unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+        let config = from_json::<ConfigResponse>(&res).unwrap().config;
+        assert_eq!(config.admin, admin);
+        assert_eq!(config.limits, Limits::default());
+
+        let limits = Limits {
+            max_title_length: 10,
+            ..Limits::default()
+        };
+        let msg = ExecuteMsg::UpdateConfig {
+            admin: None,
+            limits: Some(limits.clone()),
+            tip_fee: None,
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&addr("creator"), &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        execute(deps.as_mut(), mock_env(), message_info(&admin, &[]), msg).unwrap();
+
+        // Every length must be positive and storage safe
+        for invalid in [
+            Limits {
+                max_comment_length: 0,
+                ..Limits::default()
+            },
+            Limits {
+                max_title_length: MAX_TEXT_LENGTH + 1,
+                ..Limits::default()
+            },
+        ] {
+            let msg = ExecuteMsg::UpdateConfig {
+                admin: None,
+                limits: Some(invalid),
+                tip_fee: None,
+            };
+            let err =
+                execute(deps.as_mut(), mock_env(), message_info(&admin, &[]), msg).unwrap_err();
+            assert!(matches!(err, ContractError::InvalidLimits {}));
+        }
+        let msg = InstantiateMsg {
+            admin: None,
+            limits: Some(Limits {
+                max_about_length: 0,
+                ..Limits::default()
+            }),
+            tip_fee: None,
+        };
+        let err = instantiate(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&addr("creator"), &[]),
+            msg,
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::InvalidLimits {}));
+
+        // Hand over to a new admin
+        let new_admin = deps.api.addr_make("new_admin");
+        let msg = ExecuteMsg::UpdateConfig {
+            admin: Some(new_admin.to_string()),
+            limits: None,
+            tip_fee: None,
+        };
+        execute(deps.as_mut(), mock_env(), message_info(&admin, &[]), msg).unwrap();
+        let config = CONFIG.load(&deps.storage).unwrap();
+        assert_eq!(config.admin, new_admin);
+        assert_eq!(config.limits, limits);
     }
 
     #[test]
-    fn delete_post() {
+    fn migrate_requires_admin_without_config() {
         let mut deps = mock_dependencies();
+        set_contract_version(&mut deps.storage, CONTRACT_NAME, "0.1.0").unwrap();
+        PROFILE_INDEX
+            .save(&mut deps.storage, &Index { current_index: 0 })
+            .unwrap();
+        POST_INDEX
+            .save(&mut deps.storage, &Index { current_index: 0 })
+            .unwrap();
+
+        let err = migrate(deps.as_mut(), mock_env(), MigrateMsg { admin: None }).unwrap_err();
+        assert!(matches!(err, ContractError::AdminRequired {}));
+
+        let admin = deps.api.addr_make("admin");
+        let msg = MigrateMsg {
+            admin: Some(admin.to_string()),
+        };
+        migrate(deps.as_mut(), mock_env(), msg).unwrap();
+        assert_eq!(CONFIG.load(&deps.storage).unwrap().admin, admin);
+    }
 
-        // Instantiate contract
-        let msg = InstantiateMsg {};
-        let info = message_info(
-            &Addr::from_vec("creator".as_bytes().to_vec()).unwrap(),
-            &coins(1_000_000, "hackATOM"),
+    #[test]
+    fn moderators_hide_posts_and_resolve_reports() {
+        let mut deps = mock_dependencies();
+        let admin = deps.api.addr_make("admin");
+        let moderator = deps.api.addr_make("moderator");
+        let msg = InstantiateMsg {
+            admin: Some(admin.to_string()),
+            limits: None,
+            tip_fee: None,
+        };
+        instantiate(deps.as_mut(), mock_env(), message_info(&admin, &[]), msg).unwrap();
+        let first = create_post_as(deps.as_mut(), "alice");
+        let second = create_post_as(deps.as_mut(), "alice");
+        let msg = ExecuteMsg::Comment {
+            id: first,
+            comment: "spam".to_string(),
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&addr("bob"), &[]),
+            msg,
+        )
+        .unwrap();
+
+        // Only the admin appoints moderators
+        let msg = ExecuteMsg::AddModerator {
+            address: moderator.to_string(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&moderator, &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        execute(deps.as_mut(), mock_env(), message_info(&admin, &[]), msg).unwrap();
+
+        // Anyone can report, once per target
+        let msg = ExecuteMsg::ReportPost {
+            id: first,
+            reason: "offensive".to_string(),
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&addr("carol"), &[]),
+            msg.clone(),
+        )
+        .unwrap();
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&addr("carol"), &[]),
+            msg,
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::AlreadyReported {}));
+        let msg = ExecuteMsg::ReportComment {
+            id: first,
+            comment_id: 1,
+            reason: "spam".to_string(),
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&addr("carol"), &[]),
+            msg,
+        )
+        .unwrap();
+        let msg = ExecuteMsg::ReportComment {
+            id: first,
+            comment_id: 2,
+            reason: "spam".to_string(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&addr("carol"), &[]),
+            msg,
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::CommentNotFound {}));
+
+        let msg = QueryMsg::Reports {
+            start_after: None,
+            limit: None,
+        };
+        let res: ReportsResponse =
+            from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(res.reports.len(), 2);
+        assert_eq!(res.reports[0].target, ReportTarget::Post { id: first });
+        assert_eq!(res.reports[0].reason, "offensive");
+        assert_eq!(
+            res.reports[1].target,
+            ReportTarget::Comment {
+                id: first,
+                comment_id: 1
+            }
         );
-        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-        let info = message_info(
-            &Addr::from_vec("anyone".as_bytes().to_vec()).unwrap(),
-            &coins(10, "hackATOM"),
-        );
-        let title = "title".to_string();
-        let content = "content".to_string();
+        // Moderators hide posts and work through the queue
+        let msg = ExecuteMsg::HidePost { id: first };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&addr("carol"), &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&moderator, &[]),
+            msg,
+        )
+        .unwrap();
+        assert!(load_post(deps.as_ref(), first).hidden);
+        let msg = ExecuteMsg::ResolveReport { report_id: 1 };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&moderator, &[]),
+            msg.clone(),
+        )
+        .unwrap();
+        let err = execute(deps.as_mut(), mock_env(), message_info(&admin, &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::ReportNotFound {}));
+        let msg = QueryMsg::Reports {
+            start_after: None,
+            limit: None,
+        };
+        let res: ReportsResponse =
+            from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        let ids: Vec<_> = res.reports.iter().map(|report| report.id).collect();
+        assert_eq!(ids, vec![2]);
+
+        // Hidden posts are left out of feeds
+        setup_follow_feed(&mut deps);
+        let msg = QueryMsg::GlobalFeed {
+            start_after: None,
+            limit: None,
+        };
+        assert_eq!(feed_ids(deps.as_ref(), msg), vec![second]);
+        let msg = QueryMsg::Feed {
+            user_name: "bob".to_string(),
+            start_after: None,
+            limit: Some(1),
+        };
+        assert_eq!(feed_ids(deps.as_ref(), msg.clone()), vec![second]);
+        let msg = ExecuteMsg::UnhidePost { id: first };
+        execute(deps.as_mut(), mock_env(), message_info(&admin, &[]), msg).unwrap();
+        let msg = QueryMsg::GlobalFeed {
+            start_after: None,
+            limit: None,
+        };
+        assert_eq!(feed_ids(deps.as_ref(), msg), vec![second, first]);
+
+        // Removed moderators lose their powers
+        let msg = ExecuteMsg::RemoveModerator {
+            address: moderator.to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), message_info(&admin, &[]), msg).unwrap();
+        let msg = ExecuteMsg::HidePost { id: first };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&moderator, &[]),
+            msg,
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+    }
+
+    #[test]
+    fn banned_users_cannot_post() {
+        let mut deps = mock_dependencies();
+        let admin = deps.api.addr_make("admin");
+        let user = deps.api.addr_make("user");
+        let msg = InstantiateMsg {
+            admin: Some(admin.to_string()),
+            limits: None,
+            tip_fee: None,
+        };
+        instantiate(deps.as_mut(), mock_env(), message_info(&admin, &[]), msg).unwrap();
+        let id = create_post_as(deps.as_mut(), "alice");
+
+        let msg = ExecuteMsg::BanUser {
+            address: user.to_string(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&user, &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        execute(deps.as_mut(), mock_env(), message_info(&admin, &[]), msg).unwrap();
+
+        let is_banned = |deps: Deps| {
+            let msg = QueryMsg::IsBanned {
+                address: user.to_string(),
+            };
+            from_json::<IsBannedResponse>(query(deps, mock_env(), msg).unwrap())
+                .unwrap()
+                .is_banned
+        };
+        assert!(is_banned(deps.as_ref()));
+
+        for msg in [
+            ExecuteMsg::CreatePost {
+                title: "title".to_string(),
+                content: "content".to_string(),
+                parent_id: None,
+                quote_of: None,
+            },
+            ExecuteMsg::Comment {
+                id,
+                comment: "comment".to_string(),
+            },
+            ExecuteMsg::LikePost { id },
+        ] {
+            let err =
+                execute(deps.as_mut(), mock_env(), message_info(&user, &[]), msg).unwrap_err();
+            assert!(matches!(err, ContractError::Banned {}));
+        }
+
+        let msg = ExecuteMsg::UnbanUser {
+            address: user.to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), message_info(&admin, &[]), msg).unwrap();
+        assert!(!is_banned(deps.as_ref()));
+        let msg = ExecuteMsg::LikePost { id };
+        execute(deps.as_mut(), mock_env(), message_info(&user, &[]), msg).unwrap();
+    }
 
+    #[test]
+    fn tip_post_in_native_tokens() {
+        let mut deps = mock_dependencies();
+        let admin = deps.api.addr_make("admin");
+        let author = deps.api.addr_make("author");
+        let reader = deps.api.addr_make("reader");
+        let msg = InstantiateMsg {
+            admin: Some(admin.to_string()),
+            limits: None,
+            tip_fee: Some(Decimal::percent(10)),
+        };
+        instantiate(deps.as_mut(), mock_env(), message_info(&admin, &[]), msg).unwrap();
         let msg = ExecuteMsg::CreatePost {
-            title: title.clone(),
-            content: content.clone(),
+            title: "title".to_string(),
+            content: "content".to_string(),
+            parent_id: None,
+            quote_of: None,
         };
-        let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        execute(deps.as_mut(), mock_env(), message_info(&author, &[]), msg).unwrap();
+
+        let msg = ExecuteMsg::TipPost { id: 1 };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&reader, &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::NoFunds {}));
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&author, &coins(100, "untrn")),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::CannotTipOwnPost {}));
+
+        let funds = [coin(1_005, "uatom"), coin(100, "untrn")];
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&reader, &funds),
+            msg,
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages
+                .iter()
+                .map(|msg| msg.msg.clone())
+                .collect::<Vec<_>>(),
+            vec![
+                CosmosMsg::Bank(BankMsg::Send {
+                    to_address: author.to_string(),
+                    amount: vec![coin(905, "uatom"), coin(90, "untrn")],
+                }),
+                CosmosMsg::Bank(BankMsg::Send {
+                    to_address: admin.to_string(),
+                    amount: vec![coin(100, "uatom"), coin(10, "untrn")],
+                }),
+            ]
+        );
 
-        // Get update current index.
-        let res = query(deps.as_ref(), mock_env(), QueryMsg::CurrentIndex {}).unwrap();
-        let value: GetIndexResponse = from_json(&res).unwrap();
-        let new_index = value.current_index;
+        // Post totals are before fees, earnings after
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::PostTips { id: 1 }).unwrap();
+        let tips = from_json::<TipsResponse>(&res).unwrap().tips;
+        assert_eq!(tips.len(), 2);
+        assert_eq!(tips[0].denom, Denom::Native("uatom".to_string()));
+        assert_eq!(tips[0].amount, Uint128::new(1_005));
+        let msg = QueryMsg::AuthorEarnings {
+            address: author.to_string(),
+        };
+        let tips = from_json::<TipsResponse>(query(deps.as_ref(), mock_env(), msg).unwrap())
+            .unwrap()
+            .tips;
+        assert_eq!(tips[1].denom, Denom::Native("untrn".to_string()));
+        assert_eq!(tips[1].amount, Uint128::new(90));
+
+        // The fee is capped at the whole tip
+        let msg = ExecuteMsg::UpdateConfig {
+            admin: None,
+            limits: None,
+            tip_fee: Some(Decimal::percent(101)),
+        };
+        let err = execute(deps.as_mut(), mock_env(), message_info(&admin, &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidTipFee {}));
+    }
 
-        // Delete post
-        let info = message_info(
-            &Addr::from_vec("anyone".as_bytes().to_vec()).unwrap(),
-            &coins(10, "hackATOM"),
+    #[test]
+    fn tip_post_in_cw20_tokens() {
+        let mut deps = mock_dependencies();
+        let author = deps.api.addr_make("author");
+        let reader = deps.api.addr_make("reader");
+        let token = deps.api.addr_make("token");
+        setup_profiles(deps.as_mut(), &[]);
+        for _ in 0..3 {
+            let msg = ExecuteMsg::CreatePost {
+                title: "title".to_string(),
+                content: "content".to_string(),
+                parent_id: None,
+                quote_of: None,
+            };
+            execute(deps.as_mut(), mock_env(), message_info(&author, &[]), msg).unwrap();
+        }
+
+        let tip = |id, amount| {
+            ExecuteMsg::Receive(Cw20ReceiveMsg {
+                sender: reader.to_string(),
+                amount: Uint128::new(amount),
+                msg: to_json_binary(&ReceiveMsg::TipPost { id }).unwrap(),
+            })
+        };
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&token, &[]),
+            tip(1, 50),
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: token.to_string(),
+                msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: author.to_string(),
+                    amount: Uint128::new(50),
+                })
+                .unwrap(),
+                funds: vec![],
+            })
         );
+        for (id, amount) in [(2, 80), (3, 60), (1, 40)] {
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                message_info(&token, &[]),
+                tip(id, amount),
+            )
+            .unwrap();
+        }
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&token, &[]),
+            tip(1, 0),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::InvalidZeroAmount {}));
+
+        let top = |deps: Deps, limit| {
+            let msg = QueryMsg::TopTippedPosts {
+                denom: Denom::Cw20(token.clone()),
+                limit,
+            };
+            from_json::<TopTippedPostsResponse>(query(deps, mock_env(), msg).unwrap())
+                .unwrap()
+                .posts
+                .iter()
+                .map(|post| (post.id, post.amount.u128()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(top(deps.as_ref(), Some(2)), vec![(1, 90), (2, 80)]);
 
-        let msg = ExecuteMsg::DeletePost { id: new_index };
-        let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        // Deleted posts drop out of the ranking
+        let msg = ExecuteMsg::DeletePost { id: 1 };
+        execute(deps.as_mut(), mock_env(), message_info(&author, &[]), msg).unwrap();
+        assert_eq!(top(deps.as_ref(), None), vec![(2, 80), (3, 60)]);
 
-        // Check updates
-        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetPost { id: 1 });
+        let msg = QueryMsg::AuthorEarnings {
+            address: author.to_string(),
+        };
+        let tips = from_json::<TipsResponse>(query(deps.as_ref(), mock_env(), msg).unwrap())
+            .unwrap()
+            .tips;
+        assert_eq!(tips.len(), 1);
+        assert_eq!(tips[0].denom, Denom::Cw20(token));
+        assert_eq!(tips[0].amount, Uint128::new(230));
+    }
 
-        // Check it's deleted
+    #[test]
+    fn replies_form_a_thread() {
+        let mut deps = mock_dependencies();
+        setup_profiles(deps.as_mut(), &[]);
+        let root = create_post_as(deps.as_mut(), "alice");
+        let first = reply_as(deps.as_mut(), "bob", root);
+        let second = reply_as(deps.as_mut(), "carol", root);
+        let nested = reply_as(deps.as_mut(), "alice", first);
+        assert_eq!((first, second, nested), (2, 3, 4));
+
+        let msg = ExecuteMsg::CreatePost {
+            title: "quote".to_string(),
+            content: "look at this".to_string(),
+            parent_id: None,
+            quote_of: Some(root),
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&addr("bob"), &[]),
+            msg,
+        )
+        .unwrap();
+        assert_eq!(load_post(deps.as_ref(), 5).quote_of, Some(root));
+        assert_eq!(load_post(deps.as_ref(), root).replies, 2);
+        assert_eq!(load_post(deps.as_ref(), nested).parent_id, Some(first));
+
+        // Quotes aren't replies, replies are regular posts
+        assert_eq!(thread_shape(deps.as_ref(), root, None, None), "1(2(4),3)");
+        assert_eq!(thread_shape(deps.as_ref(), root, Some(1), None), "1(2,3)");
+        assert_eq!(thread_shape(deps.as_ref(), root, None, Some(2)), "1(2,3)");
+        assert_eq!(thread_shape(deps.as_ref(), first, None, None), "2(4)");
+        let msg = QueryMsg::GlobalFeed {
+            start_after: None,
+            limit: None,
+        };
+        assert_eq!(feed_ids(deps.as_ref(), msg), vec![5, 4, 3, 2, 1]);
+
+        // Hidden replies are left out of the thread with their replies
+        let msg = ExecuteMsg::HidePost { id: first };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&addr("creator"), &[]),
+            msg,
+        )
+        .unwrap();
+        assert_eq!(thread_shape(deps.as_ref(), root, None, None), "1(3)");
+
+        for (parent_id, quote_of) in [(Some(99), None), (None, Some(99))] {
+            let msg = ExecuteMsg::CreatePost {
+                title: "title".to_string(),
+                content: "content".to_string(),
+                parent_id,
+                quote_of,
+            };
+            let err = execute(
+                deps.as_mut(),
+                mock_env(),
+                message_info(&addr("bob"), &[]),
+                msg,
+            )
+            .unwrap_err();
+            assert!(matches!(err, ContractError::PostNotFound {}));
+        }
+    }
+
+    #[test]
+    fn delete_post_with_replies_leaves_a_tombstone() {
+        let mut deps = mock_dependencies();
+        setup_profiles(deps.as_mut(), &[]);
+        let root = create_post_as(deps.as_mut(), "alice");
+        let reply = reply_as(deps.as_mut(), "bob", root);
+        let nested = reply_as(deps.as_mut(), "carol", reply);
+        for msg in [
+            ExecuteMsg::LikePost { id: reply },
+            ExecuteMsg::Comment {
+                id: reply,
+                comment: "comment".to_string(),
+            },
+        ] {
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                message_info(&addr("carol"), &[]),
+                msg,
+            )
+            .unwrap();
+        }
+
+        let msg = ExecuteMsg::DeletePost { id: reply };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&addr("bob"), &[]),
+            msg,
+        )
+        .unwrap();
+        let tombstone = load_post(deps.as_ref(), reply);
+        assert!(tombstone.deleted);
+        assert_eq!(
+            (tombstone.title.as_str(), tombstone.content.as_str()),
+            ("", "")
+        );
+        assert_eq!(thread_shape(deps.as_ref(), root, None, None), "1(2(3))");
+        let msg = QueryMsg::GlobalFeed {
+            start_after: None,
+            limit: None,
+        };
+        assert_eq!(feed_ids(deps.as_ref(), msg), vec![nested, root]);
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Stats {}).unwrap();
+        assert_eq!(from_json::<StatsResponse>(&res).unwrap().stats.posts, 2);
+
+        // Its comments and likers are no longer listed
+        let msg = QueryMsg::Comments {
+            id: reply,
+            start_after: None,
+            limit: None,
+        };
+        assert!(query(deps.as_ref(), mock_env(), msg).is_err());
+        let msg = QueryMsg::Likers {
+            id: reply,
+            start_after: None,
+            limit: None,
+        };
+        assert!(query(deps.as_ref(), mock_env(), msg).is_err());
+
+        // Tombstones can't be liked, replied to or deleted again
+        for msg in [
+            ExecuteMsg::LikePost { id: reply },
+            ExecuteMsg::DeletePost { id: reply },
+        ] {
+            let err = execute(
+                deps.as_mut(),
+                mock_env(),
+                message_info(&addr("bob"), &[]),
+                msg,
+            )
+            .unwrap_err();
+            assert!(matches!(err, ContractError::PostNotFound {}));
+        }
+        let msg = ExecuteMsg::CreatePost {
+            title: "title".to_string(),
+            content: "content".to_string(),
+            parent_id: Some(reply),
+            quote_of: None,
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&addr("dave"), &[]),
+            msg,
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::PostNotFound {}));
+
+        // Removing the last reply clears the tombstone it held up
+        let msg = ExecuteMsg::DeletePost { id: nested };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&addr("carol"), &[]),
+            msg,
+        )
+        .unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetPost { id: reply });
         assert!(res.is_err());
+        assert_eq!(load_post(deps.as_ref(), root).replies, 0);
+        assert_eq!(thread_shape(deps.as_ref(), root, None, None), "1");
+    }
+
+    #[test]
+    fn deleted_posts_reject_likes_comments_and_reports() {
+        let mut deps = mock_dependencies();
+        setup_profiles(deps.as_mut(), &[]);
+        let removed = create_post_as(deps.as_mut(), "alice");
+        let tombstone = create_post_as(deps.as_mut(), "alice");
+        reply_as(deps.as_mut(), "carol", tombstone);
+        for id in [removed, tombstone] {
+            for msg in [
+                ExecuteMsg::LikePost { id },
+                ExecuteMsg::Comment {
+                    id,
+                    comment: "comment".to_string(),
+                },
+            ] {
+                execute(
+                    deps.as_mut(),
+                    mock_env(),
+                    message_info(&addr("bob"), &[]),
+                    msg,
+                )
+                .unwrap();
+            }
+            let msg = ExecuteMsg::DeletePost { id };
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                message_info(&addr("alice"), &[]),
+                msg,
+            )
+            .unwrap();
+        }
+        assert!(load_post(deps.as_ref(), tombstone).deleted);
+
+        // Comment ids: 1 on the removed post, 2 on the tombstone
+        for (id, comment_id) in [(removed, 1), (tombstone, 2)] {
+            for msg in [
+                ExecuteMsg::LikePost { id },
+                ExecuteMsg::UnlikePost { id },
+                ExecuteMsg::Comment {
+                    id,
+                    comment: "comment".to_string(),
+                },
+                ExecuteMsg::EditComment {
+                    id,
+                    comment_id,
+                    comment: "edited".to_string(),
+                },
+                ExecuteMsg::DeleteComment { id, comment_id },
+                ExecuteMsg::ReportComment {
+                    id,
+                    comment_id,
+                    reason: "spam".to_string(),
+                },
+                ExecuteMsg::ReportPost {
+                    id,
+                    reason: "spam".to_string(),
+                },
+            ] {
+                let err = execute(
+                    deps.as_mut(),
+                    mock_env(),
+                    message_info(&addr("bob"), &[]),
+                    msg,
+                )
+                .unwrap_err();
+                assert!(matches!(err, ContractError::PostNotFound {}));
+            }
+        }
+    }
+
+    #[test]
+    fn migrate_checks_contract_version() {
+        let mut deps = mock_dependencies();
+        setup_profiles(deps.as_mut(), &[]);
+
+        set_contract_version(&mut deps.storage, "crates.io:other", "0.1.0").unwrap();
+        let err = migrate(deps.as_mut(), mock_env(), MigrateMsg { admin: None }).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidMigrationContract {}));
+
+        set_contract_version(&mut deps.storage, CONTRACT_NAME, "99.0.0").unwrap();
+        let err = migrate(deps.as_mut(), mock_env(), MigrateMsg { admin: None }).unwrap_err();
+        assert!(matches!(err, ContractError::MigrationDowngrade {}));
+
+        set_contract_version(&mut deps.storage, CONTRACT_NAME, "0.0.1").unwrap();
+        let msg = MigrateMsg {
+            admin: Some(deps.api.addr_make("admin").to_string()),
+        };
+        migrate(deps.as_mut(), mock_env(), msg).unwrap();
+        let version = get_contract_version(&deps.storage).unwrap();
+        assert_eq!(version.version, CONTRACT_VERSION);
+    }
+
+    #[test]
+    fn migrate_again_is_a_no_op() {
+        let mut deps = mock_dependencies();
+        set_contract_version(&mut deps.storage, CONTRACT_NAME, "0.1.0").unwrap();
+        let profile = migrations::v0_2_0::v0_1::Profile {
+            id: 0,
+            addr: addr("alice"),
+            user_name: "alice".to_string(),
+            about: "about".to_string(),
+            image: "image".to_string(),
+            followers: vec![],
+            following: vec![],
+            created_at: 0,
+            updated_at: 0,
+        };
+        migrations::v0_2_0::v0_1::PROFILES
+            .save(&mut deps.storage, "alice".to_string(), &profile)
+            .unwrap();
+        let msg = MigrateMsg {
+            admin: Some(deps.api.addr_make("admin").to_string()),
+        };
+        let res = migrate(deps.as_mut(), mock_env(), msg).unwrap();
+        assert_eq!(res.attributes.len(), 6);
+
+        let snapshot = |storage: &MockStorage| {
+            storage
+                .range(None, None, Order::Ascending)
+                .collect::<Vec<_>>()
+        };
+        let before = snapshot(&deps.storage);
+        let res = migrate(deps.as_mut(), mock_env(), MigrateMsg { admin: None }).unwrap();
+        assert_eq!(res.attributes.len(), 3);
+        assert_eq!(res.attributes[1].value, CONTRACT_VERSION);
+        assert_eq!(snapshot(&deps.storage), before);
     }
 }
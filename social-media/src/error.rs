@@ -9,14 +9,107 @@ pub enum ContractError {
     #[error("Unauthorized")]
     Unauthorized {},
 
+    #[error("AdminRequired")]
+    AdminRequired {},
+
+    #[error("InvalidMigrationContract")]
+    InvalidMigrationContract {},
+
+    #[error("MigrationDowngrade")]
+    MigrationDowngrade {},
+
+    #[error("InvalidVersion")]
+    InvalidVersion {},
+
     #[error("AboutTooLong")]
     AboutTooLong {},
 
+    #[error("ImageUrlTooLong")]
+    ImageUrlTooLong {},
+
+    #[error("InvalidImageUrl")]
+    InvalidImageUrl {},
+
+    #[error("TitleTooLong")]
+    TitleTooLong {},
+
+    #[error("ContentTooLong")]
+    ContentTooLong {},
+
+    #[error("CommentTooLong")]
+    CommentTooLong {},
+
+    #[error("ControlCharacter")]
+    ControlCharacter {},
+
     #[error("ProfileNotFound")]
     ProfileNotFound {},
 
     #[error("PostNotFound")]
     PostNotFound {},
+
+    #[error("Banned")]
+    Banned {},
+
+    #[error("AlreadyReported")]
+    AlreadyReported {},
+
+    #[error("ReportNotFound")]
+    ReportNotFound {},
+
+    #[error("ReasonTooLong")]
+    ReasonTooLong {},
+
+    #[error("NoFunds")]
+    NoFunds {},
+
+    #[error("InvalidZeroAmount")]
+    InvalidZeroAmount {},
+
+    #[error("InvalidTipFee")]
+    InvalidTipFee {},
+
+    #[error("InvalidLimits")]
+    InvalidLimits {},
+
+    #[error("CannotTipOwnPost")]
+    CannotTipOwnPost {},
+
+    #[error("UsernameTaken")]
+    UsernameTaken {},
+
+    #[error("UsernameReserved")]
+    UsernameReserved {},
+
+    #[error("UsernameChangeCooldown")]
+    UsernameChangeCooldown {},
+
+    #[error("InvalidUsernameLength")]
+    InvalidUsernameLength {},
+
+    #[error("InvalidUsernameCharacter")]
+    InvalidUsernameCharacter {},
+
+    #[error("ProfileAlreadyExists")]
+    ProfileAlreadyExists {},
+
+    #[error("CannotFollowSelf")]
+    CannotFollowSelf {},
+
+    #[error("AlreadyFollowing")]
+    AlreadyFollowing {},
+
+    #[error("NotFollowing")]
+    NotFollowing {},
+
+    #[error("AlreadyLiked")]
+    AlreadyLiked {},
+
+    #[error("NotLiked")]
+    NotLiked {},
+
+    #[error("CommentNotFound")]
+    CommentNotFound {},
     // Add any other custom errors you like here.
     // Look at https://docs.rs/thiserror/1.0.21/thiserror/ for details.
 }
@@ -0,0 +1,319 @@
+//! Ordered state migrations, one module per version that changed the storage layout.
+//!
+//! Each step reads the state written by the previous version and rewrites it in the
+//! shape the next version expects. `contract::migrate` runs every step newer than the
+//! stored contract version, oldest first.
+
+pub mod v0_2_0 {
+    use cosmwasm_std::{attr, Addr, Attribute, Decimal, Deps, DepsMut, Env, Order, StdResult};
+
+    use crate::contract::execute::add_follow;
+    use crate::state::{
+        Comment, Config, Index, Limits, Post, Profile, Stats, ADDR_TO_USERNAME, AUTHOR_POSTS,
+        COMMENTS, COMMENT_SEQ, CONFIG, FOLLOWING, LIKES, POSTS, PROFILES, PROFILE_INDEX, STATS,
+    };
+    use crate::ContractError;
+
+    /// Layout written by v0.1.x
+    pub mod v0_1 {
+        use cosmwasm_std::Addr;
+        use cw_storage_plus::Map;
+        use schemars::JsonSchema;
+        use serde::{Deserialize, Serialize};
+
+        #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+        pub struct Profile {
+            pub id: u64,
+            pub addr: Addr,
+            pub user_name: String,
+            pub about: String,
+            pub image: String,
+            pub followers: Vec<String>,
+            pub following: Vec<String>,
+            pub created_at: u64,
+            pub updated_at: u64,
+        }
+
+        #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+        pub struct Post {
+            pub id: u64,
+            pub title: String,
+            pub content: String,
+            pub author: Addr,
+            pub likes: u64,
+            pub likers: Vec<Addr>,
+            pub comments: Vec<(Addr, String)>,
+            pub created_at: u64,
+            pub updated_at: u64,
+        }
+
+        pub const PROFILES: Map<String, Profile> = Map::new("profiles");
+        pub const POSTS: Map<u64, Post> = Map::new("posts");
+        pub const USER_POSTS: Map<String, Vec<u64>> = Map::new("user_posts");
+    }
+
+    /// v0.1 had no config, so `admin` is required
+    pub fn migrate(
+        deps: DepsMut,
+        env: &Env,
+        admin: Option<String>,
+    ) -> Result<Vec<Attribute>, ContractError> {
+        let admin = admin.ok_or(ContractError::AdminRequired {})?;
+        let config = Config {
+            admin: deps.api.addr_validate(&admin)?,
+            limits: Limits::default(),
+            tip_fee: Decimal::zero(),
+        };
+        CONFIG.save(deps.storage, &config)?;
+
+        // v0.1 never initialized the profile index, so no profile could be created
+        if !PROFILE_INDEX.exists(deps.storage) {
+            PROFILE_INDEX.save(deps.storage, &Index { current_index: 0 })?;
+        }
+
+        // Rewrite the profiles without their follow lists and bind them to their address
+        let profiles = v0_1::PROFILES
+            .range(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        for (user_name, old) in profiles.iter() {
+            let profile = Profile {
+                id: old.id,
+                addr: old.addr.clone(),
+                user_name: old.user_name.clone(),
+                about: old.about.clone(),
+                image: old.image.clone(),
+                created_at: old.created_at,
+                updated_at: old.updated_at,
+            };
+            PROFILES.save(deps.storage, user_name.clone(), &profile)?;
+            // An address could own several profiles, it keeps the first by name
+            if !ADDR_TO_USERNAME.has(deps.storage, &old.addr) {
+                ADDR_TO_USERNAME.save(deps.storage, &old.addr, user_name)?;
+            }
+        }
+
+        // Either side's list is enough to make an edge, entries for missing profiles,
+        // profiles whose address kept another one or the profile itself are dropped
+        let mut edges = 0u64;
+        for (user_name, old) in profiles.iter() {
+            let follows_out = old
+                .following
+                .iter()
+                .map(|following| (user_name.clone(), following.clone()));
+            let follows_in = old
+                .followers
+                .iter()
+                .map(|follower| (follower.clone(), user_name.clone()));
+            for (follower, following) in follows_out.chain(follows_in) {
+                let (Some(follower), Some(following)) = (
+                    bound_addr(deps.as_ref(), follower)?,
+                    bound_addr(deps.as_ref(), following)?,
+                ) else {
+                    continue;
+                };
+                if follower == following || FOLLOWING.has(deps.storage, (&follower, &following)) {
+                    continue;
+                }
+                add_follow(deps.storage, &follower, &following, env.block.time)?;
+                edges += 1;
+            }
+        }
+
+        // Move likers and comments out of the posts and index posts by author
+        let posts = v0_1::POSTS
+            .range(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        let time = env.block.time.seconds();
+        let mut stats = Stats {
+            profiles: profiles.len() as u64,
+            posts: posts.len() as u64,
+            ..Stats::default()
+        };
+        for (id, old) in posts {
+            let mut post = Post {
+                id: old.id,
+                title: old.title,
+                content: old.content,
+                author: old.author,
+                likes: 0,
+                comments: 0,
+                hidden: false,
+                parent_id: None,
+                quote_of: None,
+                replies: 0,
+                deleted: false,
+                created_at: old.created_at,
+                updated_at: old.updated_at,
+            };
+            for liker in old.likers {
+                // Repeated likes collapse into one
+                if !LIKES.has(deps.storage, (id, &liker)) {
+                    LIKES.save(deps.storage, (id, &liker), &())?;
+                    post.likes += 1;
+                }
+            }
+            for (author, content) in old.comments {
+                let comment_id = COMMENT_SEQ.may_load(deps.storage)?.unwrap_or_default() + 1;
+                let comment = Comment {
+                    id: comment_id,
+                    post_id: id,
+                    author,
+                    content,
+                    created_at: time,
+                    updated_at: time,
+                };
+                COMMENT_SEQ.save(deps.storage, &comment_id)?;
+                COMMENTS.save(deps.storage, (id, comment_id), &comment)?;
+                post.comments += 1;
+            }
+
+            AUTHOR_POSTS.save(deps.storage, (&post.author, id), &())?;
+            POSTS.save(deps.storage, id, &post)?;
+            stats.likes += post.likes;
+            stats.comments += post.comments;
+        }
+        STATS.save(deps.storage, &stats)?;
+
+        // AUTHOR_POSTS replaces the per-user post lists
+        let users = v0_1::USER_POSTS
+            .keys(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        for user in users {
+            v0_1::USER_POSTS.remove(deps.storage, user);
+        }
+
+        Ok(vec![
+            attr("follow_edges", edges.to_string()),
+            attr("likes", stats.likes.to_string()),
+            attr("comments", stats.comments.to_string()),
+        ])
+    }
+
+    // Address of the profile named `user_name`, unless the profile is missing or its
+    // address kept another profile
+    fn bound_addr(deps: Deps, user_name: String) -> StdResult<Option<Addr>> {
+        let Some(profile) = PROFILES.may_load(deps.storage, user_name.clone())? else {
+            return Ok(None);
+        };
+        let bound = ADDR_TO_USERNAME.may_load(deps.storage, &profile.addr)?;
+        Ok((bound == Some(user_name)).then_some(profile.addr))
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use cosmwasm_std::testing::{mock_dependencies, mock_env};
+        use cosmwasm_std::Addr;
+
+        use super::*;
+        use crate::state::{FollowCounts, FOLLOW_COUNTS};
+
+        fn old_profile(
+            user_name: &str,
+            followers: Vec<&str>,
+            following: Vec<&str>,
+        ) -> v0_1::Profile {
+            v0_1::Profile {
+                id: 0,
+                addr: Addr::unchecked(user_name),
+                user_name: user_name.to_string(),
+                about: "about".to_string(),
+                image: "image".to_string(),
+                followers: followers.into_iter().map(String::from).collect(),
+                following: following.into_iter().map(String::from).collect(),
+                created_at: 0,
+                updated_at: 0,
+            }
+        }
+
+        #[test]
+        fn moves_embedded_follows() {
+            let mut deps = mock_dependencies();
+            for profile in [
+                old_profile("alice", vec!["bob"], vec!["bob", "carol"]),
+                // Duplicate follower entries are collapsed
+                old_profile("bob", vec!["alice", "alice"], vec!["alice"]),
+                old_profile("carol", vec!["alice"], vec![]),
+            ] {
+                v0_1::PROFILES
+                    .save(&mut deps.storage, profile.user_name.clone(), &profile)
+                    .unwrap();
+            }
+
+            let admin = deps.api.addr_make("admin").to_string();
+            let res = migrate(deps.as_mut(), &mock_env(), Some(admin)).unwrap();
+            assert_eq!(res[0].value, "3");
+            let [alice, bob, carol] = ["alice", "bob", "carol"].map(Addr::unchecked);
+            assert!(FOLLOWING.has(&deps.storage, (&alice, &bob)));
+            assert!(FOLLOWING.has(&deps.storage, (&alice, &carol)));
+            assert!(FOLLOWING.has(&deps.storage, (&bob, &alice)));
+            assert!(!FOLLOWING.has(&deps.storage, (&carol, &alice)));
+            let counts = FOLLOW_COUNTS.load(&deps.storage, &alice).unwrap();
+            assert_eq!(
+                counts,
+                FollowCounts {
+                    followers: 1,
+                    following: 2
+                }
+            );
+
+            let profile = PROFILES.load(&deps.storage, "alice".to_string()).unwrap();
+            assert_eq!(profile.user_name, "alice");
+            let user_name = ADDR_TO_USERNAME.load(&deps.storage, &bob).unwrap();
+            assert_eq!(user_name, "bob");
+            assert_eq!(STATS.load(&deps.storage).unwrap().profiles, 3);
+        }
+
+        #[test]
+        fn moves_embedded_likes_and_comments() {
+            let mut deps = mock_dependencies();
+            let old = v0_1::Post {
+                id: 1,
+                title: "title".to_string(),
+                content: "content".to_string(),
+                author: Addr::unchecked("alice"),
+                likes: 3,
+                likers: vec![
+                    Addr::unchecked("bob"),
+                    Addr::unchecked("carol"),
+                    Addr::unchecked("bob"),
+                ],
+                comments: vec![
+                    (Addr::unchecked("bob"), "first".to_string()),
+                    (Addr::unchecked("carol"), "second".to_string()),
+                ],
+                created_at: 1,
+                updated_at: 2,
+            };
+            v0_1::POSTS.save(&mut deps.storage, 1, &old).unwrap();
+            v0_1::USER_POSTS
+                .save(&mut deps.storage, "alice".to_string(), &vec![1])
+                .unwrap();
+
+            let admin = deps.api.addr_make("admin").to_string();
+            let res = migrate(deps.as_mut(), &mock_env(), Some(admin)).unwrap();
+            assert_eq!((res[1].value.as_str(), res[2].value.as_str()), ("2", "2"));
+
+            let post = POSTS.load(&deps.storage, 1).unwrap();
+            assert_eq!((post.likes, post.comments), (2, 2));
+            assert_eq!(post.updated_at, 2);
+            assert!(LIKES.has(&deps.storage, (1, &Addr::unchecked("carol"))));
+            assert!(AUTHOR_POSTS.has(&deps.storage, (&Addr::unchecked("alice"), 1)));
+            assert!(v0_1::USER_POSTS.is_empty(&deps.storage));
+            let comment = COMMENTS.load(&deps.storage, (1, 2)).unwrap();
+            assert_eq!(
+                (comment.author, comment.content),
+                (Addr::unchecked("carol"), "second".to_string())
+            );
+            assert_eq!(COMMENT_SEQ.load(&deps.storage).unwrap(), 2);
+            let stats = STATS.load(&deps.storage).unwrap();
+            assert_eq!((stats.posts, stats.likes, stats.comments), (1, 2, 2));
+        }
+
+        #[test]
+        fn requires_an_admin() {
+            let mut deps = mock_dependencies();
+            let err = migrate(deps.as_mut(), &mock_env(), None).unwrap_err();
+            assert!(matches!(err, ContractError::AdminRequired {}));
+        }
+    }
+}
@@ -1,11 +1,35 @@
-use crate::state::{Post, PostId, Profile};
+use crate::state::{
+    Comment, CommentId, Config, Limits, Post, PostId, Profile, Report, Stats, TokenAmount,
+    UsernameChange,
+};
 use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{Addr, Decimal, Timestamp, Uint128};
+use cw20::{Cw20ReceiveMsg, Denom};
 
 #[cw_serde]
-pub struct InstantiateMsg {}
+pub struct InstantiateMsg {
+    // Defaults to the sender
+    pub admin: Option<String>,
+    // Defaults to `Limits::default()`
+    pub limits: Option<Limits>,
+    // Share of every tip sent to the admin, defaults to zero
+    pub tip_fee: Option<Decimal>,
+}
+
+#[cw_serde]
+pub struct MigrateMsg {
+    // Required when migrating from v0.1, which had no config
+    pub admin: Option<String>,
+}
 
 #[cw_serde]
 pub enum ExecuteMsg {
+    // Admin only
+    UpdateConfig {
+        admin: Option<String>,
+        limits: Option<Limits>,
+        tip_fee: Option<Decimal>,
+    },
     CreateProfile {
         user_name: String,
         about: String,
@@ -16,17 +40,24 @@ pub enum ExecuteMsg {
         about: String,
         image_url: String,
     },
+    // Renames the sender's profile, the old name stays reserved for them for a while.
+    // A profile can be renamed once every 30 days.
+    ChangeUsername {
+        new_user_name: String,
+    },
+    // Follow and Unfollow act on the sender's profile
     Follow {
-        my_user_name: String,
         following_user_name: String,
     },
     Unfollow {
-        my_user_name: String,
         following_user_name: String,
     },
+    // Set `parent_id` to reply to a post and `quote_of` to quote one
     CreatePost {
         title: String,
         content: String,
+        parent_id: Option<PostId>,
+        quote_of: Option<PostId>,
     },
     UpdatePost {
         id: u64,
@@ -36,22 +67,117 @@ pub enum ExecuteMsg {
     LikePost {
         id: u64,
     },
+    UnlikePost {
+        id: u64,
+    },
     Comment {
         id: u64,
         comment: String,
     },
+    // Only the comment author can edit
+    EditComment {
+        id: u64,
+        comment_id: CommentId,
+        comment: String,
+    },
+    // The comment or the post author can delete
+    DeleteComment {
+        id: u64,
+        comment_id: CommentId,
+    },
     DeletePost {
         id: u64,
     },
+    // Admin only
+    AddModerator {
+        address: String,
+    },
+    RemoveModerator {
+        address: String,
+    },
+    ReportPost {
+        id: u64,
+        reason: String,
+    },
+    ReportComment {
+        id: u64,
+        comment_id: CommentId,
+        reason: String,
+    },
+    // Moderators only
+    ResolveReport {
+        report_id: u64,
+    },
+    HidePost {
+        id: u64,
+    },
+    UnhidePost {
+        id: u64,
+    },
+    // Forwards the attached funds to the post author, minus the tip fee
+    TipPost {
+        id: u64,
+    },
+    // CW20 tips, see `ReceiveMsg`
+    Receive(Cw20ReceiveMsg),
+    // Banned users can't post, comment or like
+    BanUser {
+        address: String,
+    },
+    UnbanUser {
+        address: String,
+    },
+}
+
+// Hooks for CW20 tokens sent with `Send`
+#[cw_serde]
+pub enum ReceiveMsg {
+    TipPost { id: u64 },
 }
 
 #[cw_serde]
 #[derive(QueryResponses)]
 pub enum QueryMsg {
-    // CurrentIndex returns the current index as a json-encoded u64
+    // CurrentIndex returns the current post index as a json-encoded u64
     #[returns(GetIndexResponse)]
     CurrentIndex {},
 
+    #[returns(ConfigResponse)]
+    Config {},
+
+    // Moderators returns the moderators besides the admin
+    #[returns(ModeratorsResponse)]
+    Moderators {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    #[returns(IsBannedResponse)]
+    IsBanned { address: String },
+
+    // Reports returns the open reports, oldest first
+    #[returns(ReportsResponse)]
+    Reports {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+
+    // PostTips returns the tips a post received, per token and before fees
+    #[returns(TipsResponse)]
+    PostTips { id: u64 },
+
+    // TopTippedPosts returns the posts with the most tips in a token, highest first
+    #[returns(TopTippedPostsResponse)]
+    TopTippedPosts { denom: Denom, limit: Option<u32> },
+
+    // AuthorEarnings returns the tips an author received, per token and after fees
+    #[returns(TipsResponse)]
+    AuthorEarnings { address: String },
+
+    // Stats returns the number of profiles, posts, comments and likes
+    #[returns(StatsResponse)]
+    Stats {},
+
     // GetPost returns the profile as a json-encoded Post
     #[returns(GetProfileResponse)]
     GetProfile { user_name: String },
@@ -60,9 +186,83 @@ pub enum QueryMsg {
     #[returns(GetPostResponse)]
     GetPost { id: u64 },
 
-    // GetPost returns the users post as a json-encoded Posts
+    // GetUserPosts returns the ids of the posts `user` (an address) wrote, oldest first
     #[returns(GetUserPostsResponse)]
-    GetUserPosts { user: String },
+    GetUserPosts {
+        user: String,
+        start_after: Option<PostId>,
+        limit: Option<u32>,
+    },
+
+    // UsernameHistory returns the renames of a profile, oldest first
+    #[returns(UsernameHistoryResponse)]
+    UsernameHistory {
+        user_name: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+
+    // Followers returns the profiles following a user, ordered by address
+    #[returns(FollowersResponse)]
+    Followers {
+        user_name: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    // Following returns the profiles a user follows, ordered by address
+    #[returns(FollowingResponse)]
+    Following {
+        user_name: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    // IsFollowing returns whether follower follows following, and since when
+    #[returns(IsFollowingResponse)]
+    IsFollowing { follower: String, following: String },
+
+    // Comments returns the comments on a post, oldest first
+    #[returns(CommentsResponse)]
+    Comments {
+        id: u64,
+        start_after: Option<CommentId>,
+        limit: Option<u32>,
+    },
+
+    // Feed returns posts by the profiles a user follows, newest first.
+    // Only the first 100 followed profiles, by address, are included, `truncated`
+    // tells when more were left out.
+    #[returns(FeedResponse)]
+    Feed {
+        user_name: String,
+        start_after: Option<PostId>,
+        limit: Option<u32>,
+    },
+
+    // GlobalFeed returns all posts, newest first
+    #[returns(FeedResponse)]
+    GlobalFeed {
+        start_after: Option<PostId>,
+        limit: Option<u32>,
+    },
+
+    // Thread returns a post and its replies as a tree, oldest replies first. Replies are
+    // collected level by level until `depth` levels or `limit` replies are reached.
+    #[returns(ThreadResponse)]
+    Thread {
+        root_id: u64,
+        depth: Option<u32>,
+        limit: Option<u32>,
+    },
+
+    // Likers returns the addresses that liked a post
+    #[returns(LikersResponse)]
+    Likers {
+        id: u64,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
 }
 
 // We define a custom struct for each query response
@@ -75,6 +275,8 @@ pub struct GetIndexResponse {
 #[cw_serde]
 pub struct GetProfileResponse {
     pub profile: Profile,
+    pub followers: u64,
+    pub following: u64,
 }
 // We define a custom struct for each query response
 #[cw_serde]
@@ -87,3 +289,101 @@ pub struct GetPostResponse {
 pub struct GetUserPostsResponse {
     pub posts: Vec<PostId>,
 }
+
+#[cw_serde]
+pub struct Follow {
+    pub addr: Addr,
+    pub user_name: String,
+    pub since: Timestamp,
+}
+
+#[cw_serde]
+pub struct FollowersResponse {
+    pub followers: Vec<Follow>,
+}
+
+#[cw_serde]
+pub struct FollowingResponse {
+    pub following: Vec<Follow>,
+}
+
+#[cw_serde]
+pub struct IsFollowingResponse {
+    pub is_following: bool,
+    pub since: Option<Timestamp>,
+}
+
+#[cw_serde]
+pub struct CommentsResponse {
+    pub comments: Vec<Comment>,
+}
+
+#[cw_serde]
+pub struct LikersResponse {
+    pub likers: Vec<Addr>,
+}
+
+#[cw_serde]
+pub struct FeedResponse {
+    pub posts: Vec<Post>,
+    // Set when followed profiles were left out of the feed
+    pub truncated: bool,
+}
+
+#[cw_serde]
+pub struct StatsResponse {
+    pub stats: Stats,
+}
+
+#[cw_serde]
+pub struct UsernameHistoryResponse {
+    pub changes: Vec<UsernameChange>,
+}
+
+#[cw_serde]
+pub struct ConfigResponse {
+    pub config: Config,
+}
+
+#[cw_serde]
+pub struct ModeratorsResponse {
+    pub moderators: Vec<Addr>,
+}
+
+#[cw_serde]
+pub struct IsBannedResponse {
+    pub is_banned: bool,
+}
+
+#[cw_serde]
+pub struct ReportsResponse {
+    pub reports: Vec<Report>,
+}
+
+#[cw_serde]
+pub struct TipsResponse {
+    pub tips: Vec<TokenAmount>,
+}
+
+#[cw_serde]
+pub struct TippedPost {
+    pub id: PostId,
+    pub author: Addr,
+    pub amount: Uint128,
+}
+
+#[cw_serde]
+pub struct TopTippedPostsResponse {
+    pub posts: Vec<TippedPost>,
+}
+
+#[cw_serde]
+pub struct ThreadNode {
+    pub post: Post,
+    pub children: Vec<ThreadNode>,
+}
+
+#[cw_serde]
+pub struct ThreadResponse {
+    pub thread: ThreadNode,
+}
@@ -1,17 +1,129 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::Addr;
+use cosmwasm_std::{Addr, Decimal, Timestamp, Uint128};
+use cw20::Denom;
 use cw_storage_plus::{Item, Map};
 
 pub type PostId = u64;
+pub type CommentId = u64;
 pub type Username = String;
 
+// Usernames are 3 to 20 characters of a-z, 0-9 and underscore
+pub const MIN_USERNAME_LENGTH: usize = 3;
+pub const MAX_USERNAME_LENGTH: usize = 20;
+
+// How long a released username stays reserved for its previous owner (30 days)
+pub const USERNAME_RESERVATION_SECONDS: u64 = 60 * 60 * 24 * 30;
+
+// Profiles can be renamed once per reservation period, so an address holds at most one
+// reserved name besides its current one
+pub const USERNAME_CHANGE_COOLDOWN_SECONDS: u64 = USERNAME_RESERVATION_SECONDS;
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 pub struct Index {
     pub current_index: u64,
 }
 
+// Image URLs must use one of these schemes
+pub const IMAGE_URL_SCHEMES: [&str; 2] = ["https://", "ipfs://"];
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct Config {
+    // May update the config
+    pub admin: Addr,
+    pub limits: Limits,
+    // Share of every tip sent to the admin
+    #[serde(default)]
+    pub tip_fee: Decimal,
+}
+
+// Maximum lengths of user supplied text, in bytes. Each must be between 1 and
+// `MAX_TEXT_LENGTH`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct Limits {
+    pub max_about_length: u32,
+    pub max_image_url_length: u32,
+    pub max_title_length: u32,
+    pub max_content_length: u32,
+    pub max_comment_length: u32,
+    #[serde(default = "default_max_reason_length")]
+    pub max_reason_length: u32,
+}
+
+// Upper bound for any of the limits, so a post with its title stays well inside a
+// storage value
+pub const MAX_TEXT_LENGTH: u32 = 20_000;
+
+fn default_max_reason_length() -> u32 {
+    500
+}
+
+impl Limits {
+    pub fn is_valid(&self) -> bool {
+        [
+            self.max_about_length,
+            self.max_image_url_length,
+            self.max_title_length,
+            self.max_content_length,
+            self.max_comment_length,
+            self.max_reason_length,
+        ]
+        .iter()
+        .all(|length| (1..=MAX_TEXT_LENGTH).contains(length))
+    }
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            max_about_length: 240,
+            max_image_url_length: 256,
+            max_title_length: 120,
+            max_content_length: 4_000,
+            max_comment_length: 1_000,
+            max_reason_length: default_max_reason_length(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TokenAmount {
+    pub denom: Denom,
+    pub amount: Uint128,
+}
+
+/// Storage key for a token, e.g. `native:untrn` or `cw20:<contract address>`.
+pub fn denom_key(denom: &Denom) -> String {
+    match denom {
+        Denom::Native(denom) => format!("native:{}", denom),
+        Denom::Cw20(addr) => format!("cw20:{}", addr),
+    }
+}
+
+// A released username only its previous owner can claim until `expires_at`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct Reservation {
+    pub addr: Addr,
+    pub expires_at: Timestamp,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct UsernameChange {
+    pub old_user_name: Username,
+    pub new_user_name: Username,
+    pub changed_at: Timestamp,
+}
+
+// Live totals, kept up to date by every handler that changes them
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, JsonSchema)]
+pub struct Stats {
+    pub profiles: u64,
+    pub posts: u64,
+    pub comments: u64,
+    pub likes: u64,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 pub struct Profile {
     pub id: u64,
@@ -19,12 +131,16 @@ pub struct Profile {
     pub user_name: String,
     pub about: String,
     pub image: String, // URL to image
-    pub followers: Vec<String>,
-    pub following: Vec<String>,
     pub created_at: u64,
     pub updated_at: u64,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, JsonSchema)]
+pub struct FollowCounts {
+    pub followers: u64,
+    pub following: u64,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 pub struct Post {
     pub id: u64,
@@ -32,14 +148,98 @@ pub struct Post {
     pub content: String,
     pub author: Addr,
     pub likes: u64,
-    pub likers: Vec<Addr>,
-    pub comments: Vec<(Addr, String)>,
+    pub comments: u64,
+    // Hidden by a moderator, left out of feeds
+    #[serde(default)]
+    pub hidden: bool,
+    // The post this one replies to
+    #[serde(default)]
+    pub parent_id: Option<PostId>,
+    // The post this one quotes
+    #[serde(default)]
+    pub quote_of: Option<PostId>,
+    #[serde(default)]
+    pub replies: u64,
+    // Deleted while it still had replies, kept without content to hold the thread together
+    #[serde(default)]
+    pub deleted: bool,
     pub created_at: u64,
     pub updated_at: u64,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct Comment {
+    pub id: CommentId,
+    pub post_id: PostId,
+    pub author: Addr,
+    pub content: String,
+    pub created_at: u64,
+    pub updated_at: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportTarget {
+    Post { id: PostId },
+    Comment { id: PostId, comment_id: CommentId },
+}
+
+impl ReportTarget {
+    // Key for deduplicating reports, comment ids start at 1
+    pub fn key(&self) -> (PostId, CommentId) {
+        match self {
+            ReportTarget::Post { id } => (*id, 0),
+            ReportTarget::Comment { id, comment_id } => (*id, *comment_id),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct Report {
+    pub id: u64,
+    pub target: ReportTarget,
+    pub reporter: Addr,
+    pub reason: String,
+    pub created_at: u64,
+}
+
+pub const CONFIG: Item<Config> = Item::new("config");
+// Moderators besides the admin, who always moderates
+pub const MODERATORS: Map<&Addr, ()> = Map::new("moderators");
+pub const BANNED: Map<&Addr, ()> = Map::new("banned");
+// Open reports, removed once resolved
+pub const REPORTS: Map<u64, Report> = Map::new("reports");
+pub const REPORT_SEQ: Item<u64> = Item::new("report_seq");
+// Report ids per (reporter, post id, comment id), one report per target and reporter
+pub const REPORTED: Map<(&Addr, PostId, CommentId), u64> = Map::new("reported");
+// Tips per (post id, token key), before fees
+pub const POST_TIPS: Map<(PostId, &str), TokenAmount> = Map::new("post_tips");
+// Posts ranked by tips per (token key, total, post id)
+pub const TIP_RANKING: Map<(&str, u128, PostId), ()> = Map::new("tip_ranking");
+// Tips per (author, token key), after fees
+pub const AUTHOR_EARNINGS: Map<(&Addr, &str), TokenAmount> = Map::new("author_earnings");
 pub const PROFILE_INDEX: Item<Index> = Item::new("profile_index");
 pub const POST_INDEX: Item<Index> = Item::new("post_index");
+pub const STATS: Item<Stats> = Item::new("stats");
 pub const PROFILES: Map<Username, Profile> = Map::new("profiles");
+// Reverse index from an address to its (single) profile
+pub const ADDR_TO_USERNAME: Map<&Addr, Username> = Map::new("addr_to_username");
+pub const RESERVED_USERNAMES: Map<Username, Reservation> = Map::new("reserved_usernames");
+// Username changes per address, numbered from 0
+pub const USERNAME_HISTORY: Map<(&Addr, u64), UsernameChange> = Map::new("username_history");
+// Follow edges with the time they were made, keyed by profile address (follower, followed)
+// so renames leave them alone
+pub const FOLLOWING: Map<(&Addr, &Addr), Timestamp> = Map::new("following");
+// The same edges keyed (followed, follower)
+pub const FOLLOWERS: Map<(&Addr, &Addr), Timestamp> = Map::new("followers");
+pub const FOLLOW_COUNTS: Map<&Addr, FollowCounts> = Map::new("follow_counts");
 pub const POSTS: Map<PostId, Post> = Map::new("posts");
-pub const USER_POSTS: Map<String, Vec<PostId>> = Map::new("user_posts");
+pub const LIKES: Map<(PostId, &Addr), ()> = Map::new("likes");
+// Replies per parent post, oldest first
+pub const REPLIES: Map<(PostId, PostId), ()> = Map::new("replies");
+pub const COMMENTS: Map<(PostId, CommentId), Comment> = Map::new("comments");
+// Last comment id handed out, shared by all posts
+pub const COMMENT_SEQ: Item<CommentId> = Item::new("comment_seq");
+// Posts per author. Post ids grow with block time, so each author's range is in
+// chronological order.
+pub const AUTHOR_POSTS: Map<(&Addr, PostId), ()> = Map::new("author_posts");